@@ -0,0 +1,130 @@
+use std::{fmt, thread::{self, JoinHandle}, time::{Duration, Instant}};
+
+use crate::{save::SaveError, save_slots::write_save};
+
+/// How often the game autosaves, unless the player picks otherwise.
+pub const DEFAULT_AUTOSAVE_INTERVAL: Duration = Duration::from_secs(300);
+
+/// # Autosave Event
+/// 
+/// Something that happened which is worth saving straight after.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutosaveEvent {
+    /// A bet, or part of one, was settled.
+    BetSettled,
+    /// The player was kicked out of a game.
+    Kickout,
+    /// The machine was upgraded.
+    MachineUpgrade,
+    /// A contract from the machine was met or missed.
+    ContractEnded,
+    /// The player left a game.
+    LeftGame,
+    /// The autosave interval ran out.
+    Interval,
+}
+
+impl fmt::Display for AutosaveEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AutosaveEvent::BetSettled => write!(f, "a bet settled"),
+            AutosaveEvent::Kickout => write!(f, "a kickout"),
+            AutosaveEvent::MachineUpgrade => write!(f, "a machine upgrade"),
+            AutosaveEvent::ContractEnded => write!(f, "a contract ending"),
+            AutosaveEvent::LeftGame => write!(f, "leaving a game"),
+            AutosaveEvent::Interval => write!(f, "the autosave interval"),
+        }
+    }
+}
+
+/// # Autosave
+/// 
+/// Keeps track of when the game should next save itself, and the save being written.
+/// 
+/// Saves are made after significant events, and whenever the interval runs out
+/// without one. The save is written on its own thread, so neither frontend waits on
+/// the disk. Only one is written at a time, anything asked for meanwhile waits for
+/// the next poll.
+pub struct Autosave {
+    /// How long to go without a save before making one. Zero turns autosave off,
+    /// events included.
+    pub interval: Duration,
+    /// When the last save, auto or not, was made.
+    pub last_save: Instant,
+    /// The event waiting to be saved after, if there is one.
+    pub pending: Option<AutosaveEvent>,
+    /// The save being written, and what it was made for.
+    writing: Option<(AutosaveEvent, JoinHandle<Result<(), SaveError>>)>,
+}
+
+impl Default for Autosave {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Autosave {
+    pub fn new() -> Self {
+        Self {
+            interval: DEFAULT_AUTOSAVE_INTERVAL,
+            last_save: Instant::now(),
+            pending: None,
+            writing: None,
+        }
+    }
+
+    /// # Enabled
+    pub fn enabled(&self) -> bool {
+        !self.interval.is_zero()
+    }
+
+    /// # Request
+    /// 
+    /// Asks for a save after the event, made on the next poll.
+    pub fn request(&mut self, event: AutosaveEvent) {
+        self.pending = Some(event);
+    }
+
+    /// # Saved
+    /// 
+    /// Notes a save was just made, so the interval starts over.
+    pub fn saved(&mut self, now: Instant) {
+        self.last_save = now;
+        self.pending = None;
+    }
+
+    /// # Due
+    /// 
+    /// What a save is due for right now, if one is. Nothing is due while a save is
+    /// still being written.
+    pub fn due(&self, now: Instant) -> Option<AutosaveEvent> {
+        if !self.enabled() || self.writing.is_some() {
+            return None;
+        }
+        self.pending.or_else(|| (now - self.last_save >= self.interval)
+            .then_some(AutosaveEvent::Interval))
+    }
+
+    /// # Start
+    /// 
+    /// Writes the save to the slot on its own thread.
+    pub fn start(&mut self, event: AutosaveEvent, slot: String, save: String, backups: usize,
+    now: Instant) {
+        self.saved(now);
+        let handle = thread::spawn(move || write_save(&slot, &save, backups));
+        self.writing = Some((event, handle));
+    }
+
+    /// # Finished
+    /// 
+    /// Once the save being written is done, what it was made for and how it went.
+    pub fn finished(&mut self) -> Option<(AutosaveEvent, Result<(), SaveError>)> {
+        if !self.writing.as_ref().is_some_and(|(_, handle)| handle.is_finished()) {
+            return None;
+        }
+        let (event, handle) = self.writing.take()?;
+        let result = handle.join()
+            .unwrap_or_else(|_| Err(SaveError::Io(String::from("The autosave stopped partway."))));
+        Some((event, result))
+    }
+}
@@ -0,0 +1,407 @@
+use std::{cmp::Ordering, time::{Duration, Instant}};
+
+use rand::Rng;
+
+use crate::{cards::deck::{Card, Deck}, game::{entropy, EarlyExit, GameCommonData}, money::Money};
+
+/// The commission the house takes from banker wins.
+pub const BANKER_COMMISSION: f64 = 0.05;
+/// When the shoe gets this low, the cut card is out and it's reshuffled.
+pub const BACCARAT_RESHUFFLE_AT: usize = 10;
+
+/// # Baccarat Bet
+/// 
+/// Which hand the player is backing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BaccaratBet {
+    Player,
+    Banker,
+    Tie,
+}
+
+impl BaccaratBet {
+    /// # Payout Rate
+    /// 
+    /// What a win returns per unit bet, stake included. Player pays 1:1, Banker 1:1
+    /// less commission, and Tie 8:1.
+    pub fn payout_rate(&self) -> f64 {
+        match self {
+            BaccaratBet::Player => 2.0,
+            BaccaratBet::Banker => 2.0 - BANKER_COMMISSION,
+            BaccaratBet::Tie => 9.0,
+        }
+    }
+
+    /// # Win Chance
+    /// 
+    /// The long run chance of the bet winning, from an 8 deck shoe.
+    pub fn win_chance(&self) -> f64 {
+        match self {
+            BaccaratBet::Player => 0.4462,
+            BaccaratBet::Banker => 0.4586,
+            BaccaratBet::Tie => 0.0952,
+        }
+    }
+
+    /// # Expected Return
+    /// 
+    /// The long run return per unit bet. Player and Banker bets push on a tie.
+    pub fn expected_return(&self) -> f64 {
+        let push = match self {
+            BaccaratBet::Tie => 0.0,
+            _ => BaccaratBet::Tie.win_chance(),
+        };
+        self.win_chance() * self.payout_rate() + push
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "p" => Some(BaccaratBet::Player),
+            "b" => Some(BaccaratBet::Banker),
+            "t" => Some(BaccaratBet::Tie),
+            _ => None,
+        }
+    }
+}
+
+/// # Baccarat Value
+/// 
+/// The value of a card in baccarat. Aces are 1, tens and faces are 0.
+pub fn baccarat_value(card: &Card) -> u8 {
+    let value = card.rank.value();
+    if value >= 10 { 0 } else { value }
+}
+
+/// # Hand Total
+/// 
+/// The total of a hand, the sum of its values mod 10.
+pub fn hand_total(hand: &[Card]) -> u8 {
+    hand.iter().map(baccarat_value).sum::<u8>() % 10
+}
+
+/// # Banker Draws
+/// 
+/// The banker's third card rule, given the banker's total and the player's third
+/// card, if the player drew one.
+pub fn banker_draws(banker_total: u8, player_third: Option<u8>) -> bool {
+    match player_third {
+        None => banker_total <= 5,
+        Some(third) => match banker_total {
+            0..=2 => true,
+            3 => third != 8,
+            4 => (2..=7).contains(&third),
+            5 => (4..=7).contains(&third),
+            6 => (6..=7).contains(&third),
+            _ => false,
+        },
+    }
+}
+
+/// # Third Cards
+/// 
+/// One way the third card draw can go, the value of each hand's third card (if drawn),
+/// how likely it is, and who it makes the winner.
+#[derive(Debug, Clone, Copy)]
+pub struct ThirdCards {
+    pub player: Option<u8>,
+    pub banker: Option<u8>,
+    pub chance: f64,
+    pub result: BaccaratBet,
+}
+
+/// # Baccarat
+/// 
+/// Punto banco. Back the player, the banker, or a tie, and the hands are drawn to
+/// fixed rules. Closest to 9 wins.
+#[derive(Debug)]
+pub struct Baccarat {
+    /// The shoe, either 1 or 8 decks.
+    pub deck: Deck,
+    pub player_hand: Vec<Card>,
+    pub banker_hand: Vec<Card>,
+    /// What the player is backing.
+    pub wager: BaccaratBet,
+    /// Whether the current hand's third cards were forced with entropy.
+    pub forced: bool,
+    /// The current state of the game.
+    pub state: BaccaratState,
+    /// Shared common Data
+    pub base: GameCommonData,
+}
+
+#[derive(Debug)]
+pub enum BaccaratState {
+    /// No active bet, the last hands are shown.
+    Hold,
+    /// The first four cards are out, and the third card draw is waiting.
+    InBet,
+}
+
+impl Baccarat {
+    /// Create a new Baccarat table with a single deck shoe.
+    pub fn new<R: Rng>(rng: &mut R) -> Self {
+        Self {
+            deck: Deck::new(rng, 1),
+            player_hand: vec![],
+            banker_hand: vec![],
+            wager: BaccaratBet::Banker,
+            forced: false,
+            state: BaccaratState::Hold,
+            // Surrendering before the third cards returns half the stake.
+            base: GameCommonData { early_exit: EarlyExit::Partial(0.5),
+                ..GameCommonData::new("Baccarat".to_string(), Money::from_pence(5),
+                    Money::from_pence(500), 0.0, Duration::from_secs(120)) },
+        }
+    }
+
+    /// # Set Shoe
+    /// 
+    /// Swaps to a fresh shoe of the given number of decks.
+    pub fn set_shoe<R: Rng>(&mut self, rng: &mut R, decks: u8) {
+        self.deck = Deck::new(rng, decks);
+    }
+
+    /// # Deal
+    /// 
+    /// Deals the first two cards to each hand, and starts the bet.
+    pub fn deal<R: Rng>(&mut self, rng: &mut R) {
+        if self.deck.remaining() <= BACCARAT_RESHUFFLE_AT {
+            self.deck.reshuffle(rng);
+        }
+        self.player_hand = vec![self.deck.draw(rng)];
+        self.banker_hand = vec![self.deck.draw(rng)];
+        self.player_hand.push(self.deck.draw(rng));
+        self.banker_hand.push(self.deck.draw(rng));
+        self.forced = false;
+        self.base.bet_start = Some(Instant::now());
+    }
+
+    /// # Natural
+    /// 
+    /// Whether either hand has a natural 8 or 9, which ends the hand.
+    pub fn natural(&self) -> bool {
+        hand_total(&self.player_hand[..2]) >= 8 || hand_total(&self.banker_hand[..2]) >= 8
+    }
+
+    /// # Result
+    /// 
+    /// The winner of the hands as they stand.
+    pub fn result(&self) -> BaccaratBet {
+        Baccarat::winner(hand_total(&self.player_hand), hand_total(&self.banker_hand))
+    }
+
+    fn winner(player: u8, banker: u8) -> BaccaratBet {
+        match player.cmp(&banker) {
+            Ordering::Greater => BaccaratBet::Player,
+            Ordering::Less => BaccaratBet::Banker,
+            Ordering::Equal => BaccaratBet::Tie,
+        }
+    }
+
+    /// # Third Card Outcomes
+    /// 
+    /// Every way the third card draw can go from the first four cards, with its exact
+    /// chance given the cards left in the shoe.
+    pub fn third_card_outcomes(&self) -> Vec<ThirdCards> {
+        let player = hand_total(&self.player_hand[..2]);
+        let banker = hand_total(&self.banker_hand[..2]);
+        if self.natural() {
+            return vec![ThirdCards { player: None, banker: None, chance: 1.0,
+                result: Baccarat::winner(player, banker) }];
+        }
+        let mut counts = [0u32; 10];
+        for card in self.deck.cards.iter() {
+            counts[baccarat_value(card) as usize] += 1;
+        }
+        let left = self.deck.remaining() as f64;
+        let mut outcomes = vec![];
+        if player <= 5 {
+            for value in 0..10 {
+                if counts[value] == 0 {
+                    continue;
+                }
+                let chance = counts[value] as f64 / left;
+                let mut after = counts;
+                after[value] -= 1;
+                Baccarat::banker_turn(player, banker, Some(value as u8), chance, &after,
+                    &mut outcomes);
+            }
+        } else {
+            Baccarat::banker_turn(player, banker, None, 1.0, &counts, &mut outcomes);
+        }
+        outcomes
+    }
+
+    /// The banker's side of the third card outcomes, once the player's draw is settled.
+    fn banker_turn(player: u8, banker: u8, player_third: Option<u8>, chance: f64,
+    counts: &[u32; 10], outcomes: &mut Vec<ThirdCards>) {
+        let player_total = (player + player_third.unwrap_or(0)) % 10;
+        if banker_draws(banker, player_third) {
+            let left: u32 = counts.iter().sum();
+            for (value, count) in counts.iter().enumerate().filter(|(_, c)| **c > 0) {
+                let total = (banker + value as u8) % 10;
+                outcomes.push(ThirdCards { player: player_third, banker: Some(value as u8),
+                    chance: chance * *count as f64 / left as f64,
+                    result: Baccarat::winner(player_total, total) });
+            }
+        } else {
+            outcomes.push(ThirdCards { player: player_third, banker: None, chance,
+                result: Baccarat::winner(player_total, banker) });
+        }
+    }
+
+    /// # Result Chance
+    /// 
+    /// The exact chance of the hand ending in the given result from here.
+    pub fn result_chance(&self, result: BaccaratBet) -> f64 {
+        self.third_card_outcomes().iter()
+            .filter(|o| o.result == result)
+            .map(|o| o.chance)
+            .sum::<f64>()
+            .min(1.0)
+    }
+
+    /// # Force Cost
+    /// 
+    /// The entropy cost to force the third card draw to give the result, the surprisal
+    /// of the result from here. None if no draw can give it.
+    pub fn force_cost(&self, result: BaccaratBet) -> Option<f64> {
+        let chance = self.result_chance(result);
+        if chance > 0.0 {
+            Some(entropy(chance))
+        } else {
+            None
+        }
+    }
+
+    /// # Draw Third Cards
+    /// 
+    /// Finishes the hands by the drawing rules. If a result is forced, the third cards
+    /// are picked from the draws that give it, weighted by their chance.
+    /// 
+    /// Returns the surprisal of the result the draw ended with.
+    pub fn draw_third_cards<R: Rng>(&mut self, rng: &mut R, force: Option<BaccaratBet>) -> f64 {
+        let outcomes = self.third_card_outcomes();
+        let (player_third, banker_third) = match force {
+            Some(result) => {
+                let matching: Vec<&ThirdCards> = outcomes.iter()
+                    .filter(|o| o.result == result).collect();
+                let total: f64 = matching.iter().map(|o| o.chance).sum();
+                let mut roll = rng.random_range(0.0..total);
+                let mut pick = matching[0];
+                for outcome in matching {
+                    pick = outcome;
+                    if roll < outcome.chance {
+                        break;
+                    }
+                    roll -= outcome.chance;
+                }
+                self.forced = true;
+                (pick.player.map(|v| self.draw_value(rng, v)),
+                    pick.banker.map(|v| self.draw_value(rng, v)))
+            },
+            None => {
+                let player = hand_total(&self.player_hand);
+                let banker = hand_total(&self.banker_hand);
+                if self.natural() {
+                    (None, None)
+                } else {
+                    let player_third = if player <= 5 { Some(self.deck.draw(rng)) } else { None };
+                    let banker_third = if banker_draws(banker,
+                        player_third.as_ref().map(baccarat_value)) {
+                        Some(self.deck.draw(rng))
+                    } else {
+                        None
+                    };
+                    (player_third, banker_third)
+                }
+            },
+        };
+        if let Some(card) = player_third {
+            self.player_hand.push(card);
+        }
+        if let Some(card) = banker_third {
+            self.banker_hand.push(card);
+        }
+        let result = self.result();
+        entropy(outcomes.iter().filter(|o| o.result == result).map(|o| o.chance).sum::<f64>()
+            .min(1.0))
+    }
+
+    /// Draws a card of the given value from anywhere in the shoe.
+    fn draw_value<R: Rng>(&mut self, rng: &mut R, value: u8) -> Card {
+        self.deck.draw_matching(rng, |c| baccarat_value(c) == value)
+            .unwrap_or_else(|| self.deck.draw(rng))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::deck::{Rank, Suit};
+
+    fn card(rank: Rank) -> Card {
+        Card { rank, suit: Suit::Clubs }
+    }
+
+    #[test]
+    fn totals_drop_the_tens() {
+        assert_eq!(hand_total(&[card(Rank::King), card(Rank::Nine)]), 9);
+        assert_eq!(hand_total(&[card(Rank::Seven), card(Rank::Eight)]), 5);
+        assert_eq!(hand_total(&[card(Rank::Ten), card(Rank::Queen), card(Rank::Ace)]), 1);
+    }
+
+    #[test]
+    fn banker_follows_the_third_card_rules() {
+        // Standing player, the banker draws on 5 or less.
+        assert!(banker_draws(5, None));
+        assert!(!banker_draws(6, None));
+        // Otherwise it depends on the player's third card.
+        assert!(banker_draws(2, Some(9)));
+        assert!(banker_draws(3, Some(9)));
+        assert!(!banker_draws(3, Some(8)));
+        assert!(!banker_draws(4, Some(1)));
+        assert!(banker_draws(4, Some(2)));
+        assert!(banker_draws(4, Some(7)));
+        assert!(!banker_draws(5, Some(3)));
+        assert!(banker_draws(5, Some(4)));
+        assert!(!banker_draws(6, Some(5)));
+        assert!(banker_draws(6, Some(6)));
+        assert!(!banker_draws(7, Some(6)));
+    }
+
+    #[test]
+    fn banker_wins_pay_less_commission() {
+        assert_eq!(BaccaratBet::Banker.payout_rate(), 2.0 - BANKER_COMMISSION);
+        // The house keeps a little over 1% of banker and player bets, and far more of ties.
+        assert!((BaccaratBet::Banker.expected_return() - 0.98947).abs() < 1e-5);
+        assert!((BaccaratBet::Player.expected_return() - 0.9876).abs() < 1e-5);
+        assert!(BaccaratBet::Tie.expected_return() < 0.9);
+    }
+
+    #[test]
+    fn third_card_outcomes_cover_every_draw() {
+        let mut rng = rand::rng();
+        for _ in 0..50 {
+            let mut table = Baccarat::new(&mut rng);
+            table.deal(&mut rng);
+            let total: f64 = table.third_card_outcomes().iter().map(|o| o.chance).sum();
+            assert!((total - 1.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn forced_draws_give_the_result() {
+        let mut rng = rand::rng();
+        for _ in 0..50 {
+            let mut table = Baccarat::new(&mut rng);
+            table.deal(&mut rng);
+            let result = [BaccaratBet::Tie, BaccaratBet::Banker, BaccaratBet::Player].into_iter()
+                .find(|&result| table.force_cost(result).is_some())
+                .unwrap();
+            table.draw_third_cards(&mut rng, Some(result));
+            assert_eq!(table.result(), result);
+            assert!(table.forced);
+        }
+    }
+}
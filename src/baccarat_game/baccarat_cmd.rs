@@ -0,0 +1,171 @@
+use std::{io::{stdin, stdout}, time::Instant};
+
+use crossterm::{style::Print, terminal, ExecutableCommand};
+use rand::Rng;
+
+use crate::{autosave::AutosaveEvent, baccarat_game::baccarat::{hand_total, Baccarat, BaccaratBet, BaccaratState}, cards::deck::Card, common_state::CommonState, game::BetResult, machine::machine::Manipulation, money::{looks_like_money, Money}};
+
+pub fn select_screen<R: Rng>(common_state: &mut CommonState, table: &mut Baccarat,
+rng: &mut R) -> Option<BaccaratState> {
+    match table.state {
+        BaccaratState::Hold => {
+            holding_screen(common_state, table, rng)
+        },
+        BaccaratState::InBet => {
+            in_bet(common_state, table, rng)
+        },
+    }
+}
+
+fn hand_str(hand: &[Card]) -> String {
+    hand.iter().map(|c| c.to_string()).collect::<Vec<String>>().join(" ")
+}
+
+/// # Print Hands
+/// 
+/// Prints both hands and their totals.
+fn print_hands(table: &Baccarat) {
+    if table.player_hand.is_empty() {
+        return;
+    }
+    stdout().execute(Print(format!("\tPlayer: {} ({})\tBanker: {} ({})\n",
+        hand_str(&table.player_hand), hand_total(&table.player_hand),
+        hand_str(&table.banker_hand), hand_total(&table.banker_hand)))).unwrap();
+}
+
+/// # Holding Screen
+/// 
+/// Shows the last hands, and lets the player pick their bet and shoe.
+pub fn holding_screen<R: Rng>(common_state: &mut CommonState, table: &mut Baccarat,
+rng: &mut R) -> Option<BaccaratState> {
+    stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
+    let mut msg = String::new();
+    loop {
+        let kicked_out = table.base.kickout_update(Instant::now());
+        stdout().execute(Print(format!("\t\t!!!Baccarat!!!\nCommands: P/B/T -> Back Player/Banker/Tie | S -> Swap 1/8 Deck Shoe | D -> Deal | Q -> Exit | Enter £sd to change Bet\nBet Min: {} | Bet Max: {}\n",
+            table.base.bet_min, table.base.bet_max))).unwrap();
+        stdout().execute(Print(format!("Money: {}\tEntropy: {} b\tSuspicion: {}\n", common_state.money, common_state.entropy, table.base.suspicion))).unwrap();
+        stdout().execute(Print(format!("Current Bet: {} on {:?} (pays x{})\tShoe: {} decks, {} cards left\n",
+            table.base.current_bet, table.wager, table.wager.payout_rate(), table.deck.decks,
+            table.deck.remaining()))).unwrap();
+        if kicked_out {
+            stdout().execute(Print(format!("!!! Kicked Out! Come back in {:.0} s !!!\n",
+                table.base.kickout_time_remaining()))).unwrap();
+        }
+        print_hands(table);
+        stdout().execute(Print(format!("{}\n", msg))).unwrap();
+        let mut buff = String::new();
+        stdin().read_line(&mut buff).unwrap();
+        buff = buff.trim().to_lowercase();
+        let amount = buff.parse::<Money>();
+        if let Ok(bet) = amount {
+            if let Err(err) = table.base.set_bet(bet, common_state.money) {
+                msg = err.to_string();
+            }
+        } else if let Some(wager) = BaccaratBet::parse(&buff) {
+            table.wager = wager;
+            msg = String::new();
+        } else if buff == "s" {
+            let decks = if table.deck.decks == 8 { 1 } else { 8 };
+            table.set_shoe(rng, decks);
+            msg = format!("Fresh {} deck shoe.", decks);
+        } else if buff == "d" {
+            let stake = table.base.current_bet;
+            match common_state.try_bet(&mut table.base, stake, Instant::now()) {
+                Ok(()) => {
+                    table.state = BaccaratState::InBet;
+                    return Some(BaccaratState::InBet);
+                },
+                Err(err) => msg = err.to_string(),
+            }
+        } else if buff == "q" {
+            return None;
+        } else {
+            msg = match amount {
+                Err(err) if looks_like_money(&buff) => err,
+                _ => String::from("Invalid Command."),
+            };
+        }
+        stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
+    }
+}
+
+/// # In Bet
+/// 
+/// Deals the first four cards, then lets the player force the third card draw with
+/// entropy before it's drawn.
+pub fn in_bet<R: Rng>(common_state: &mut CommonState, table: &mut Baccarat,
+rng: &mut R) -> Option<BaccaratState> {
+    stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
+    // The stake was taken when the bet was placed.
+    let stake = table.base.current_bet;
+    table.deal(rng);
+    let mut msg = String::new();
+    let bits = loop {
+        if table.natural() {
+            // Nothing left to draw.
+            break table.draw_third_cards(rng, None);
+        }
+        stdout().execute(Print("\t\t!!!Baccarat!!!\nCommands: D -> Draw | FP/FB/FT -> Force Player/Banker/Tie | Q -> Surrender (half stake back)\n")).unwrap();
+        stdout().execute(Print(format!("Money: {}\tEntropy: {} b\tSuspicion: {}\n", common_state.money, common_state.entropy, table.base.suspicion))).unwrap();
+        stdout().execute(Print(format!("Bet: {} on {:?}\n", stake, table.wager))).unwrap();
+        print_hands(table);
+        for result in [BaccaratBet::Player, BaccaratBet::Banker, BaccaratBet::Tie] {
+            let price = table.force_cost(result).map_or(String::from("--"), |cost| format!("{:.2} b",
+                common_state.machine.entropy_cost(Manipulation::Elimination, cost)));
+            stdout().execute(Print(format!("{:?}: {:.2}%\tForce: {}\n", result,
+                table.result_chance(result) * 100.0, price))).unwrap();
+        }
+        stdout().execute(Print(format!("{}\n", msg))).unwrap();
+        let mut buff = String::new();
+        stdin().read_line(&mut buff).unwrap();
+        buff = buff.trim().to_lowercase();
+        if buff == "d" {
+            break table.draw_third_cards(rng, None);
+        } else if buff == "q" {
+            if common_state.leave_bet(&mut table.base).is_some() {
+                table.state = BaccaratState::Hold;
+                return Some(BaccaratState::Hold);
+            }
+            break table.draw_third_cards(rng, None);
+        } else if let Some(result) = buff.strip_prefix('f').and_then(BaccaratBet::parse) {
+            match table.force_cost(result) {
+                None => msg = String::from("No draw can give that result."),
+                Some(cost) => match common_state.spend_manipulation(Manipulation::Elimination, cost,
+                    "Baccarat force", rng) {
+                    Ok(made) => {
+                        // The table saw it happen, long odds draw more eyes.
+                        table.base.add_suspicion(cost + made.suspicion_spike());
+                        // A misfire leaves the draw to chance.
+                        break table.draw_third_cards(rng, made.took_effect().then_some(result));
+                    },
+                    Err(err) => msg = err.to_string(),
+                },
+            }
+        } else {
+            msg = String::from("Invalid Command.");
+        }
+        stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
+    };
+    if !table.forced {
+        common_state.harvest(bits);
+    }
+    // finalize financial gains.
+    let result = table.result();
+    let payout = if result == table.wager {
+        table.wager.payout_rate()
+    } else if result == BaccaratBet::Tie {
+        // Player and Banker bets push on a tie.
+        1.0
+    } else {
+        0.0
+    };
+    let result = BetResult { won: result == table.wager, payout,
+        win_chance: table.wager.win_chance(), expected_return: table.wager.expected_return() };
+    common_state.settle_bet(&mut table.base, result);
+    if table.forced && table.base.kickout_roll(rng, Instant::now()) {
+        common_state.autosave.request(AutosaveEvent::Kickout);
+    }
+    table.state = BaccaratState::Hold;
+    Some(BaccaratState::Hold)
+}
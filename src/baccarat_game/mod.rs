@@ -0,0 +1,2 @@
+pub mod baccarat;
+pub mod baccarat_cmd;
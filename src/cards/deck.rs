@@ -0,0 +1,197 @@
+use std::fmt;
+
+use rand::{seq::SliceRandom, Rng};
+
+/// The ranks of a card, Ace low through King.
+pub const RANKS: [Rank; 13] = [Rank::Ace, Rank::Two, Rank::Three, Rank::Four, Rank::Five,
+    Rank::Six, Rank::Seven, Rank::Eight, Rank::Nine, Rank::Ten, Rank::Jack, Rank::Queen,
+    Rank::King];
+/// The suits of a card.
+pub const SUITS: [Suit; 4] = [Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Rank {
+    Ace = 1,
+    Two,
+    Three,
+    Four,
+    Five,
+    Six,
+    Seven,
+    Eight,
+    Nine,
+    Ten,
+    Jack,
+    Queen,
+    King,
+}
+
+impl Rank {
+    /// # Value
+    /// 
+    /// The face value of the rank, Ace is 1 and King is 13.
+    pub fn value(&self) -> u8 {
+        *self as u8
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Suit {
+    Clubs,
+    Diamonds,
+    Hearts,
+    Spades,
+}
+
+/// # Card
+/// 
+/// A single playing card.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Card {
+    pub rank: Rank,
+    pub suit: Suit,
+}
+
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rank = match self.rank {
+            Rank::Ace => "A",
+            Rank::Jack => "J",
+            Rank::Queen => "Q",
+            Rank::King => "K",
+            _ => "",
+        };
+        let suit = match self.suit {
+            Suit::Clubs => "c",
+            Suit::Diamonds => "d",
+            Suit::Hearts => "h",
+            Suit::Spades => "s",
+        };
+        if rank.is_empty() {
+            write!(f, "{}{}", self.rank.value(), suit)
+        } else {
+            write!(f, "{}{}", rank, suit)
+        }
+    }
+}
+
+/// # Deck
+/// 
+/// A shoe of one or more standard 52 card decks, shared between the card games.
+/// 
+/// The remaining cards are always known, so games can work out the exact odds of
+/// the next draw, and the entropy cost of changing it.
+#[derive(Debug)]
+pub struct Deck {
+    /// How many 52 card decks are in the shoe.
+    pub decks: u8,
+    /// The cards left in the shoe. The next card drawn is the last one.
+    pub cards: Vec<Card>,
+}
+
+impl Deck {
+    /// Create a new shuffled shoe of the given number of decks.
+    pub fn new<R: Rng>(rng: &mut R, decks: u8) -> Self {
+        let mut deck = Self { decks: decks.max(1), cards: vec![] };
+        deck.reshuffle(rng);
+        deck
+    }
+
+    /// # Reshuffle
+    /// 
+    /// Gathers every card back into the shoe and shuffles it.
+    pub fn reshuffle<R: Rng>(&mut self, rng: &mut R) {
+        self.cards.clear();
+        for _ in 0..self.decks {
+            for suit in SUITS {
+                for rank in RANKS {
+                    self.cards.push(Card { rank, suit });
+                }
+            }
+        }
+        self.cards.shuffle(rng);
+    }
+
+    /// # Reshuffle Without
+    /// 
+    /// Reshuffles, leaving out the cards still on the table.
+    pub fn reshuffle_without<R: Rng>(&mut self, rng: &mut R, held: &[Card]) {
+        self.reshuffle(rng);
+        for card in held {
+            if let Some(i) = self.cards.iter().position(|c| c == card) {
+                self.cards.remove(i);
+            }
+        }
+    }
+
+    /// # Size
+    /// 
+    /// How many cards the full shoe holds.
+    pub fn size(&self) -> usize {
+        self.decks as usize * 52
+    }
+
+    /// # Remaining
+    /// 
+    /// How many cards are left in the shoe.
+    pub fn remaining(&self) -> usize {
+        self.cards.len()
+    }
+
+    /// # Peek
+    /// 
+    /// The card the next draw gives, if the shoe isn't empty.
+    pub fn peek(&self) -> Option<&Card> {
+        self.cards.last()
+    }
+
+    /// # Rank Counts
+    /// 
+    /// How many of each rank are left in the shoe, indexed by value - 1.
+    pub fn rank_counts(&self) -> [u32; 13] {
+        let mut counts = [0; 13];
+        for card in self.cards.iter() {
+            counts[card.rank.value() as usize - 1] += 1;
+        }
+        counts
+    }
+
+    /// # Chance
+    /// 
+    /// The exact chance that the next card drawn matches the predicate.
+    pub fn chance<F: Fn(&Card) -> bool>(&self, predicate: F) -> f64 {
+        if self.cards.is_empty() {
+            return 0.0;
+        }
+        self.cards.iter().filter(|c| predicate(c)).count() as f64 / self.cards.len() as f64
+    }
+
+    /// # Draw
+    /// 
+    /// Draws the next card from the shoe. If it's empty, it's reshuffled first.
+    pub fn draw<R: Rng>(&mut self, rng: &mut R) -> Card {
+        if self.cards.is_empty() {
+            self.reshuffle(rng);
+        }
+        self.cards.pop().unwrap()
+    }
+
+    /// # Draw Matching
+    /// 
+    /// Draws a random card from those left that match the predicate, instead of the
+    /// next one. This is how entropy forces a draw.
+    /// 
+    /// Returns None if no card left matches.
+    pub fn draw_matching<R: Rng, F: Fn(&Card) -> bool>(&mut self, rng: &mut R,
+    predicate: F) -> Option<Card> {
+        let matches: Vec<usize> = self.cards.iter().enumerate()
+            .filter(|(_, c)| predicate(c))
+            .map(|(i, _)| i)
+            .collect();
+        if matches.is_empty() {
+            return None;
+        }
+        let pick = matches[rng.random_range(0..matches.len())];
+        Some(self.cards.remove(pick))
+    }
+}
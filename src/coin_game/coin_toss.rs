@@ -2,7 +2,26 @@ use std::time::{Duration, Instant};
 
 use rand::prelude::*;
 
-use crate::game::GameCommonData;
+use crate::{game::{entropy, GameCommonData}, money::Money};
+
+/// How long a coin toss bet runs, before any delay.
+pub const COIN_TOSS_BET_TIME: Duration = Duration::from_secs(30);
+/// The entropy a reflip costs.
+pub const REFLIP_BITS: f64 = 0.5;
+/// The entropy forcing a side costs.
+pub const FORCE_BITS: f64 = 1.0;
+/// The entropy peeking at the next flip costs.
+pub const PEEK_BITS: f64 = 0.25;
+/// The heads chance a biased coin lands with.
+pub const BIASED_HEADS_CHANCE: f64 = 0.75;
+/// The entropy delaying observation costs.
+pub const DELAY_BITS: f64 = 0.5;
+/// How much time delaying observation adds to the bet.
+pub const DELAY_TIME: Duration = Duration::from_secs(15);
+/// The entropy insuring the outcome costs.
+pub const INSURANCE_BITS: f64 = 1.0;
+/// What an insured loss pays back, per unit staked.
+pub const INSURED_PAYOUT: f64 = 0.5;
 
 /// # Coin Toss
 /// 
@@ -13,8 +32,19 @@ pub struct CoinToss {
     pub heads_chance: f64,
     /// The current result of the game. 
     pub result: bool,
+    /// The chance the current result had of coming up.
+    pub result_chance: f64,
+    /// Whether the current result was forced, or reflipped onto knowing it, so it
+    /// harvests nothing.
+    pub forced: bool,
     /// The current state of the game.
     pub state: CoinTossState,
+    /// The next flip, once it's been peeked at.
+    pub next_flip: Option<bool>,
+    /// How much longer the bet runs, from delaying observation.
+    pub delay: Duration,
+    /// Whether the bet's outcome is insured.
+    pub insured: bool,
     /// Shared common Data
     pub base: GameCommonData,
 }
@@ -33,14 +63,25 @@ pub enum CoinTossState {
     InBet,
 }
 
+impl Default for CoinToss {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CoinToss {
     /// Create a new CoinToss Game
     pub fn new() -> Self {
         Self { 
             heads_chance: 0.5,
             result: true,
+            result_chance: 0.5,
+            forced: false,
             state: CoinTossState::Hold,
-            base: GameCommonData::new("Coin Toss".to_string(), 1.0, 100.0, 
+            next_flip: None,
+            delay: Duration::ZERO,
+            insured: false,
+            base: GameCommonData::new("Coin Toss".to_string(), Money::from_pence(1), Money::from_pence(100), 
                 2.0, Duration::from_secs(30))
         }
     }
@@ -49,27 +90,24 @@ impl CoinToss {
     /// 
     /// The time remaining for a coin toss bet.
     /// 
-    /// For Coin Toss the bet duration is 30 seconds.
+    /// For Coin Toss the bet duration is 30 seconds, plus any delay.
     /// 
     /// If no ongoing bet, or the bet time has run out, return 0.0.
     pub fn bet_time_remaining(&self) -> f64 {
-        self.base.bet_time_remaining(Duration::from_secs(30)).unwrap_or(0.0)
+        self.base.bet_time_remaining(COIN_TOSS_BET_TIME + self.delay).unwrap_or(0.0)
     }
 
     /// # Entropy Gained
     /// 
-    /// Given the current state, how much entropy is gained by the user.
-    /// 
-    /// While the exact rules on this will depend on the game and bet involved,
-    /// for Coin Toss, it's fairly simple. The player always wins on heads,
-    /// and there are only two states possible, and they have an equal chance
-    /// of happening.
-    /// 
-    /// This means no actual calculation is needed as the value of each is just 1.
-    /// 
-    /// This comes from -lg(1/2) = 1
+    /// The entropy the current result harvests, the surprisal of the side it landed
+    /// on. A fair coin gives -lg(1/2) = 1 bit either way, a biased one more for the
+    /// side it's weighted against. A forced result gives nothing.
     pub fn entropy_gained(&self) -> f64 {
-        1.
+        if self.forced {
+            0.0
+        } else {
+            entropy(self.result_chance)
+        }
     }
 
     /// # Game Loop
@@ -78,7 +116,7 @@ impl CoinToss {
     /// and resolving changes.
     /// 
     /// This should have parameters for the game state, changes/clicks made, and similar stuff.
-    pub fn game_loop(&mut self, _interface_state: Option<()>, now: Instant) {
+    pub fn game_loop(&mut self, _interface_state: Option<()>, _now: Instant) {
         // Check if we're kicked out currently.
     }
 
@@ -89,7 +127,13 @@ impl CoinToss {
     /// 
     /// TODO: Add in potential failure correction.
     pub fn bet<R: Rng>(&mut self, rng: &mut R) -> bool {
+        self.heads_chance = 0.5;
+        self.next_flip = None;
+        self.delay = Duration::ZERO;
+        self.insured = false;
+        self.forced = false;
         self.result = self.flip(rng);
+        self.result_chance = self.side_chance(self.result);
         self.base.bet_start = Some(Instant::now());
         self.result
     }
@@ -102,4 +146,52 @@ impl CoinToss {
     pub fn flip<R: Rng>(&self, rng: &mut R) -> bool {
         rng.random_bool(self.heads_chance)
     }
+
+    /// # Side Chance
+    /// 
+    /// The chance of the coin landing on the given side, heads being true.
+    pub fn side_chance(&self, heads: bool) -> f64 {
+        if heads { self.heads_chance } else { 1.0 - self.heads_chance }
+    }
+
+    /// # Reflip
+    /// 
+    /// Flips the coin again, landing on the peeked flip if there is one. Landing on a
+    /// peeked flip is picking it knowingly, so it counts as forced.
+    pub fn reflip<R: Rng>(&mut self, rng: &mut R) -> bool {
+        match self.next_flip.take() {
+            Some(peeked) => self.force(peeked),
+            None => {
+                self.result = self.flip(rng);
+                self.result_chance = self.side_chance(self.result);
+                self.forced = false;
+            },
+        }
+        self.result
+    }
+
+    /// # Force
+    /// 
+    /// Sets the coin to the given side, heads being true.
+    pub fn force(&mut self, heads: bool) {
+        self.result = heads;
+        self.forced = true;
+    }
+
+    /// # Peek
+    /// 
+    /// What the next reflip will land on, deciding it now if it's not been peeked at.
+    pub fn peek<R: Rng>(&mut self, rng: &mut R) -> bool {
+        let heads_chance = self.heads_chance;
+        *self.next_flip.get_or_insert_with(|| rng.random_bool(heads_chance))
+    }
+
+    /// # Bias Cost
+    /// 
+    /// The entropy weighting the coin costs, the surprisal of the shift in odds. None
+    /// if it's already biased.
+    pub fn bias_cost(&self) -> Option<f64> {
+        (self.heads_chance < BIASED_HEADS_CHANCE)
+            .then(|| (BIASED_HEADS_CHANCE / self.heads_chance).log2())
+    }
 }
\ No newline at end of file
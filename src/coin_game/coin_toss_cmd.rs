@@ -3,7 +3,8 @@ use std::{io::{stdin, stdout}, thread::sleep, time::{Duration, Instant}};
 use crossterm::{event::{poll, read, Event, KeyCode}, style::Print, terminal, ExecutableCommand};
 use rand::Rng;
 
-use crate::{coin_game::coin_toss::{CoinToss, CoinTossState}, common_state::CommonState};
+use crate::{coin_game::coin_toss::{CoinToss, CoinTossState, BIASED_HEADS_CHANCE, DELAY_BITS, DELAY_TIME, FORCE_BITS, INSURANCE_BITS, INSURED_PAYOUT, PEEK_BITS, REFLIP_BITS}, common_state::CommonState, game::BetResult,
+    machine::{machine::Manipulation, research::Research}, money::{looks_like_money, Money}};
 
 pub fn select_screen<R: Rng>(common_state: &mut CommonState, 
 coin_toss: &mut CoinToss, start: Instant, rng: &mut R) -> Option<CoinTossState> {
@@ -23,51 +24,139 @@ coin_toss: &mut CoinToss, start: Instant, rng: &mut R) -> Option<CoinTossState>
 pub fn in_bet<R: Rng>(common_state: &mut CommonState, coin_toss: &mut CoinToss, 
 _start: Instant, rng: &mut R) -> Option<CoinTossState> {
     stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
-    // subtract money for bet
-    common_state.money -= coin_toss.base.current_bet;
+    // The stake was taken when the bet was placed.
     // Commit flip
     coin_toss.result = coin_toss.bet(rng);
-    // save entropy
-    let entropy_gained = coin_toss.entropy_gained();
-    common_state.add_entropy(entropy_gained);
     // start timer
     coin_toss.base.bet_start = Some(Instant::now());
+    let mut msg = String::new();
     loop {
         // print screen and timer.
+        let reflip = common_state.machine.entropy_cost(Manipulation::Reroll, REFLIP_BITS);
+        let force = common_state.machine.entropy_cost(Manipulation::Selection, FORCE_BITS);
         stdout().execute(
-            Print("\t\t!!!Coin Toss!!!\nLand on heads to win!
-            Commands: F -> Flip again (0.5 Entropy Cost) | W -> Select Heads (1 Entropy Cost) |
-            L -> Select Tails (1 Entropy Cost) | Q -> End Bet
-            Bet Min: $1 | Bet Max: $100\n")).unwrap();
+            Print(format!("\t\t!!!Coin Toss!!!\nLand on heads to win!
+            Commands: F -> Flip again ({:.2} Entropy Cost) | W -> Select Heads ({:.2} Entropy Cost) |
+            L -> Select Tails ({:.2} Entropy Cost) | Q -> End Bet
+            {}Bet Min: 1d | Bet Max: 8s 4d\n", reflip, force, force,
+            research_commands(common_state, coin_toss)))).unwrap();
         stdout().execute(
-            Print(format!("Money: ${}\tEntropy: {}b\tSuspicion: {}\n", common_state.money, common_state.entropy, coin_toss.base.suspicion))
+            Print(format!("Money: {}\tEntropy: {}b\tSuspicion: {}\n", common_state.money, common_state.entropy, coin_toss.base.suspicion))
         ).unwrap();
-        stdout().execute(Print(format!("Current Bet: {}\t Entropy Gained: {}\n", coin_toss.base.current_bet, entropy_gained))).unwrap();
+        stdout().execute(Print(format!("Current Bet: {}\t Entropy on Result: {:.2}\n", coin_toss.base.current_bet,
+            common_state.harvest_value(coin_toss.entropy_gained())))).unwrap();
         stdout().execute(Print(format!("Time Remaining: {} s\n", coin_toss.bet_time_remaining()))).unwrap();
         if coin_toss.result {
             stdout().execute(Print("\t\tH\t! You're Winner !\n")).unwrap();
         } else {
             stdout().execute(Print("\t\t\tT\t! FAILURE !\n")).unwrap();
         }
+        stdout().execute(Print(format!("{}\n", msg))).unwrap();
         // Get key presses while looping.
-        if poll(Duration::from_millis(500)).unwrap() {
-            if let Event::Key(event) = read().unwrap() {
-                if event.code == KeyCode::Char('f') {
-                    // Flip coin again, ignore whether the player has won or lost.
-                    common_state.entropy -= 0.5;
-                    coin_toss.result = coin_toss.flip(rng);
-                } else if event.code == KeyCode::Char('w') {
-                    // force coin to heads
-                    common_state.entropy -= 1.0;
-                    coin_toss.result = true;
-                } else if event.code == KeyCode::Char('l') {
-                    // force coin to tails
-                    common_state.entropy -= 1.0;
-                    coin_toss.result = false;
-                } else if event.code == KeyCode::Char('q') {
-                    // exiting bet early.
-                    break;
+        if poll(Duration::from_millis(500)).unwrap()
+            && let Event::Key(event) = read().unwrap() {
+            let machine = &common_state.machine;
+            if event.code == KeyCode::Char('f') {
+                // Flip coin again, ignore whether the player has won or lost.
+                msg = match common_state.spend_manipulation(Manipulation::Reroll, REFLIP_BITS,
+                    "Coin Toss reflip", rng) {
+                    Ok(made) => {
+                        coin_toss.base.add_suspicion(made.suspicion_spike());
+                        if made.took_effect() {
+                            coin_toss.reflip(rng);
+                        }
+                        made.report("Reflipped the coin")
+                    },
+                    Err(err) => err.to_string(),
+                };
+            } else if event.code == KeyCode::Char('w') {
+                // force coin to heads
+                msg = match common_state.spend_manipulation(Manipulation::Selection, FORCE_BITS,
+                    "Coin Toss force", rng) {
+                    Ok(made) => {
+                        coin_toss.base.add_suspicion(made.suspicion_spike());
+                        if made.took_effect() {
+                            coin_toss.force(true);
+                        }
+                        made.report("Forced heads")
+                    },
+                    Err(err) => err.to_string(),
+                };
+            } else if event.code == KeyCode::Char('l') {
+                // force coin to tails
+                msg = match common_state.spend_manipulation(Manipulation::Selection, FORCE_BITS,
+                    "Coin Toss force", rng) {
+                    Ok(made) => {
+                        coin_toss.base.add_suspicion(made.suspicion_spike());
+                        if made.took_effect() {
+                            coin_toss.force(false);
+                        }
+                        made.report("Forced tails")
+                    },
+                    Err(err) => err.to_string(),
+                };
+            } else if event.code == KeyCode::Char('p') && machine.researched(Research::Peek) {
+                msg = match common_state.spend_manipulation(Manipulation::Reroll, PEEK_BITS,
+                    "Coin Toss peek", rng) {
+                    Ok(made) => {
+                        coin_toss.base.add_suspicion(made.suspicion_spike());
+                        let next = if coin_toss.peek(rng) { "heads" } else { "tails" };
+                        made.report(&format!("The next flip lands {}. Peeked", next))
+                    },
+                    Err(err) => err.to_string(),
+                };
+            } else if event.code == KeyCode::Char('b') && machine.researched(Research::Bias) {
+                msg = match coin_toss.bias_cost() {
+                    None => String::from("The coin is already biased."),
+                    Some(cost) => match common_state.spend_manipulation(Manipulation::Elimination,
+                        cost, "Coin Toss bias", rng) {
+                        Ok(made) => {
+                            coin_toss.base.add_suspicion(made.suspicion_spike());
+                            if made.took_effect() {
+                                coin_toss.heads_chance = BIASED_HEADS_CHANCE;
+                            }
+                            made.report("Biased the coin")
+                        },
+                        Err(err) => err.to_string(),
+                    },
+                };
+            } else if event.code == KeyCode::Char('d') && machine.researched(Research::DelayedObservation) {
+                msg = if !coin_toss.delay.is_zero() {
+                    String::from("Observation is already delayed.")
+                } else {
+                    match common_state.spend_manipulation(Manipulation::Reroll, DELAY_BITS,
+                        "Coin Toss delay", rng) {
+                        Ok(made) => {
+                            coin_toss.base.add_suspicion(made.suspicion_spike());
+                            if made.took_effect() {
+                                coin_toss.delay = DELAY_TIME;
+                            }
+                            made.report("Delayed observation")
+                        },
+                        Err(err) => err.to_string(),
+                    }
+                };
+            } else if event.code == KeyCode::Char('i') && machine.researched(Research::OutcomeInsurance) {
+                msg = if coin_toss.insured {
+                    String::from("The outcome is already insured.")
+                } else {
+                    match common_state.spend_manipulation(Manipulation::Elimination, INSURANCE_BITS,
+                        "Coin Toss insurance", rng) {
+                        Ok(made) => {
+                            coin_toss.base.add_suspicion(made.suspicion_spike());
+                            coin_toss.insured = made.took_effect();
+                            made.report("Insured the outcome")
+                        },
+                        Err(err) => err.to_string(),
+                    }
+                };
+            } else if event.code == KeyCode::Char('q') {
+                // exiting bet early, by the game's early exit rule.
+                if common_state.leave_bet(&mut coin_toss.base).is_some() {
+                    coin_toss.state = CoinTossState::Hold;
+                    return Some(CoinTossState::Hold);
                 }
+                break;
             }
         }
         // lostly check that the bet is over. If it is, close out and move on.
@@ -77,12 +166,52 @@ _start: Instant, rng: &mut R) -> Option<CoinTossState> {
         sleep(Duration::from_millis(50));
         stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
     }
+    // Only the result that stood is harvested, and only if it came up naturally.
+    common_state.harvest(coin_toss.entropy_gained());
     // finalize financial gains if successful.
-    if coin_toss.result {
-        common_state.money += coin_toss.base.base_payout * coin_toss.base.current_bet;
-    }
+    let won = coin_toss.result;
+    let result = BetResult { won,
+        payout: if won {
+            coin_toss.base.base_payout
+        } else if coin_toss.insured {
+            INSURED_PAYOUT
+        } else {
+            0.0
+        },
+        win_chance: coin_toss.heads_chance,
+        expected_return: coin_toss.heads_chance * coin_toss.base.base_payout };
+    common_state.settle_bet(&mut coin_toss.base, result);
     coin_toss.state = CoinTossState::Hold;
-    return Some(CoinTossState::Hold);
+    Some(CoinTossState::Hold)
+}
+
+/// # Research Commands
+/// 
+/// The commands the machine's research has added to the bet, with their costs.
+fn research_commands(common_state: &CommonState, coin_toss: &CoinToss) -> String {
+    let machine = &common_state.machine;
+    let mut commands = vec![];
+    if machine.researched(Research::Peek) {
+        commands.push(format!("P -> Peek at the next Flip ({:.2} Entropy Cost)",
+            machine.entropy_cost(Manipulation::Reroll, PEEK_BITS)));
+    }
+    if machine.researched(Research::Bias) {
+        commands.push(format!("B -> Bias toward Heads ({:.2} Entropy Cost)",
+            machine.entropy_cost(Manipulation::Elimination, coin_toss.bias_cost().unwrap_or(0.0))));
+    }
+    if machine.researched(Research::DelayedObservation) {
+        commands.push(format!("D -> Delay Observation {} s ({:.2} Entropy Cost)", DELAY_TIME.as_secs(),
+            machine.entropy_cost(Manipulation::Reroll, DELAY_BITS)));
+    }
+    if machine.researched(Research::OutcomeInsurance) {
+        commands.push(format!("I -> Insure the Outcome ({:.2} Entropy Cost)",
+            machine.entropy_cost(Manipulation::Elimination, INSURANCE_BITS)));
+    }
+    if commands.is_empty() {
+        String::new()
+    } else {
+        format!("{}\n            ", commands.join(" | "))
+    }
 }
 
 /// # Start Bet
@@ -99,8 +228,8 @@ pub fn start_bet(common_state: &CommonState, coin_toss: &mut CoinToss, start: In
         let side = (time.as_secs_f32() * 10.0) as i32 % 2;
         let from_start = Instant::now() - flip_start;
         // 
-        stdout().execute(Print("\t\t!!!Coin Toss!!!\nLand on heads to win!\nCommands: F -> Flip | Q -> Exit | Enter number to change Bet\nBet Min: $1 | Bet Max: $100\n")).unwrap();
-        stdout().execute(Print(format!("Money: ${}\tEntropy: {} b\n", common_state.money, common_state.entropy))).unwrap();
+        stdout().execute(Print("\t\t!!!Coin Toss!!!\nLand on heads to win!\nCommands: F -> Flip | Q -> Exit | Enter £sd to change Bet\nBet Min: 1d | Bet Max: 8s 4d\n")).unwrap();
+        stdout().execute(Print(format!("Money: {}\tEntropy: {} b\n", common_state.money, common_state.entropy))).unwrap();
         stdout().execute(Print(format!("Current Bet: {}\n", coin_toss.base.current_bet))).unwrap();
         stdout().execute(Print(msg.as_str())).unwrap();
         if side == 1 {
@@ -125,14 +254,13 @@ pub fn start_bet(common_state: &CommonState, coin_toss: &mut CoinToss, start: In
 /// the current outcome of the coin toss game.
 /// 
 /// It should also have a list of commands for the holding state.
-pub fn holding_screen(common_state: &mut CommonState, coin_toss: &mut CoinToss, start: Instant) -> Option<CoinTossState> {
+pub fn holding_screen(common_state: &mut CommonState, coin_toss: &mut CoinToss, _start: Instant) -> Option<CoinTossState> {
     stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
     let mut msg = String::new();
     loop {
-        let time = ((Instant::now() - start).as_secs_f32() * 2.0) as i32 % 2;
         // Set up bet and promts for it.
-        stdout().execute(Print("\t\t!!!Coin Toss!!!\nCommands: F -> Flip | Q -> Exit | Enter number to change Bet\nBet Min: $1 | Bet Max: $100\n")).unwrap();
-        stdout().execute(Print(format!("Money: ${}\tEntropy: {} b\n", common_state.money, common_state.entropy))).unwrap();
+        stdout().execute(Print("\t\t!!!Coin Toss!!!\nCommands: F -> Flip | Q -> Exit | Enter £sd to change Bet\nBet Min: 1d | Bet Max: 8s 4d\n")).unwrap();
+        stdout().execute(Print(format!("Money: {}\tEntropy: {} b\n", common_state.money, common_state.entropy))).unwrap();
         stdout().execute(Print(format!("Current Bet: {}\n", coin_toss.base.current_bet))).unwrap();
         stdout().execute(Print(msg.as_str())).unwrap();
         stdout().execute(Print("\n\t\t H or T? \n")).unwrap();
@@ -142,26 +270,32 @@ pub fn holding_screen(common_state: &mut CommonState, coin_toss: &mut CoinToss,
         //println!("{}, {}", buff, buff.len());
         buff = buff.trim_end().to_string();
         //buff.parse::<f64>().unwrap();
-        if let Ok(bet) = buff.parse::<f64>() {
-            if bet < coin_toss.base.bet_min || bet > coin_toss.base.bet_max {
-                msg = String::from("Bet must be within bounds!");
-            } if bet > common_state.money {
-                msg = String::from("Not enough money!!");
-            } else {
-                coin_toss.base.current_bet = bet.floor();
+        let amount = buff.parse::<Money>();
+        if let Ok(bet) = amount {
+            if let Err(err) = coin_toss.base.set_bet(bet, common_state.money) {
+                msg = err.to_string();
             }
         } else if buff.to_lowercase() == "f" {
-            stdout().execute(Print("Flipping!")).unwrap();
-            coin_toss.state = CoinTossState::StartBet;
-            return Some(CoinTossState::StartBet);
+            let stake = coin_toss.base.current_bet;
+            match common_state.try_bet(&mut coin_toss.base, stake, Instant::now()) {
+                Ok(()) => {
+                    stdout().execute(Print("Flipping!")).unwrap();
+                    coin_toss.state = CoinTossState::StartBet;
+                    return Some(CoinTossState::StartBet);
+                },
+                Err(err) => msg = err.to_string(),
+            }
         } else if buff.to_lowercase() == "q" {
             stdout().execute(Print("Quitting!")).unwrap();
             return None;
         } else {
-            msg = String::from("Invalid Command.");
+            msg = match amount {
+                Err(err) if looks_like_money(&buff) => err,
+                _ => String::from("Invalid Command."),
+            };
         }
 
-        if common_state.money < 1.0 {
+        if common_state.money < coin_toss.base.bet_min {
             stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
             stdout().execute(Print("!!! Ran out of Money! Game Over !!!")).unwrap();
             stdin().read_line(&mut buff).unwrap();
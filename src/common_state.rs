@@ -1,6 +1,14 @@
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 
-use crate::machine::machine::Machine;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::{autosave::{Autosave, AutosaveEvent, DEFAULT_AUTOSAVE_INTERVAL}, game::{entropy, BetResult, GameCommonData, Settlement}, ledger::{Ledger, SpendError, TransactionKind}, machine::{contract::{Contract, ContractGoal, Contracts}, machine::{Machine, Malfunction, Manipulated, Manipulation, INVEST_RESERVE, MAX_LEVEL, OFFLINE_RUN_MAX}, market::Market, research::{load_research, save_research, Funding, Research}}, money::Money, save::{wall_clock, SaveError, SaveFile, Section}, save_slots::DEFAULT_SAVE_BACKUPS, sports_game::league::League};
+
+/// How much the machine regenerates or leaks before it's logged in the ledger.
+const UNLOGGED_RUN_MAX: f64 = 1.0;
+/// The share of an outcome's surprisal harvested, unless the player picks otherwise.
+pub const DEFAULT_HARVEST_FRACTION: f64 = 1.0;
 
 /// # Common State
 /// 
@@ -15,8 +23,8 @@ pub struct CommonState {
     /// then it's game over.
     /// 
     /// This is jokingly measured in £sd (Pounds, shillings, pence) for the player,
-    /// but is measured directly here.
-    pub money: f64,
+    /// and stored in farthings.
+    pub money: Money,
     /// The Entropy the player has available to them. This is measured in bits and
     /// is spent manipulating the games in question.
     pub entropy: f64,
@@ -32,7 +40,7 @@ pub struct CommonState {
     /// 
     /// Should be between the current active game's min and max as well as less than
     /// or equal to the current money available.
-    pub current_bet: f64,
+    pub current_bet: Money,
 
     /// Click on the start bet Button.
     pub button_clicked: ButtonAction,
@@ -40,6 +48,11 @@ pub struct CommonState {
     /// The Entropy Machine of the player. Sets the cap on how much entropy can be stored
     /// in one moment.
     pub machine: Machine,
+    /// The game time the machine has been run up to.
+    pub machine_run_to: Duration,
+    /// Entropy the machine has regenerated and leaked that isn't in the ledger yet.
+    /// It's logged once it adds up to a bit, rather than every step.
+    pub unlogged_run: (f64, f64),
 
     /// The last time the save was made since game start.
     pub last_prior_save: Instant,
@@ -47,86 +60,776 @@ pub struct CommonState {
     /// 
     /// Added to and updated periodically.
     pub game_length: Duration,
+
+    /// The share of each natural outcome's surprisal harvested as entropy.
+    pub harvest_fraction: f64,
+    /// The entropy market, where entropy is traded for money.
+    pub market: Market,
+
+    /// The sports league, if the player has visited the sportsbook. Kept here, as 
+    /// the season and its bets carry on between visits and saves.
+    pub league: Option<League>,
+
+    /// Every change to money and entropy made through the spending methods.
+    pub ledger: Ledger,
+
+    /// The stakes games are holding for bets still running. If a save is loaded with 
+    /// any still held, those bets were never finished, so they're closed by their
+    /// game's early exit rule.
+    pub escrow: Money,
+
+    /// The common data of each game the player has been to, kept between visits and
+    /// saves. The sportsbook keeps its own, in the league.
+    pub game_data: Vec<GameCommonData>,
+
+    /// The save slot the game is saved to.
+    pub save_slot: String,
+    /// How many backups of the save slot to keep.
+    pub save_backups: usize,
+    /// Whether the run was loaded from a save that was tampered with. Tainted runs stay
+    /// tainted, and are kept off leaderboards and achievements.
+    pub tainted: bool,
+    /// When the game next saves itself, and the autosave being written.
+    pub autosave: Autosave,
+
+    /// The random number generator the games draw from. Kept here so it's saved with 
+    /// everything else.
+    pub rng: ChaCha8Rng,
 }
 
 impl CommonState {
     pub fn empty() -> Self {
         Self {
             player_name: String::new(),
-            money: 0.0,
+            money: Money::ZERO,
             entropy: 0.0,
             active_game: 0,
-            current_bet: 0.0,
+            current_bet: Money::ZERO,
             button_clicked: ButtonAction::None,
-            machine: Machine {
-                level: 0.0
-            },
+            machine: Machine::new(0),
+            machine_run_to: Duration::ZERO,
+            unlogged_run: (0.0, 0.0),
+            harvest_fraction: DEFAULT_HARVEST_FRACTION,
+            market: Market::default(),
             last_prior_save: Instant::now(),
             game_length: Duration::ZERO,
+            league: None,
+            ledger: Ledger::default(),
+            escrow: Money::ZERO,
+            game_data: vec![],
+            save_slot: String::new(),
+            save_backups: DEFAULT_SAVE_BACKUPS,
+            tainted: false,
+            autosave: Autosave::new(),
+            rng: ChaCha8Rng::from_os_rng(),
         }
     }
 
     pub fn new(player_name: String) -> Self {
         Self {
             player_name,
-            money: 240.0,
+            money: Money::from_pounds(1),
             entropy: 100.0,
             active_game: 0,
-            current_bet: 1.0,
+            current_bet: Money::from_pence(1),
             button_clicked: ButtonAction::None,
-            machine: Machine { level: 0.0 },
+            machine: Machine::new(0),
+            machine_run_to: Duration::ZERO,
+            unlogged_run: (0.0, 0.0),
+            harvest_fraction: DEFAULT_HARVEST_FRACTION,
+            market: Market::new(rand::random()),
             last_prior_save: Instant::now(),
-            game_length: Duration::ZERO
+            game_length: Duration::ZERO,
+            league: None,
+            ledger: Ledger::default(),
+            escrow: Money::ZERO,
+            game_data: vec![],
+            save_slot: String::new(),
+            save_backups: DEFAULT_SAVE_BACKUPS,
+            tainted: false,
+            autosave: Autosave::new(),
+            rng: ChaCha8Rng::from_os_rng(),
         }
     }
 
+    /// # Game Time
+    /// 
+    /// How long the game has been going on, up to this moment. The game length as of 
+    /// the last save, plus the time since.
+    pub fn game_time(&self) -> Duration {
+        self.game_length + (Instant::now() - self.last_prior_save)
+    }
+
     pub fn add_entropy(&mut self, entropy_gained: f64) {
+        let before = self.entropy;
         self.entropy += entropy_gained;
-        self.entropy = self.entropy.min(self.machine.entropy_cap());
+        // Past the cap, the machine overcharges, at rising risk.
+        self.entropy = self.entropy.min(self.machine.overcharge_cap());
+        if self.entropy != before {
+            self.ledger.record(self.game_time(), TransactionKind::Entropy(self.entropy - before),
+                "Entropy harvested");
+        }
+    }
+
+    /// # Harvest
+    /// 
+    /// Harvests entropy from an outcome that came up naturally, the harvest fraction
+    /// of its surprisal. Games don't harvest outcomes the player forced.
+    pub fn harvest(&mut self, surprisal: f64) {
+        let bits = self.harvest_value(surprisal);
+        self.add_entropy(bits);
+    }
+
+    /// # Harvest Value
+    /// 
+    /// How much entropy an outcome of the given surprisal harvests.
+    pub fn harvest_value(&self, surprisal: f64) -> f64 {
+        surprisal * self.harvest_fraction
+    }
+
+    /// # Run Machine
+    /// 
+    /// Runs the machine up to the current game time, regenerating and leaking entropy.
+    pub fn run_machine(&mut self) {
+        let now = self.game_time();
+        let (regen, leak) = self.machine.run(self.entropy, now.saturating_sub(self.machine_run_to));
+        self.machine.cool(now.saturating_sub(self.machine_run_to));
+        self.machine_run_to = now;
+        self.entropy += regen - leak;
+        self.unlogged_run.0 += regen;
+        self.unlogged_run.1 += leak;
+        self.log_machine_run(false);
+    }
+
+    /// # Tick
+    /// 
+    /// Runs the machine up to the current game time, and has the Machine check on its
+    /// contract. The frontends call this every frame or poll, so the spending and
+    /// crediting calls never fine the player or request an autosave mid-bet.
+    pub fn tick(&mut self) {
+        self.run_machine();
+        self.check_contract();
+    }
+
+    /// # Check Contract
+    /// 
+    /// Has the Machine issue its next contract if there's none running, and fails the
+    /// one running once its deadline passes.
+    fn check_contract(&mut self) {
+        let now = self.game_time();
+        match self.machine.contracts.active {
+            None => {
+                let contract = Contract::issue(self.machine.contracts.issued(),
+                    self.machine.entropy_cap(), now);
+                self.machine.contracts.active = Some(contract);
+                self.machine.contracts.news = Some(format!("New contract from the Machine: {}", 
+                    contract.goal));
+            },
+            Some(contract) if now >= contract.deadline => {
+                self.machine.contracts.active = None;
+                self.machine.contracts.failed += 1;
+                let fine = contract.penalty_money.min(self.money);
+                // The fine is never more than the player has, so it can't fail.
+                let _ = self.spend_money(fine, "Contract penalty");
+                let levels = contract.penalty_levels.min(self.machine.level);
+                self.machine.drop_levels(levels);
+                self.drop_to_cap("Entropy lost to contract penalty");
+                self.machine.contracts.news = Some(format!(
+                    "Contract missed! The Machine took {} levels and {}.", levels, fine));
+                self.autosave.request(AutosaveEvent::ContractEnded);
+            },
+            Some(_) => {},
+        }
+    }
+
+    /// Pays the contract's reward, once it's met.
+    fn complete_contract(&mut self, contract: Contract) {
+        self.machine.contracts.active = None;
+        self.machine.contracts.completed += 1;
+        let levels = contract.reward_levels.min(MAX_LEVEL - self.machine.level);
+        self.machine.level += levels;
+        self.credit_payout(contract.reward_money, "Contract reward");
+        self.machine.contracts.news = Some(format!(
+            "Contract met! The Machine granted {} levels and {}.", levels, contract.reward_money));
+        self.autosave.request(AutosaveEvent::ContractEnded);
+    }
+
+    /// # Deliver Contract
+    /// 
+    /// Delivers as much entropy or money as the running contract still needs, or as 
+    /// the player has. Money keeps back the same reserve as upgrades. Returns what was
+    /// delivered, or why nothing could be.
+    pub fn deliver_contract(&mut self) -> Result<String, String> {
+        self.tick();
+        let mut contract = self.machine.contracts.active
+            .ok_or_else(|| String::from("There's no contract running."))?;
+        let delivery = match &mut contract.goal {
+            ContractGoal::Entropy { needed, delivered } => {
+                let amount = (*needed - *delivered).min(self.entropy);
+                if amount <= 0.0 {
+                    return Err(String::from("There's no entropy to deliver."));
+                }
+                self.spend_entropy(amount, "Contract delivery").map_err(|err| err.to_string())?;
+                *delivered += amount;
+                format!("{:.2} b", amount)
+            },
+            ContractGoal::Money { needed, delivered } => {
+                let amount = (*needed - *delivered).min(self.money - INVEST_RESERVE);
+                if amount <= Money::ZERO {
+                    return Err(format!("Must keep at least {} back.", INVEST_RESERVE));
+                }
+                self.spend_money(amount, "Contract delivery").map_err(|err| err.to_string())?;
+                *delivered += amount;
+                amount.to_string()
+            },
+            ContractGoal::Feat { .. } =>
+                return Err(String::from("Feats can't be delivered, win the bet instead.")),
+        };
+        if contract.goal.met() {
+            self.complete_contract(contract);
+        } else {
+            self.machine.contracts.active = Some(contract);
+        }
+        Ok(format!("Delivered {} to the Machine.", delivery))
+    }
+
+    /// Meets the running contract if it's a feat the bet just pulled off.
+    fn contract_feat(&mut self, result: &BetResult) {
+        if let Some(mut contract) = self.machine.contracts.active
+            && let ContractGoal::Feat { bits, done } = &mut contract.goal
+            && result.won && result.win_chance > 0.0 && entropy(result.win_chance) >= *bits {
+            *done = true;
+            self.complete_contract(contract);
+        }
+    }
+
+    /// Drops any entropy above the overcharge cap, after the machine loses levels.
+    fn drop_to_cap(&mut self, reason: &str) {
+        let lost = self.entropy - self.machine.overcharge_cap();
+        if lost > 0.0 {
+            self.entropy -= lost;
+            self.ledger.record(self.game_time(), TransactionKind::Entropy(-lost), reason);
+        }
+    }
+
+    /// Logs what the machine has regenerated and leaked, once it's at least a bit or
+    /// when forced.
+    fn log_machine_run(&mut self, force: bool) {
+        let (regen, leak) = self.unlogged_run;
+        if regen >= UNLOGGED_RUN_MAX || (force && regen > 0.0) {
+            self.ledger.record(self.game_time(), TransactionKind::Entropy(regen),
+                "Machine regeneration");
+            self.unlogged_run.0 = 0.0;
+        }
+        if leak >= UNLOGGED_RUN_MAX || (force && leak > 0.0) {
+            self.ledger.record(self.game_time(), TransactionKind::Entropy(-leak), "Machine leakage");
+            self.unlogged_run.1 = 0.0;
+        }
+    }
+
+    /// Runs the machine for the time passed since the save was made, up to the offline
+    /// limit, so a little regenerates and leaks while the game is closed.
+    fn run_offline(&mut self, saved_at: SystemTime, wall_now: SystemTime) {
+        let away = wall_now.duration_since(saved_at).unwrap_or_default().min(OFFLINE_RUN_MAX);
+        let (regen, leak) = self.machine.run(self.entropy, away);
+        self.machine.cool(away);
+        self.entropy += regen - leak;
+        if regen > 0.0 {
+            self.ledger.record(self.game_time(), TransactionKind::Entropy(regen),
+                "Offline machine regeneration");
+        }
+        if leak > 0.0 {
+            self.ledger.record(self.game_time(), TransactionKind::Entropy(-leak),
+                "Offline machine leakage");
+        }
+    }
+
+    /// # Spend Money
+    /// 
+    /// Takes money from the player, so long as they have it, and logs it.
+    pub fn spend_money(&mut self, amount: Money, reason: &str) -> Result<(), SpendError> {
+        if amount > self.money {
+            return Err(SpendError::InsufficientFunds { needed: amount, available: self.money });
+        }
+        self.money -= amount;
+        self.ledger.record(self.game_time(), TransactionKind::Money(-amount), reason);
+        Ok(())
+    }
+
+    /// # Spend Entropy
+    /// 
+    /// Takes entropy from the player, so long as they have it, and logs it.
+    pub fn spend_entropy(&mut self, bits: f64, reason: &str) -> Result<(), SpendError> {
+        if bits > self.entropy {
+            return Err(SpendError::InsufficientEntropy { needed: bits, available: self.entropy });
+        }
+        self.entropy -= bits;
+        self.ledger.record(self.game_time(), TransactionKind::Entropy(-bits), reason);
+        Ok(())
+    }
+
+    /// # Spend Manipulation
+    /// 
+    /// Spends the entropy for a manipulation of the given surprisal, after the 
+    /// machine's component for its class has taken its discount off. The machine heats
+    /// up making it, and may malfunction if it's running hot or overcharged. Returns 
+    /// what was spent and how it went, it's up to the game to skip a misfire and
+    /// add a suspicion spike to the table.
+    pub fn spend_manipulation<R: Rng>(&mut self, kind: Manipulation, bits: f64, reason: &str,
+    rng: &mut R) -> Result<Manipulated, SpendError> {
+        let cost = self.machine.entropy_cost(kind, bits);
+        self.spend_entropy(cost, reason)?;
+        let malfunction = self.machine.manipulate(rng, cost, self.entropy);
+        if let Some(Malfunction::Vent(vented)) = malfunction {
+            self.entropy -= vented;
+            self.ledger.record(self.game_time(), TransactionKind::Entropy(-vented),
+                "Machine vent");
+        }
+        Ok(Manipulated { spent: cost, malfunction })
+    }
+
+    /// # Credit Payout
+    /// 
+    /// Gives the player money won, and logs it. Nothing is logged for nothing won.
+    pub fn credit_payout(&mut self, amount: Money, reason: &str) {
+        if amount.is_zero() {
+            return;
+        }
+        self.money += amount;
+        self.ledger.record(self.game_time(), TransactionKind::Money(amount), reason);
+    }
+
+    /// # Upgrade Machine
+    /// 
+    /// Invests money in the machine, buying as many whole levels as it covers and
+    /// keeping the rest. Returns the levels bought and what they cost, or why the 
+    /// investment can't be made.
+    pub fn upgrade_machine(&mut self, invest: Money) -> Result<(u64, Money), String> {
+        let (levels, cost) = self.machine.check_invest(invest, self.money)?;
+        self.spend_money(cost, "Machine upgrade").map_err(|err| err.to_string())?;
+        self.machine.level += levels;
+        self.machine.invested += cost;
+        self.autosave.request(AutosaveEvent::MachineUpgrade);
+        Ok((levels, cost))
+    }
+
+    /// # Sell Machine
+    /// 
+    /// Sells the machine's top levels back for part of what they cost. Any entropy 
+    /// above the lowered overcharge cap is lost. Returns what was paid back, or why the levels 
+    /// can't be sold.
+    pub fn sell_machine(&mut self, levels: u64) -> Result<Money, String> {
+        let refund = self.machine.check_sell(levels)?;
+        self.machine.drop_levels(levels);
+        self.credit_payout(refund, "Machine sell-back");
+        self.drop_to_cap("Entropy lost to machine sell-back");
+        self.autosave.request(AutosaveEvent::MachineUpgrade);
+        Ok(refund)
+    }
+
+    /// # Sell Entropy
+    /// 
+    /// Sells bits on the entropy market at its current price. Returns what they
+    /// fetched, or why they can't be sold.
+    pub fn sell_entropy(&mut self, bits: f64) -> Result<Money, String> {
+        self.run_machine();
+        let now = self.game_time();
+        let value = self.market.check_sell(bits, self.entropy, now)?;
+        self.spend_entropy(bits, "Market sale").map_err(|err| err.to_string())?;
+        self.credit_payout(value, "Market sale");
+        self.market.trade(bits, now);
+        Ok(value)
+    }
+
+    /// # Buy Entropy
+    /// 
+    /// Buys bits on the entropy market at its current price, up to the machine's cap. 
+    /// Money keeps back the same reserve as upgrades. Returns what they cost, or why
+    /// they can't be bought.
+    pub fn buy_entropy(&mut self, bits: f64) -> Result<Money, String> {
+        self.run_machine();
+        let now = self.game_time();
+        let room = (self.machine.entropy_cap() - self.entropy).max(0.0);
+        if bits > room {
+            return Err(format!("The machine only has room for {:.2} b more.", room));
+        }
+        let cost = self.market.check_buy(bits, self.money, INVEST_RESERVE, now)?;
+        self.spend_money(cost, "Market purchase").map_err(|err| err.to_string())?;
+        self.entropy += bits;
+        self.ledger.record(now, TransactionKind::Entropy(bits), "Market purchase");
+        self.market.trade(-bits, now);
+        Ok(cost)
+    }
+
+    /// # Upgrade Component
+    /// 
+    /// Buys the next level of the machine's component for a manipulation class.
+    /// Returns what it cost, or why it can't be bought.
+    pub fn upgrade_component(&mut self, kind: Manipulation) -> Result<Money, String> {
+        let cost = self.machine.check_component(kind, self.money)?;
+        self.spend_money(cost, format!("{} component upgrade", kind.name()).as_str())
+            .map_err(|err| err.to_string())?;
+        self.machine.components[kind as usize] += 1;
+        self.autosave.request(AutosaveEvent::MachineUpgrade);
+        Ok(cost)
+    }
+
+    /// # Upgrade Cooling
+    /// 
+    /// Buys the next level of the machine's cooling. Returns what it cost, or why it 
+    /// can't be bought.
+    pub fn upgrade_cooling(&mut self) -> Result<Money, String> {
+        let cost = self.machine.check_cooling(self.money)?;
+        self.spend_money(cost, "Machine cooling upgrade").map_err(|err| err.to_string())?;
+        self.machine.cooling += 1;
+        self.autosave.request(AutosaveEvent::MachineUpgrade);
+        Ok(cost)
+    }
+
+    /// # Fund Research
+    /// 
+    /// Pays for research with money or entropy, unlocking it. Money keeps back the 
+    /// same reserve as upgrades. Returns why it can't be researched, if it can't.
+    pub fn fund_research(&mut self, research: Research, funding: Funding) -> Result<(), String> {
+        self.machine.check_research(research)?;
+        let project = research.project();
+        let reason = format!("{} research", project.name);
+        match funding {
+            Funding::Money if project.money + INVEST_RESERVE > self.money =>
+                return Err(format!("{} costs {}, and at least {} must be left.", project.name,
+                    project.money, INVEST_RESERVE)),
+            Funding::Money => self.spend_money(project.money, &reason),
+            Funding::Entropy => self.spend_entropy(project.entropy, &reason),
+        }.map_err(|err| err.to_string())?;
+        self.machine.research.push(research);
+        self.autosave.request(AutosaveEvent::MachineUpgrade);
+        Ok(())
+    }
+
+    /// # Check Bet
+    /// 
+    /// Checks that a bet can be placed on the game right now, without taking it.
+    /// 
+    /// The game's current bet must be within its bounds, and the stake affordable. The
+    /// stake may be a multiple of the current bet, for games which take several at once.
+    pub fn check_bet(&self, game: &GameCommonData, stake: Money, now: Instant)
+    -> Result<(), SpendError> {
+        if let Some(end) = game.kickout_end_time().filter(|end| *end > now) {
+            return Err(SpendError::KickedOut { remaining: (end - now).as_secs_f64() });
+        }
+        if game.current_bet < game.bet_min || game.current_bet > game.bet_max {
+            return Err(SpendError::BetOutOfRange { bet: game.current_bet, min: game.bet_min,
+                max: game.bet_max });
+        }
+        if stake > self.money {
+            return Err(SpendError::InsufficientFunds { needed: stake, available: self.money });
+        }
+        Ok(())
+    }
+
+    /// # Try Bet
+    /// 
+    /// Checks the bet can be placed, then takes the stake and puts it in the game's
+    /// escrow until the bet is settled.
+    pub fn try_bet(&mut self, game: &mut GameCommonData, stake: Money, now: Instant)
+    -> Result<(), SpendError> {
+        self.check_bet(game, stake, now)?;
+        self.spend_money(stake, format!("{} bet", game.name).as_str())?;
+        game.hold_stake(stake, now);
+        self.escrow += stake;
+        Ok(())
+    }
+
+    /// # Settle Bet
+    /// 
+    /// Settles the game's bet, paying out any winnings. Returns the winnings.
+    pub fn settle_bet(&mut self, game: &mut GameCommonData, result: BetResult) -> Money {
+        self.contract_feat(&result);
+        let settlement = game.settle(result);
+        self.release(game, settlement)
+    }
+
+    /// # Settle Bet Part
+    /// 
+    /// Settles part of the game's bet, for games with several riding at once. 
+    /// Returns the winnings.
+    pub fn settle_bet_part(&mut self, game: &mut GameCommonData, stake: Money, 
+    result: BetResult) -> Money {
+        self.contract_feat(&result);
+        let settlement = game.settle_part(stake, result);
+        self.release(game, settlement)
+    }
+
+    /// # Forfeit Bet
+    /// 
+    /// Loses whatever the game has in escrow.
+    pub fn forfeit_bet(&mut self, game: &mut GameCommonData) {
+        let settlement = game.forfeit();
+        self.release(game, settlement);
+    }
+
+    /// # Leave Bet
+    /// 
+    /// Leaves the game's bet early, by its early exit rule. Returns what was paid back,
+    /// or None if the game needs to settle it as it stands.
+    pub fn leave_bet(&mut self, game: &mut GameCommonData) -> Option<Money> {
+        let settlement = game.leave_early()?;
+        Some(self.release(game, settlement))
+    }
+
+    /// Releases a settlement from escrow, paying it out.
+    fn release(&mut self, game: &GameCommonData, settlement: Settlement) -> Money {
+        self.escrow -= settlement.stake.min(self.escrow);
+        self.credit_payout(settlement.payout, format!("{} payout", game.name).as_str());
+        self.autosave.request(AutosaveEvent::BetSettled);
+        settlement.payout
     }
     
-    /// # Save Str(ing)
+    /// # Restore Game
     /// 
-    /// Creates a string which is coverted into a byte array, and returned.
+    /// Carries the saved progress of a game, if it's been played before, onto a
+    /// freshly made one.
+    pub fn restore_game(&self, game: &mut GameCommonData) {
+        if let Some(saved) = self.game_data.iter().find(|g| g.name == game.name) {
+            game.restore_from(saved);
+        }
+    }
+
+    /// # Store Game
     /// 
-    /// Should be a standard layout. Very simple, very dumb shit.
+    /// Keeps a game's data, so it's there for the next visit and the save. Done as
+    /// the player leaves it, and before autosaving while they're in it.
+    pub fn store_game(&mut self, game: &GameCommonData) {
+        match self.game_data.iter_mut().find(|g| g.name == game.name) {
+            Some(saved) => *saved = game.clone(),
+            None => self.game_data.push(game.clone()),
+        }
+    }
+
+    /// # Autosave Poll
     /// 
-    /// I should be ashamed of myself, but I'm not. 
+    /// Starts an autosave if one's due, and reports on the last one once it's written.
+    /// Cheap enough to call every frame, or after every command. Nothing is saved
+    /// until the game has a slot.
     /// 
-    /// If anyone wishes to complain, they can send their complaint to me by
-    /// throwing it in the trash.
+    /// Returns a message for the player when an autosave finishes.
+    pub fn autosave_poll(&mut self) -> Option<String> {
+        let report = self.autosave.finished().map(|(event, result)| match result {
+            Ok(()) => format!("Autosaved after {}.", event),
+            Err(err) => format!("Autosave after {} failed: {}", event, err),
+        });
+        let now = Instant::now();
+        if !self.save_slot.is_empty()
+            && let Some(event) = self.autosave.due(now) {
+            let save = self.save_str();
+            self.autosave.start(event, self.save_slot.clone(), save, self.save_backups, now);
+        }
+        report
+    }
+
+    /// # Save Str(ing)
+    /// 
+    /// Writes the whole state as a versioned save, see SaveFile for the layout.
     pub fn save_str(&mut self) -> String {
+        self.run_machine();
+        self.log_machine_run(true);
         // update game length and last prior save
         self.game_length += Instant::now() - self.last_prior_save;
         self.last_prior_save = Instant::now();
-        let mut output = String::new();
-        output += format!("{},", self.player_name).as_str();
-        output += format!("{},", self.money).as_str();
-        output += format!("{},", self.entropy).as_str();
-        output += format!("{},", self.machine.level).as_str();
-        output += format!("{},", self.game_length.as_secs_f64()).as_str();
-        output
+        let now = Instant::now();
+        let wall_now = SystemTime::now();
+        let mut save = SaveFile::new();
+        let mut common = Section::new("common");
+        common.set("player_name", &self.player_name);
+        common.set("money", self.money.save_str());
+        common.set("entropy", self.entropy);
+        common.set("current_bet", self.current_bet.save_str());
+        common.set("game_length", self.game_length.as_secs_f64());
+        common.set("escrow", self.escrow.save_str());
+        common.set("save_backups", self.save_backups);
+        common.set("autosave_interval", self.autosave.interval.as_secs());
+        common.set("tainted", self.tainted);
+        common.set("harvest_fraction", self.harvest_fraction);
+        common.set("saved_at", wall_clock(wall_now));
+        save.sections.push(common);
+        save.sections.push(self.machine.save_section());
+        save.sections.push(save_research(&self.machine.research));
+        save.sections.push(self.machine.contracts.save_section());
+        save.sections.push(self.market.save_section());
+        save.sections.push(save_rng(&self.rng));
+        for game in self.game_data.iter() {
+            save.sections.push(game.save_section(now, wall_now));
+        }
+        if let Some(league) = &self.league {
+            save.sections.push(league.save_section(now, wall_now));
+        }
+        save.sections.push(self.ledger.save_section());
+        save.to_string()
     }
-    
-    /// # Load State
-    /// 
-    /// Loads the common state data from the file.
-    pub fn load_state(&mut self, file: String) {
-        let splits: Vec<&str> = file.split(',').collect::<Vec<&str>>();
-        self.player_name = splits.get(0).unwrap().to_string();
-        self.money = splits[1].parse::<f64>().unwrap();
-        self.entropy = splits[2].parse::<f64>().unwrap();
-        self.machine.level = splits[3].parse::<f64>().unwrap();
-        self.game_length = Duration::from_secs_f64(splits[4].parse::<f64>().unwrap());
-        self.last_prior_save = Instant::now();
+
+    /// # Load
+    /// 
+    /// Reads a state from a save, migrating the old CSV saves as it goes. Nothing is
+    /// half loaded, if any of it can't be read the whole save is rejected.
+    pub fn load(file: &str) -> Result<Self, SaveError> {
+        let mut state = Self::empty();
+        if SaveFile::is_save_file(file) {
+            state.load_sections(&SaveFile::parse(file)?)?;
+        } else {
+            state.load_legacy(file)?;
+        }
+        state.last_prior_save = Instant::now();
+        state.machine_run_to = state.game_length;
+        state.close_unfinished_bets();
+        Ok(state)
+    }
+
+    /// Closes the bets that were still running when the save was made, so quitting
+    /// mid bet can't dodge how it would have gone. Each game's bet is closed by its
+    /// early exit rule, and games that settle as they stand give the stake back, as
+    /// there's nothing played out to settle on and a crash shouldn't cost the bet.
+    /// Stakes held by older saves, which didn't say which game held them, go back too.
+    fn close_unfinished_bets(&mut self) {
+        let mut games = std::mem::take(&mut self.game_data);
+        for game in games.iter_mut().filter(|game| !game.escrow.is_zero()) {
+            let settlement = game.leave_early().unwrap_or_else(|| game.refund());
+            self.escrow -= settlement.stake.min(self.escrow);
+            self.credit_payout(settlement.payout,
+                format!("{} bet left unfinished", game.name).as_str());
+        }
+        self.game_data = games;
+        let refund = std::mem::take(&mut self.escrow);
+        self.credit_payout(refund, "Refund of unfinished bets");
+    }
+
+    /// Reads the sections of a versioned save.
+    fn load_sections(&mut self, save: &SaveFile) -> Result<(), SaveError> {
+        let now = Instant::now();
+        let wall_now = SystemTime::now();
+        let common = save.section("common")?;
+        self.player_name = common.parse("player_name")?;
+        self.money = common.parse_money("money")?;
+        self.entropy = common.parse("entropy")?;
+        self.current_bet = common.parse_money("current_bet")?;
+        self.game_length = common.parse_secs("game_length")?;
+        self.escrow = common.parse_money("escrow")?;
+        self.save_backups = common.parse_or("save_backups", DEFAULT_SAVE_BACKUPS)?;
+        self.autosave.interval = Duration::from_secs(common.parse_or("autosave_interval",
+            DEFAULT_AUTOSAVE_INTERVAL.as_secs())?);
+        self.tainted = common.parse_or("tainted", false)?;
+        self.harvest_fraction = common.parse_or("harvest_fraction", DEFAULT_HARVEST_FRACTION)?;
+        self.machine = Machine::load_section(save.section("machine")?)?;
+        // Saves from before research had every game open, so they keep them.
+        self.machine.research = save.sections_named("research").next()
+            .map(load_research)
+            .transpose()?
+            .unwrap_or_else(|| Research::GAMES.to_vec());
+        self.machine.contracts = save.sections_named("contracts").next()
+            .map(Contracts::load_section)
+            .transpose()?
+            .unwrap_or_default();
+        // Older saves don't say when they were made, so the machine didn't run while away.
+        let saved_at = common.get("saved_at").map(|_| common.parse_wall_clock("saved_at"))
+            .transpose()?;
+        self.rng = load_rng(save.section("rng")?)?;
+        self.market = match save.sections_named("market").next() {
+            Some(section) => Market::load_section(section)?,
+            None => Market::new(self.rng.random()),
+        };
+        self.game_data = save.sections_named("game")
+            .map(|section| GameCommonData::load_section(section, now, wall_now))
+            .collect::<Result<_, _>>()?;
+        self.league = save.sections_named("league").next()
+            .map(|section| League::load_section(section, now, wall_now))
+            .transpose()?;
+        self.ledger = save.sections_named("ledger").next()
+            .map(Ledger::load_section)
+            .transpose()?
+            .unwrap_or_default();
+        if let Some(saved_at) = saved_at {
+            self.run_offline(saved_at, wall_now);
+        }
+        Ok(())
+    }
+
+    /// Reads an old CSV save, a line of name,money,entropy,level,game_length,escrow,
+    /// then [section] blocks for games that carry on between visits.
+    fn load_legacy(&mut self, file: &str) -> Result<(), SaveError> {
+        let (common, sections) = match file.find("\n[") {
+            Some(split) => (&file[..split], &file[split + 1..]),
+            None => (file, ""),
+        };
+        let splits: Vec<&str> = common.split(',').collect::<Vec<&str>>();
+        let field = |i: usize, name: &str| splits.get(i).copied()
+            .ok_or_else(|| SaveError::BadLegacy(name.to_string()));
+        // Names used to be saved with the line break typed after them.
+        self.player_name = field(0, "player name")?.trim().to_string();
+        self.money = Money::load_str(field(1, "money")?)
+            .ok_or_else(|| SaveError::BadLegacy(String::from("money")))?;
+        self.entropy = field(2, "entropy")?.trim().parse::<f64>()
+            .map_err(|_| SaveError::BadLegacy(String::from("entropy")))?;
+        self.machine.level = Machine::level_from_save(field(3, "machine level")?)
+            .ok_or_else(|| SaveError::BadLegacy(String::from("machine level")))?;
+        self.machine.research = Research::GAMES.to_vec();
+        self.market = Market::new(self.rng.random());
+        self.game_length = field(4, "game length")?.trim().parse::<f64>().ok()
+            .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+            .ok_or_else(|| SaveError::BadLegacy(String::from("game length")))?;
+        self.escrow = splits.get(5).and_then(|s| Money::load_str(s)).unwrap_or_default();
+        let lines: Vec<&str> = sections.lines().filter(|l| !l.trim().is_empty()).collect();
+        let mut start = 0;
+        while start < lines.len() {
+            let end = lines[start + 1..].iter().position(|l| l.starts_with('['))
+                .map_or(lines.len(), |p| start + 1 + p);
+            if lines[start] == "[league]" {
+                self.league = Some(League::load_str(&lines[start + 1..end])
+                    .ok_or(SaveError::BadLeague)?);
+            }
+            start = end;
+        }
+        Ok(())
     }
 }
 
+/// # Save RNG
+/// 
+/// Writes the random number generator to a [rng] section, by its seed, stream, and
+/// how far through the stream it is, so a loaded game carries on the same sequence.
+fn save_rng(rng: &ChaCha8Rng) -> Section {
+    let mut section = Section::new("rng");
+    let seed: String = rng.get_seed().iter().map(|b| format!("{:02x}", b)).collect();
+    section.set("seed", seed);
+    section.set("stream", rng.get_stream());
+    section.set("word_pos", rng.get_word_pos());
+    section
+}
+
+/// # Load RNG
+/// 
+/// Reads the random number generator from a section written by save_rng.
+fn load_rng(section: &Section) -> Result<ChaCha8Rng, SaveError> {
+    let seed = section.parse_with("seed", |hex| {
+        if hex.len() != 64 {
+            return None;
+        }
+        let mut seed = [0u8; 32];
+        for (i, byte) in seed.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(hex.get(i * 2..i * 2 + 2)?, 16).ok()?;
+        }
+        Some(seed)
+    })?;
+    let mut rng = ChaCha8Rng::from_seed(seed);
+    rng.set_stream(section.parse("stream")?);
+    rng.set_word_pos(section.parse("word_pos")?);
+    Ok(rng)
+}
+
 pub enum GameState {
     MainMenu,
     SaveScreen,
     LoadScreen,
-    GameScreen
+    GameScreen,
+    MachineScreen,
 }
 
 pub enum ButtonAction {
@@ -138,4 +841,86 @@ pub enum ButtonAction {
     EndBet,
     /// Button to buy out the kickout Timer.
     Buyout,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{keno_game::keno::Keno, wheel_game::wheel::MoneyWheel};
+
+    #[test]
+    fn migrates_old_csv_saves() {
+        let state = CommonState::load("Ada\n,240,100,2.5,60,").unwrap();
+        assert_eq!(state.player_name, "Ada");
+        assert_eq!(state.money, Money::from_pence(240));
+        assert_eq!(state.entropy, 100.0);
+        assert_eq!(state.machine.level, 2);
+        assert_eq!(state.game_length, Duration::from_secs(60));
+        assert_eq!(state.machine.research, Research::GAMES.to_vec());
+        assert!(state.escrow.is_zero());
+    }
+
+    #[test]
+    fn old_csv_stakes_go_back() {
+        let state = CommonState::load("Ada,240,100,0,60,12").unwrap();
+        assert_eq!(state.money, Money::from_pence(252));
+        assert!(state.escrow.is_zero());
+    }
+
+    #[test]
+    fn unfinished_bets_close_by_their_early_exit_rule() {
+        let mut state = CommonState::new(String::from("Ada"));
+        state.money = Money::from_pence(100);
+        let now = Instant::now();
+        let mut keno = Keno::new();
+        state.try_bet(&mut keno.base, Money::from_pence(10), now).unwrap();
+        state.store_game(&keno.base);
+        let mut wheel = MoneyWheel::new();
+        state.try_bet(&mut wheel.base, Money::from_pence(20), now).unwrap();
+        state.store_game(&wheel.base);
+        let loaded = CommonState::load(&state.save_str()).unwrap();
+        // Keno settles as it stands, so the stake goes back. The wheel's is lost.
+        assert_eq!(loaded.money, Money::from_pence(80));
+        assert!(loaded.escrow.is_zero());
+        assert!(loaded.game_data.iter().all(|game| game.escrow.is_zero()));
+    }
+
+    #[test]
+    fn rejects_unreadable_csv_fields() {
+        assert_eq!(CommonState::load("Ada,lots,100,0,60").err(),
+            Some(SaveError::BadLegacy(String::from("money"))));
+        assert_eq!(CommonState::load("Ada,240,100,-1,60").err(),
+            Some(SaveError::BadLegacy(String::from("machine level"))));
+        assert_eq!(CommonState::load("Ada,240").err(),
+            Some(SaveError::BadLegacy(String::from("entropy"))));
+    }
+
+    #[test]
+    fn versioned_saves_read_back() {
+        let mut state = CommonState::new(String::from("Ada"));
+        state.money = Money::from_lsd(3, 4, 5, 1);
+        state.entropy = 42.5;
+        let loaded = CommonState::load(&state.save_str()).unwrap();
+        assert_eq!(loaded.player_name, "Ada");
+        assert_eq!(loaded.money, state.money);
+        assert_eq!(loaded.machine.level, state.machine.level);
+    }
+    #[test]
+    fn only_the_tick_fails_overdue_contracts() {
+        let mut state = CommonState::new(String::from("Ada"));
+        state.machine.level = 5;
+        let mut contract = Contract::issue(0, state.machine.entropy_cap(), Duration::ZERO);
+        contract.deadline = Duration::ZERO;
+        state.machine.contracts.active = Some(contract);
+        state.add_entropy(1.0);
+        state.spend_entropy(2.0, "Test").unwrap();
+        state.credit_payout(Money::from_pence(1), "Test");
+        state.spend_money(Money::from_pence(1), "Test").unwrap();
+        assert_eq!(state.machine.contracts.failed, 0);
+        assert_eq!((state.money, state.machine.level), (Money::from_pounds(1), 5));
+        state.tick();
+        assert_eq!(state.machine.contracts.failed, 1);
+        assert!(state.machine.contracts.active.is_none());
+        assert!(state.machine.level < 5);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,95 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use miniz_oxide::{deflate::compress_to_vec_zlib, inflate::decompress_to_vec_zlib_with_limit};
+
+use crate::{common_state::CommonState, save::SaveError};
+
+/// What every export starts with, with the version of the blob's layout.
+pub const EXPORT_PREFIX: &str = "IMX1:";
+/// The largest a save can unpack to. Well past any real save, it stops a bad blob
+/// from eating all the memory.
+const EXPORT_SIZE_MAX: usize = 16 * 1024 * 1024;
+
+/// # Export State
+/// 
+/// The whole game as one line of text to share. It's the save, zlib compressed and
+/// base64 encoded, after the export prefix. The zlib checksum catches blobs that
+/// were cut short or mistyped.
+pub fn export_state(common_state: &mut CommonState) -> String {
+    let save = common_state.save_str();
+    format!("{}{}", EXPORT_PREFIX, URL_SAFE_NO_PAD.encode(compress_to_vec_zlib(save.as_bytes(), 9)))
+}
+
+/// # Import State
+/// 
+/// Reads a game from an export. The blob isn't signed by this install, so there's
+/// no telling what's been done to it, and the run is marked as tainted.
+pub fn import_state(blob: &str) -> Result<CommonState, SaveError> {
+    // Pasting tends to bring along spaces and line breaks.
+    let blob: String = blob.chars().filter(|c| !c.is_whitespace()).collect();
+    if blob.is_empty() {
+        return Err(SaveError::Empty);
+    }
+    let encoded = blob.strip_prefix(EXPORT_PREFIX).ok_or(SaveError::NotExport)?;
+    let packed = URL_SAFE_NO_PAD.decode(encoded)
+        .map_err(|err| SaveError::BadExport(err.to_string()))?;
+    let save = decompress_to_vec_zlib_with_limit(&packed, EXPORT_SIZE_MAX)
+        .map_err(|err| SaveError::BadExport(err.to_string()))?;
+    let save = String::from_utf8(save)
+        .map_err(|_| SaveError::BadExport(String::from("it doesn't unpack to text")))?;
+    let mut state = CommonState::load(&save)?;
+    state.tainted = true;
+    Ok(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::money::Money;
+
+    fn exported() -> String {
+        let mut state = CommonState::new(String::from("Ada"));
+        state.money = Money::from_lsd(12, 3, 4, 0);
+        state.entropy = 17.25;
+        export_state(&mut state)
+    }
+
+    #[test]
+    fn imports_what_was_exported() {
+        let blob = exported();
+        assert!(blob.starts_with(EXPORT_PREFIX));
+        let state = import_state(&blob).unwrap();
+        assert_eq!(state.player_name, "Ada");
+        assert_eq!(state.money, Money::from_lsd(12, 3, 4, 0));
+        // Saving runs the machine up to now, which can add a sliver.
+        assert!((state.entropy - 17.25).abs() < 0.01);
+        assert!(state.tainted);
+    }
+
+    #[test]
+    fn imports_blobs_broken_up_by_pasting() {
+        let blob = exported();
+        let (head, tail) = blob.split_at(blob.len() / 2);
+        let pasted = format!("  {}\n {}\r\n", head, tail);
+        assert_eq!(import_state(&pasted).unwrap().player_name, "Ada");
+    }
+
+    #[test]
+    fn rejects_bad_blobs() {
+        assert_eq!(import_state(" \n").err(), Some(SaveError::Empty));
+        assert_eq!(import_state("hello").err(), Some(SaveError::NotExport));
+        assert!(matches!(import_state("IMX1:not*base64"), Err(SaveError::BadExport(_))));
+        // Cut short, or mistyped, the checksum doesn't match.
+        let blob = exported();
+        assert!(matches!(import_state(&blob[..blob.len() - 8]), Err(SaveError::BadExport(_))));
+        let mistyped = format!("{}{}", &blob[..blob.len() - 1],
+            if blob.ends_with('A') { 'B' } else { 'A' });
+        assert!(matches!(import_state(&mistyped), Err(SaveError::BadExport(_))));
+        // Packed fine, but not text.
+        let binary = URL_SAFE_NO_PAD.encode(compress_to_vec_zlib(&[0xff, 0xfe, 0x00], 9));
+        assert!(matches!(import_state(&format!("{}{}", EXPORT_PREFIX, binary)),
+            Err(SaveError::BadExport(_))));
+        // Text, but not a save.
+        let text = URL_SAFE_NO_PAD.encode(compress_to_vec_zlib(b"just some text", 9));
+        assert!(import_state(&format!("{}{}", EXPORT_PREFIX, text)).is_err());
+    }
+}
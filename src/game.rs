@@ -1,16 +1,89 @@
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 
-use macroquad::miniquad::gl::WGL_CONTEXT_RESET_NOTIFICATION_STRATEGY_ARB;
+use rand::Rng;
+
+use crate::{ledger::SpendError, money::Money, save::{wall_clock, SaveError, Section}};
 
 /// The current defacto bet duration. Currently set to 30 seconds.
-const BET_DURATION: Duration = Duration::from_secs(30);
+pub const BET_DURATION: Duration = Duration::from_secs(30);
+
+/// How much suspicion is added for each bit of improbability the opponents witness.
+pub const SUSPICION_PER_BIT: f64 = 0.02;
+/// How much each suspicion bracket reduces the payout by. (The X in the brackets.)
+pub const SUSPICION_PAYOUT_STEP: f64 = 0.1;
+/// The lowest a payout can be reduced to by suspicion.
+pub const SUSPICION_PAYOUT_FLOOR: f64 = 1.1;
+
+/// # Early Exit
+/// 
+/// What happens to the stake when the player leaves a bet before it's finished.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EarlyExit {
+    /// The stake is lost.
+    Forfeit,
+    /// This share of the stake is returned.
+    Partial(f64),
+    /// The bet is settled as it stands.
+    Settle,
+}
+
+impl EarlyExit {
+    /// # Save Str(ing)
+    /// 
+    /// The rule for save files, forfeit, settle, or partial:share.
+    pub fn save_str(&self) -> String {
+        match self {
+            EarlyExit::Forfeit => String::from("forfeit"),
+            EarlyExit::Partial(share) => format!("partial:{}", share),
+            EarlyExit::Settle => String::from("settle"),
+        }
+    }
+
+    /// # Load Str(ing)
+    /// 
+    /// Reads a rule written by save_str.
+    pub fn load_str(s: &str) -> Option<Self> {
+        match s.trim() {
+            "forfeit" => Some(EarlyExit::Forfeit),
+            "settle" => Some(EarlyExit::Settle),
+            other => other.strip_prefix("partial:")?.parse::<f64>().ok()
+                .filter(|share| (0.0..=1.0).contains(share))
+                .map(EarlyExit::Partial),
+        }
+    }
+}
+
+/// # Bet Result
+/// 
+/// How a bet came out, for settlement.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BetResult {
+    /// Whether the bet won, pushes aren't wins.
+    pub won: bool,
+    /// What the bet pays per unit staked, stake included, before suspicion is applied.
+    /// 0 for a loss, 1 for a push.
+    pub payout: f64,
+    /// The chance the bet had of winning.
+    pub win_chance: f64,
+    /// The long run return per unit staked.
+    pub expected_return: f64,
+}
+
+/// # Settlement
+/// 
+/// The stake released from escrow, and what was paid out for it.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Settlement {
+    pub stake: Money,
+    pub payout: Money,
+}
 
 /// # Game
 /// 
 /// The data common to all games.
 /// 
 /// Includes common mechanics between them that can be abstracted away easily.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct GameCommonData {
     /// The name of the game.
     pub name: String,
@@ -18,17 +91,21 @@ pub struct GameCommonData {
     /// The minimum bet needed to play the game. 
     /// 
     /// Must be >= 0.
-    pub bet_min: f64,
+    pub bet_min: Money,
     /// The maximum bet that can be used.
     /// 
-    /// Can be +inf, must be greater than Min.
-    pub bet_max: f64,
+    /// Can be Money::MAX, must be greater than Min.
+    pub bet_max: Money,
     /// Sets the payout rate of the game. You multiply the bet by this value times the 
     /// suspicion modifier to get the final payout.
     pub base_payout: f64,
 
     /// The current bet locked in. Should be between Min and Max bet, but may be at 0.0, if 
-    pub current_bet: f64,
+    pub current_bet: Money,
+    /// The stakes of the active bet, held until it's settled.
+    pub escrow: Money,
+    /// What happens to the stake if the player leaves a bet early.
+    pub early_exit: EarlyExit,
 
     /// The start time of the current active bet.
     /// 
@@ -44,9 +121,9 @@ pub struct GameCommonData {
     /// The gains which are expected by the game up to this point.
     /// 
     /// This is not fixed, but updated based on the bets as they are made.
-    pub expected_gains: f64,
+    pub expected_gains: Money,
     /// The real gains which were achieved by the player.
-    pub real_gains: f64,
+    pub real_gains: Money,
     /// The current suspicion level of the opponents.
     /// 
     /// Bounded between 0.0 and 1.0.
@@ -69,6 +146,8 @@ pub struct GameCommonData {
     /// How long the maximum timeout can last. This is reduced by the suspicion at time 
     /// of being kicked out down to 1/2 of the max duration.
     pub kickout_length_max: Duration,
+    /// How long the current kick out lasts. Set when kicked out.
+    pub kickout_length: Duration,
     /// How much time is left for the current kick out. (updated during kickout update)
     pub kickout_remaining: Duration,
     /// The time the player was kicked out of the game, used to determine when they will be 
@@ -82,31 +161,34 @@ pub struct GameCommonData {
     /// (real_gains - expected_gains) * kickout_buyout_factor * percent_time_remaining.
     pub buyout_factor: f64,
     /// The current Kickout buyout price.
-    pub current_kickout_buyout: f64,
+    pub current_kickout_buyout: Money,
 }
 
 impl GameCommonData {
     /// # New
     /// 
     /// Simple new to skip over the obviously empty parts at the start.
-    pub fn new(name: String, bet_min: f64, bet_max: f64, base_payout: f64, timeout_length_max: Duration) -> Self {
+    pub fn new(name: String, bet_min: Money, bet_max: Money, base_payout: f64, timeout_length_max: Duration) -> Self {
         Self {
             name,
             bet_min,
             bet_max,
             base_payout,
             current_bet: bet_min,
+            escrow: Money::ZERO,
+            early_exit: EarlyExit::Settle,
             bet_start: None,
             expected_wins: 0.0,
             real_wins: 0.0,
-            expected_gains: 0.0,
-            real_gains: 0.0,
+            expected_gains: Money::ZERO,
+            real_gains: Money::ZERO,
             suspicion: 0.0,
             kickout_length_max: timeout_length_max,
             kickout_start_time: None,
+            kickout_length: Duration::ZERO,
             buyout_factor: 0.0,
             kickout_remaining: Duration::ZERO,
-            current_kickout_buyout: 0.0,
+            current_kickout_buyout: Money::ZERO,
         }
     }
 
@@ -114,8 +196,7 @@ impl GameCommonData {
     /// 
     /// Returns how much time is left until the kickout ends.
     pub fn kickout_time_remaining(&self) -> f64 {
-        if let Some(start) = self.kickout_start_time {
-            let end = start + self.kickout_length_max;
+        if let Some(end) = self.kickout_end_time() {
             end - Instant::now()
         } else {
             Duration::ZERO
@@ -128,11 +209,7 @@ impl GameCommonData {
     /// 
     /// If not currently kicked out, it returns None.
     pub fn kickout_end_time(&self) -> Option<Instant> {
-        if let Some(start) = self.kickout_start_time {
-            Some(start + self.kickout_length_max)
-        } else {
-            None
-        }
+        self.kickout_start_time.map(|start| start + self.kickout_length)
     }
 
     /// # Kickout Check and Update
@@ -141,16 +218,14 @@ impl GameCommonData {
     /// 
     /// If not kicked out it just returns false.
     /// 
-    /// If kicked out, but past end time, it returns false and removes our start time.
+    /// If kicked out, but past end time, it returns false, removes our start time, and 
+    /// resets the kickout data, as they've let you back in with a clean slate.
     /// 
     /// If kicked out and before the end time it returns true (for kicked out).
     /// 
     /// ## Note
     /// 
     /// It's advised to only run this once per frame/update.
-    /// 
-    /// This does get rid of the kickout start time, but does not reset any other 
-    /// kickout data.
     pub fn kickout_update(&mut self, now: Instant) -> bool {
         if let Some(end) = self.kickout_end_time() {
             // if we are currently kicked out.
@@ -160,27 +235,198 @@ impl GameCommonData {
                 // update kickout_remaining
                 self.kickout_remaining = Duration::ZERO;
                 // zero out buyout.
-                self.current_kickout_buyout = 0.0;
-                true
+                self.current_kickout_buyout = Money::ZERO;
+                self.reset_kickout();
+                false
             } else {
                 // Update the kickout remaining based on current time.
                 self.kickout_remaining = end - now;
                 self.current_kickout_buyout = self.calculate_buyout(now);
-                false
+                true
             }
         } else { // if not currently kicked out, then we don't need to do anything else.
             false
         }
     }
 
+    /// # Kick Out
+    /// 
+    /// Kicks the player out of the game, starting the kickout timer.
+    /// 
+    /// The kickout lasts for the max length, reduced by the suspicion at the time down
+    /// to half the max length.
+    pub fn kick_out(&mut self, now: Instant) {
+        self.kickout_length = self.kickout_length_max
+            .mul_f64(1.0 - self.suspicion.clamp(0.0, 1.0) / 2.0);
+        self.kickout_start_time = Some(now);
+        self.kickout_remaining = self.kickout_length;
+        self.current_kickout_buyout = self.calculate_buyout(now);
+        self.bet_start = None;
+    }
+
+    /// # Kickout Roll
+    /// 
+    /// Rolls to see if the opponents kick the player out, based on the current suspicion.
+    /// 
+    /// Only suspicion at or above 0.75 can trigger it, with a (Suspicion - 0.75) * 4 
+    /// chance, guaranteed at 1.0.
+    /// 
+    /// Returns true if the player was kicked out.
+    pub fn kickout_roll<R: Rng>(&mut self, rng: &mut R, now: Instant) -> bool {
+        let chance = ((self.suspicion - 0.75) * 4.0).clamp(0.0, 1.0);
+        if chance > 0.0 && rng.random_bool(chance) {
+            self.kick_out(now);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// # Add Suspicion
+    /// 
+    /// Adds suspicion based on the improbability (in bits) of something the opponents
+    /// saw happen.
+    pub fn add_suspicion(&mut self, bits: f64) {
+        self.suspicion = (self.suspicion + bits.max(0.0) * SUSPICION_PER_BIT).clamp(0.0, 1.0);
+    }
+
+    /// # Suspicion Payout
+    /// 
+    /// Reduces the given payout rate according to the suspicion brackets.
+    /// 
+    /// Payouts already at or below the floor are left alone.
+    pub fn suspicion_payout(&self, payout: f64) -> f64 {
+        if payout <= SUSPICION_PAYOUT_FLOOR {
+            return payout;
+        }
+        let steps = if self.suspicion < 0.25 {
+            0.0
+        } else if self.suspicion < 0.5 {
+            1.0
+        } else if self.suspicion < 0.75 {
+            2.0
+        } else {
+            4.0
+        };
+        (payout - SUSPICION_PAYOUT_STEP * steps).max(SUSPICION_PAYOUT_FLOOR)
+    }
+
+    /// # Record Outcome
+    /// 
+    /// Records the result of a bet against what the game expected.
+    /// 
+    /// - win_chance is the chance the bet had of winning.
+    /// - expected_return is how much the bet was expected to return on average.
+    /// - won and real_return are what actually happened.
+    pub fn record_outcome(&mut self, win_chance: f64, expected_return: Money, won: bool, 
+    real_return: Money) {
+        self.expected_wins += win_chance;
+        if won {
+            self.real_wins += 1.0;
+        }
+        self.expected_gains += expected_return;
+        self.real_gains += real_return;
+    }
+
+    /// # Set Bet
+    /// 
+    /// Sets the current bet, so long as it's within the game's bounds and the 
+    /// money available.
+    /// 
+    /// Returns why the bet was rejected, if it was.
+    pub fn set_bet(&mut self, bet: Money, money: Money) -> Result<(), SpendError> {
+        if bet < self.bet_min || bet > self.bet_max {
+            Err(SpendError::BetOutOfRange { bet, min: self.bet_min, max: self.bet_max })
+        } else if bet > money {
+            Err(SpendError::InsufficientFunds { needed: bet, available: money })
+        } else {
+            self.current_bet = bet;
+            Ok(())
+        }
+    }
+    
+    /// # Hold Stake
+    /// 
+    /// Puts a stake into escrow and starts the bet.
+    pub fn hold_stake(&mut self, stake: Money, now: Instant) {
+        self.escrow += stake;
+        self.bet_start = Some(now);
+    }
+
+    /// # Settle Part
+    /// 
+    /// Settles part of the escrow, for games with several bets riding at once. The
+    /// suspicion payout modifier is applied to winnings, and the outcome recorded.
+    /// 
+    /// Once the escrow is empty, the bet is over.
+    pub fn settle_part(&mut self, stake: Money, result: BetResult) -> Settlement {
+        let stake = stake.min(self.escrow);
+        self.escrow -= stake;
+        let payout = if result.payout > 0.0 {
+            stake.mul_ratio(self.suspicion_payout(result.payout))
+        } else {
+            Money::ZERO
+        };
+        self.record_outcome(result.win_chance, stake.mul_ratio(result.expected_return),
+            result.won, payout);
+        if self.escrow.is_zero() {
+            self.bet_start = None;
+        }
+        Settlement { stake, payout }
+    }
+
+    /// # Settle
+    /// 
+    /// Settles everything in escrow.
+    pub fn settle(&mut self, result: BetResult) -> Settlement {
+        self.settle_part(self.escrow, result)
+    }
+
+    /// # Forfeit
+    /// 
+    /// Empties the escrow, paying nothing. For when the player is caught mid bet.
+    pub fn forfeit(&mut self) -> Settlement {
+        let stake = self.escrow;
+        self.escrow = Money::ZERO;
+        self.bet_start = None;
+        Settlement { stake, payout: Money::ZERO }
+    }
+
+    /// # Refund
+    /// 
+    /// Empties the escrow, paying the stake back. For bets that can't be finished.
+    pub fn refund(&mut self) -> Settlement {
+        let mut settlement = self.forfeit();
+        settlement.payout = settlement.stake;
+        settlement
+    }
+
+    /// # Leave Early
+    /// 
+    /// Ends the bet before it's finished, according to the game's early exit rule.
+    /// 
+    /// Returns None if the game settles early exits as they stand, which the game
+    /// needs to do itself.
+    pub fn leave_early(&mut self) -> Option<Settlement> {
+        match self.early_exit {
+            EarlyExit::Settle => None,
+            EarlyExit::Forfeit => Some(self.forfeit()),
+            EarlyExit::Partial(share) => {
+                let mut settlement = self.forfeit();
+                settlement.payout = settlement.stake.mul_ratio(share);
+                Some(settlement)
+            },
+        }
+    }
+
     /// # Reset Kickout
     /// 
     /// Resets kickout data. That means Suspicion, Expected and real Wins, and expected 
     /// and real gains.
     pub fn reset_kickout(&mut self) {
         self.suspicion = 0.0;
-        self.expected_gains = 0.0;
-        self.real_gains = 0.0;
+        self.expected_gains = Money::ZERO;
+        self.real_gains = Money::ZERO;
         self.expected_wins = 0.0;
         self.real_wins = 0.0;
     }
@@ -193,11 +439,110 @@ impl GameCommonData {
     /// and multiplied further by the buyout factor.
     /// 
     /// Buyouts are rounded to 5 second increments.
-    fn calculate_buyout(&self, now: Instant) -> f64 {
-        let buyout_max = self.buyout_factor * (self.real_gains - self.expected_gains) * 2.0;
-        let time_remaining_factor = (self.kickout_time_remaining() / 
+    fn calculate_buyout(&self, now: Instant) -> Money {
+        let buyout_max = (self.real_gains - self.expected_gains)
+            .mul_ratio(self.buyout_factor * 2.0);
+        let remaining = self.kickout_end_time()
+            .map_or(0.0, |end| (end - now).as_secs_f64());
+        let time_remaining_factor = (remaining / 
             self.kickout_length_max.as_secs_f64() / 5.0).ceil();
-        buyout_max * time_remaining_factor
+        buyout_max.mul_ratio(time_remaining_factor)
+    }
+
+    /// # Save Section
+    /// 
+    /// Writes the game's data to a [game] save section. Instants don't outlive the
+    /// program, so kickouts are saved by the real time they end, and the time they had
+    /// left. Stakes still held are saved too, so the bet can be closed on load.
+    pub fn save_section(&self, now: Instant, wall_now: SystemTime) -> Section {
+        let mut section = Section::new("game");
+        self.save_into(&mut section, now, wall_now);
+        section
+    }
+
+    /// # Save Into
+    /// 
+    /// Writes the game's data into a section, for games that save more alongside it.
+    pub fn save_into(&self, section: &mut Section, now: Instant, wall_now: SystemTime) {
+        section.set("name", &self.name);
+        section.set("bet_min", self.bet_min.save_str());
+        section.set("bet_max", self.bet_max.save_str());
+        section.set("base_payout", self.base_payout);
+        section.set("current_bet", self.current_bet.save_str());
+        section.set("expected_wins", self.expected_wins);
+        section.set("real_wins", self.real_wins);
+        section.set("expected_gains", self.expected_gains.save_str());
+        section.set("real_gains", self.real_gains.save_str());
+        section.set("suspicion", self.suspicion);
+        section.set("kickout_length_max", self.kickout_length_max.as_secs_f64());
+        section.set("kickout_length", self.kickout_length.as_secs_f64());
+        let remaining = self.kickout_end_time()
+            .map_or(Duration::ZERO, |end| end.saturating_duration_since(now));
+        section.set("kickout_remaining", remaining.as_secs_f64());
+        if !remaining.is_zero() {
+            section.set("kickout_end", wall_clock(wall_now + remaining));
+        }
+        section.set("buyout_factor", self.buyout_factor);
+        section.set("escrow", self.escrow.save_str());
+        section.set("early_exit", self.early_exit.save_str());
+    }
+
+    /// # Load Section
+    /// 
+    /// Reads the game's data from a section written by save_into.
+    /// 
+    /// Kickouts carry on running while the game's closed, so one with time left has
+    /// the real time since the save taken off. It never gets longer than it was when
+    /// saved, even if the clock's been wound back. Older saves without the end time
+    /// pick up where they left off.
+    pub fn load_section(section: &Section, now: Instant, wall_now: SystemTime)
+    -> Result<Self, SaveError> {
+        let mut game = Self::new(section.parse("name")?, section.parse_money("bet_min")?,
+            section.parse_money("bet_max")?, section.parse("base_payout")?,
+            section.parse_secs("kickout_length_max")?);
+        game.current_bet = section.parse_money("current_bet")?;
+        game.expected_wins = section.parse("expected_wins")?;
+        game.real_wins = section.parse("real_wins")?;
+        game.expected_gains = section.parse_money("expected_gains")?;
+        game.real_gains = section.parse_money("real_gains")?;
+        game.suspicion = section.parse("suspicion")?;
+        game.kickout_length = section.parse_secs("kickout_length")?;
+        game.buyout_factor = section.parse("buyout_factor")?;
+        if section.get("escrow").is_some() {
+            game.escrow = section.parse_money("escrow")?;
+            game.early_exit = section.parse_with("early_exit", EarlyExit::load_str)?;
+        }
+        let mut remaining = section.parse_secs("kickout_remaining")?;
+        if section.get("kickout_end").is_some() {
+            let end = section.parse_wall_clock("kickout_end")?;
+            remaining = remaining.min(end.duration_since(wall_now).unwrap_or(Duration::ZERO));
+            if remaining.is_zero() {
+                // Served while away, so they've let the player back in.
+                game.reset_kickout();
+            }
+        }
+        if !remaining.is_zero() {
+            game.kickout_start_time = now.checked_sub(game.kickout_length.saturating_sub(remaining));
+            game.kickout_update(now);
+        }
+        Ok(game)
+    }
+
+    /// # Restore From
+    /// 
+    /// Carries the player's progress over from saved data onto a freshly made game.
+    /// The limits and payouts stay as the game defines them.
+    pub fn restore_from(&mut self, saved: &GameCommonData) {
+        self.current_bet = saved.current_bet;
+        self.expected_wins = saved.expected_wins;
+        self.real_wins = saved.real_wins;
+        self.expected_gains = saved.expected_gains;
+        self.real_gains = saved.real_gains;
+        self.suspicion = saved.suspicion;
+        self.kickout_length = saved.kickout_length;
+        self.kickout_remaining = saved.kickout_remaining;
+        self.kickout_start_time = saved.kickout_start_time;
+        self.current_kickout_buyout = saved.current_kickout_buyout;
     }
 
     /// # Bet Time Remaining
@@ -217,11 +562,7 @@ impl GameCommonData {
     }
 
     pub fn bet_end_time(&self, bet_duration: Duration) -> Option<Instant> {
-        if let Some(start) = self.bet_start {
-            Some(start + bet_duration)
-        } else {
-            None
-        }
+        self.bet_start.map(|start| start + bet_duration)
     }
 }
 
@@ -237,5 +578,78 @@ pub fn entropy(p: f64) -> f64 {
     -(p).log2()
 }
 
+/// # Choose
+/// 
+/// The binomial coefficient, n choose k. Done in floating point, as the values get 
+/// big quickly (80 choose 20 is around 3.5e18).
+pub fn choose(n: u32, k: u32) -> f64 {
+    if k > n {
+        return 0.0;
+    }
+    let k = k.min(n - k);
+    (0..k).fold(1.0, |acc, i| acc * (n - i) as f64 / (i + 1) as f64)
+}
+
+/// # Hypergeometric
+/// 
+/// The chance of getting exactly `hits` successes when drawing `draws` items, 
+/// without replacement, from a `population` that contains `successes` winning items.
+/// 
+/// Used by draw games (keno, bingo) and card games for their exact odds.
+pub fn hypergeometric(population: u32, successes: u32, draws: u32, hits: u32) -> f64 {
+    if hits > successes || hits > draws || draws - hits > population - successes {
+        return 0.0;
+    }
+    choose(successes, hits) * choose(population - successes, draws - hits) 
+        / choose(population, draws)
+}
+
+/// # Binomial
+/// 
+/// The exact chance of every number of successes, 0 to n, in n independent trials 
+/// which each succeed with chance p.
+pub fn binomial(n: u32, p: f64) -> Vec<f64> {
+    (0..=n).map(|k| choose(n, k) * p.powi(k as i32) * (1.0 - p).powi((n - k) as i32))
+        .collect()
+}
+
 // Entropy Sum value: Not sure if I'd need it, but it's equal to the sum of all 
-// entropy(p) values for all possible states. This should be unique per game.
\ No newline at end of file
+// entropy(p) values for all possible states. This should be unique per game.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9 * b.abs().max(1.0)
+    }
+
+    #[test]
+    fn chooses() {
+        assert_eq!(choose(5, 2), 10.0);
+        assert_eq!(choose(5, 0), 1.0);
+        assert_eq!(choose(5, 5), 1.0);
+        assert_eq!(choose(3, 5), 0.0);
+        assert!(close(choose(52, 5), 2_598_960.0));
+        assert!(close(choose(80, 20), 3_535_316_142_212_174_320.0));
+    }
+
+    #[test]
+    fn hypergeometric_matches_the_card_odds() {
+        // Exactly one ace in a five card hand.
+        assert!(close(hypergeometric(52, 4, 5, 1), 4.0 * 194_580.0 / 2_598_960.0));
+        // More hits than there are winners, or than were drawn, can't happen.
+        assert_eq!(hypergeometric(52, 4, 5, 5), 0.0);
+        assert_eq!(hypergeometric(80, 10, 3, 4), 0.0);
+    }
+
+    #[test]
+    fn hypergeometric_sums_to_one() {
+        for (population, successes, draws) in [(80, 10, 20), (75, 5, 40), (52, 4, 5)] {
+            let total: f64 = (0..=draws)
+                .map(|hits| hypergeometric(population, successes, draws, hits))
+                .sum();
+            assert!(close(total, 1.0), "{} {} {}", population, successes, draws);
+        }
+    }
+}
\ No newline at end of file
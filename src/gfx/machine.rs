@@ -0,0 +1,227 @@
+use macroquad::{hash, math::vec2, ui::{root_ui, widgets::{Group, InputText}, Layout}};
+
+use crate::{common_state::{CommonState, GameState}, machine::{machine::{BalanceRow, Machine, Manipulation, LEAK_THRESHOLD}, research::{Funding, RESEARCH_TREE}}, money::Money};
+
+/// How far apart the rows of the screen are.
+const ROW_HEIGHT: f32 = 28.0;
+
+/// # Machine Screen
+/// 
+/// The macroquad Improbability Machine screen. Shows the machine, and lets the 
+/// player invest money typed in £sd to raise its entropy cap, buy component and
+/// cooling levels, or sell levels back.
+pub struct MachineScreen {
+    pub invest_input: String,
+    pub sell_input: String,
+    /// The bits to sell or buy on the market.
+    pub market_input: String,
+    /// Whether the balance report is shown.
+    pub show_report: bool,
+    /// Whether the research tree is shown, in place of the report.
+    pub show_research: bool,
+    /// Whether the entropy market is shown, in place of the report.
+    pub show_market: bool,
+    pub msg: String,
+}
+
+impl Default for MachineScreen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MachineScreen {
+    pub fn new() -> Self {
+        Self {
+            invest_input: String::new(),
+            sell_input: String::new(),
+            market_input: String::new(),
+            show_report: false,
+            show_research: false,
+            show_market: false,
+            msg: String::new(),
+        }
+    }
+
+    /// # Draw
+    /// 
+    /// Draws the screen for this frame and handles its buttons.
+    /// 
+    /// Returns the screen to go to, if it's time to leave.
+    pub fn draw(&mut self, common_state: &mut CommonState) -> Option<GameState> {
+        root_ui().label(vec2(20.0, 20.0), "----- Improbability Machine -----");
+        let mut y = 50.0;
+        let lines = [
+            format!("Money: {}  Entropy: {} b", common_state.money, common_state.entropy),
+            format!("Entropy Max: {}  Machine Level: {}", common_state.machine.entropy_cap(),
+                common_state.machine.level),
+            common_state.machine.status(common_state.entropy),
+            format!("{}  Met: {}  Missed: {}",
+                common_state.machine.contracts.summary(common_state.game_time()),
+                common_state.machine.contracts.completed, common_state.machine.contracts.failed),
+            format!("Cooling: Level {}  Sheds: {:.2} heat/s  Next Level: {}",
+                common_state.machine.cooling, common_state.machine.cooling_rate(),
+                common_state.machine.cooling_cost()),
+            format!("Regeneration: {:.3} b/s  Leakage: {:.2}%/s above {:.0} b",
+                common_state.machine.regen_rate(), common_state.machine.leak_rate() * 100.0,
+                common_state.machine.entropy_cap() * LEAK_THRESHOLD),
+            format!("Tier: {}  Next Tier: {}", common_state.machine.tier().name,
+                common_state.machine.next_tier().map_or(String::from("--"),
+                    |tier| format!("{} at Level {}", tier.name, tier.first_level))),
+            format!("Next Level: {}  Next 10 Levels: {}  Sell 1 Level: {}",
+                common_state.machine.upgrade_cost(1), common_state.machine.upgrade_cost(10),
+                common_state.machine.sell_value(1)),
+        ];
+        for line in lines.iter() {
+            root_ui().label(vec2(20.0, y), line);
+            y += ROW_HEIGHT;
+        }
+        for kind in Manipulation::ALL {
+            root_ui().label(vec2(20.0, y), &format!("{} Component: Level {}  Cost Share: {:.0}%  Next Level: {}",
+                kind.name(), common_state.machine.component_level(kind),
+                common_state.machine.cost_share(kind) * 100.0, common_state.machine.component_cost(kind)));
+            if root_ui().button(vec2(560.0, y), format!("Upgrade {}", kind.name()).as_str()) {
+                self.msg = match common_state.upgrade_component(kind) {
+                    Ok(cost) => format!("Upgraded the {} component for {}.", kind.name(), cost),
+                    Err(err) => err,
+                };
+            }
+            y += ROW_HEIGHT;
+        }
+        InputText::new(hash!()).label("Invest (£sd)").position(vec2(20.0, y))
+            .size(vec2(320.0, 24.0)).ui(&mut root_ui(), &mut self.invest_input);
+        y += ROW_HEIGHT;
+        let mut next = None;
+        if root_ui().button(vec2(20.0, y), "Invest") {
+            self.msg = match self.invest_input.trim().to_lowercase().parse::<Money>() {
+                Ok(invest) => {
+                    let before = common_state.machine.level;
+                    match common_state.upgrade_machine(invest) {
+                        Ok((levels, cost)) => {
+                            self.invest_input.clear();
+                            match common_state.machine.unlocked_since(before) {
+                                Some(tier) => format!("Gained {} levels of entropy for {}. The machine is now a {}!",
+                                    levels, cost, tier.name),
+                                None => format!("Gained {} levels of entropy for {}.", levels, cost),
+                            }
+                        },
+                        Err(err) => err,
+                    }
+                },
+                Err(err) => err,
+            };
+        }
+        y += ROW_HEIGHT;
+        InputText::new(hash!()).label("Sell Levels").position(vec2(20.0, y))
+            .size(vec2(320.0, 24.0)).ui(&mut root_ui(), &mut self.sell_input);
+        y += ROW_HEIGHT;
+        if root_ui().button(vec2(20.0, y), "Sell") {
+            self.msg = match self.sell_input.trim().parse::<u64>() {
+                Ok(levels) => match common_state.sell_machine(levels) {
+                    Ok(refund) => {
+                        self.sell_input.clear();
+                        format!("Sold {} levels back for {}.", levels, refund)
+                    },
+                    Err(err) => err,
+                },
+                Err(_) => String::from("Give the number of levels to sell."),
+            };
+        }
+        if root_ui().button(vec2(70.0, y), "Upgrade Cooling") {
+            self.msg = match common_state.upgrade_cooling() {
+                Ok(cost) => format!("Upgraded the cooling for {}.", cost),
+                Err(err) => err,
+            };
+        }
+        if root_ui().button(vec2(200.0, y), "Balance Report") {
+            self.show_report = !self.show_report;
+            self.show_research = false;
+            self.show_market = false;
+        }
+        if root_ui().button(vec2(320.0, y), "Research") {
+            self.show_research = !self.show_research;
+            self.show_report = false;
+            self.show_market = false;
+        }
+        if root_ui().button(vec2(400.0, y), "Deliver") {
+            self.msg = match common_state.deliver_contract() {
+                Ok(delivered) => common_state.machine.contracts.news.take()
+                    .map_or(delivered.clone(), |news| format!("{} {}", delivered, news)),
+                Err(err) => err,
+            };
+        }
+        if root_ui().button(vec2(470.0, y), "Market") {
+            self.show_market = !self.show_market;
+            self.show_report = false;
+            self.show_research = false;
+        }
+        if root_ui().button(vec2(540.0, y), "Back") {
+            next = Some(GameState::MainMenu);
+        }
+        y += ROW_HEIGHT;
+        root_ui().label(vec2(20.0, y), &self.msg);
+        y += ROW_HEIGHT;
+        if self.show_market {
+            root_ui().label(vec2(20.0, y), &common_state.market.summary(common_state.game_time()));
+            y += ROW_HEIGHT;
+            InputText::new(hash!()).label("Bits").position(vec2(20.0, y))
+                .size(vec2(320.0, 24.0)).ui(&mut root_ui(), &mut self.market_input);
+            y += ROW_HEIGHT;
+            let selling = root_ui().button(vec2(20.0, y), "Sell Entropy");
+            let buying = root_ui().button(vec2(120.0, y), "Buy Entropy");
+            if selling || buying {
+                self.msg = match self.market_input.trim().parse::<f64>() {
+                    Ok(bits) if selling => match common_state.sell_entropy(bits) {
+                        Ok(value) => {
+                            self.market_input.clear();
+                            format!("Sold {:.2} b for {}.", bits, value)
+                        },
+                        Err(err) => err,
+                    },
+                    Ok(bits) => match common_state.buy_entropy(bits) {
+                        Ok(cost) => {
+                            self.market_input.clear();
+                            format!("Bought {:.2} b for {}.", bits, cost)
+                        },
+                        Err(err) => err,
+                    },
+                    Err(_) => String::from("Give the number of bits to trade."),
+                };
+            }
+        }
+        if self.show_research {
+            for project in RESEARCH_TREE.iter() {
+                let done = common_state.machine.researched(project.research);
+                root_ui().label(vec2(20.0, y), &format!("[{}] {}", if done { "Done" } else { "    " },
+                    project));
+                if !done {
+                    let funded = if root_ui().button(vec2(700.0, y), "Money") {
+                        Some(Funding::Money)
+                    } else if root_ui().button(vec2(760.0, y), "Entropy") {
+                        Some(Funding::Entropy)
+                    } else {
+                        None
+                    };
+                    if let Some(funding) = funded {
+                        self.msg = match common_state.fund_research(project.research, funding) {
+                            Ok(()) => format!("Researched {}.", project.name),
+                            Err(err) => err,
+                        };
+                    }
+                }
+                y += ROW_HEIGHT;
+            }
+        }
+        if self.show_report {
+            root_ui().label(vec2(20.0, y), BalanceRow::HEADER);
+            y += ROW_HEIGHT;
+            Group::new(hash!(), vec2(760.0, 240.0)).position(vec2(20.0, y)).layout(Layout::Vertical)
+                .ui(&mut root_ui(), |ui| {
+                    for row in Machine::balance_report() {
+                        ui.label(None, &row.to_string());
+                    }
+                });
+        }
+        next
+    }
+}
@@ -1 +1,3 @@
-pub mod coin;
\ No newline at end of file
+pub mod coin;
+pub mod machine;
+pub mod slots;
\ No newline at end of file
@@ -0,0 +1,300 @@
+use macroquad::{hash, math::vec2, miniquad::window::{clipboard_get, clipboard_set}, ui::{root_ui, widgets::{Button, InputText}}};
+
+use crate::{common_state::{CommonState, GameState}, export::{export_state, import_state}, save::SaveError, save_slots::{copy_slot, delete_slot, list_backups, list_slots, read_slot, read_slot_tainted, rename_slot, restore_backup, write_slot, BackupInfo, SlotInfo}};
+
+/// How far apart the rows of the screen are.
+const ROW_HEIGHT: f32 = 28.0;
+
+/// # Slot Action
+/// 
+/// Something done to the save slots which may overwrite one.
+#[derive(Debug, Clone)]
+enum SlotAction {
+    /// Save the current game to the slot.
+    Save(String),
+    /// Start a new game in the slot.
+    New(String),
+    Rename(String, String),
+    Copy(String, String),
+    /// Import the exported game to the slot.
+    Import(String, String),
+}
+
+/// # Pending
+/// 
+/// An action waiting on the player to say yes.
+#[derive(Debug, Clone)]
+enum Pending {
+    Overwrite(SlotAction),
+    Delete(String),
+}
+
+/// # Recovery
+/// 
+/// An unreadable slot being recovered, from its backups or, if it was tampered 
+/// with, by loading it as a tainted run.
+#[derive(Debug, Clone)]
+struct Recovery {
+    slot: String,
+    backups: Vec<BackupInfo>,
+    tampered: bool,
+}
+
+/// # Slots Screen
+/// 
+/// The macroquad save and load screens. Lists the save slots for the player to pick
+/// from, with a name box for new slots and the targets of renames and copies.
+pub struct SlotsScreen {
+    pub slots: Vec<SlotInfo>,
+    pub selected: Option<usize>,
+    pub name_input: String,
+    pub msg: String,
+    pending: Option<Pending>,
+    recovering: Option<Recovery>,
+}
+
+impl Default for SlotsScreen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SlotsScreen {
+    pub fn new() -> Self {
+        Self {
+            slots: list_slots(),
+            selected: None,
+            name_input: String::new(),
+            msg: String::new(),
+            pending: None,
+            recovering: None,
+        }
+    }
+
+    /// # Refresh
+    /// 
+    /// Reads the slots again, after they've changed.
+    pub fn refresh(&mut self) {
+        self.slots = list_slots();
+        self.selected = self.selected.filter(|&i| i < self.slots.len());
+    }
+
+    /// # Draw
+    /// 
+    /// Draws the screen for this frame and handles its buttons. When saving, the
+    /// buttons save the current game, otherwise they load and manage the slots.
+    /// 
+    /// Returns the screen to go to, if it's time to leave.
+    pub fn draw(&mut self, common_state: &mut CommonState, saving: bool) -> Option<GameState> {
+        let title = if saving { "----- Save Game -----" } else { "----- Load Game -----" };
+        root_ui().label(vec2(20.0, 20.0), title);
+        let mut y = 50.0;
+        if self.slots.is_empty() {
+            root_ui().label(vec2(20.0, y), "No saves yet.");
+            y += ROW_HEIGHT;
+        }
+        for (i, slot) in self.slots.iter().enumerate() {
+            if Button::new(slot.to_string()).position(vec2(20.0, y))
+                .selected(self.selected == Some(i)).ui(&mut root_ui()) {
+                self.selected = Some(i);
+            }
+            y += ROW_HEIGHT;
+        }
+        y += ROW_HEIGHT / 2.0;
+        InputText::new(hash!()).label("Name").position(vec2(20.0, y)).size(vec2(320.0, 24.0))
+            .ui(&mut root_ui(), &mut self.name_input);
+        y += ROW_HEIGHT;
+        let selected = self.selected.map(|i| self.slots[i].name.clone());
+        let name = self.name_input.trim().to_string();
+        let mut next = None;
+        if let Some(Recovery { slot, backups, tampered }) = self.recovering.clone() {
+            root_ui().label(vec2(20.0, y), &format!("Recover {} from a backup?", slot));
+            y += ROW_HEIGHT;
+            for backup in backups.iter() {
+                if root_ui().button(vec2(20.0, y), backup.to_string()) && backup.summary.is_ok() {
+                    self.recovering = None;
+                    match restore_backup(&slot, backup.backup) {
+                        Ok(loaded) => {
+                            *common_state = loaded;
+                            next = Some(GameState::MainMenu);
+                        },
+                        Err(err) => next = self.finish(Err(err)),
+                    }
+                }
+                y += ROW_HEIGHT;
+            }
+            if tampered && root_ui().button(vec2(20.0, y), "Load as Tainted Run") {
+                self.recovering = None;
+                match read_slot_tainted(&slot) {
+                    Ok(loaded) => {
+                        *common_state = loaded;
+                        next = Some(GameState::MainMenu);
+                    },
+                    Err(err) => next = self.finish(Err(err)),
+                }
+            }
+            if tampered {
+                y += ROW_HEIGHT;
+            }
+            if root_ui().button(vec2(20.0, y), "Cancel") {
+                self.recovering = None;
+                self.msg = String::from("Nothing loaded.");
+            }
+        } else if let Some(pending) = self.pending.clone() {
+            let question = match &pending {
+                Pending::Overwrite(_) => "That slot's taken. Overwrite it?",
+                Pending::Delete(_) => "Delete that slot? It can't be undone.",
+            };
+            root_ui().label(vec2(20.0, y), question);
+            y += ROW_HEIGHT;
+            if root_ui().button(vec2(20.0, y), "Yes") {
+                self.pending = None;
+                let result = match pending {
+                    Pending::Overwrite(action) => self.perform(common_state, action, true),
+                    Pending::Delete(slot) => delete_slot(&slot)
+                        .map(|_| (format!("Deleted {}.", slot), false)),
+                };
+                next = self.finish(result);
+            }
+            if root_ui().button(vec2(80.0, y), "No") {
+                self.pending = None;
+                self.msg = String::from("Nothing changed.");
+            }
+        } else if saving {
+            if root_ui().button(vec2(20.0, y), "Save Here") {
+                next = match selected {
+                    Some(slot) => self.try_action(common_state, SlotAction::Save(slot)),
+                    None => self.pick_first(),
+                };
+            }
+            if root_ui().button(vec2(120.0, y), "Save As Name") {
+                next = self.try_action(common_state, SlotAction::Save(name));
+            }
+            if root_ui().button(vec2(250.0, y), "Export") {
+                clipboard_set(&export_state(common_state));
+                self.msg = String::from("Copied the exported game to the clipboard.");
+            }
+            if root_ui().button(vec2(310.0, y), "Back") {
+                next = Some(GameState::MainMenu);
+            }
+        } else {
+            if root_ui().button(vec2(20.0, y), "Load") {
+                next = match selected.clone() {
+                    Some(slot) => match read_slot(&slot) {
+                        Ok(loaded) => {
+                            *common_state = loaded;
+                            Some(GameState::MainMenu)
+                        },
+                        Err(err) => {
+                            let backups = list_backups(&slot);
+                            let tampered = err.is_tampered();
+                            if (!backups.is_empty() || tampered)
+                                && !matches!(err, SaveError::NoSuchSlot(_)) {
+                                self.recovering = Some(Recovery { slot, backups, tampered });
+                            }
+                            self.finish(Err(err))
+                        },
+                    },
+                    None => self.pick_first(),
+                };
+            }
+            if root_ui().button(vec2(80.0, y), "New Game") {
+                next = self.try_action(common_state, SlotAction::New(name.clone()));
+            }
+            if root_ui().button(vec2(180.0, y), "Delete") {
+                match selected.clone() {
+                    Some(slot) => self.pending = Some(Pending::Delete(slot)),
+                    None => next = self.pick_first(),
+                }
+            }
+            if root_ui().button(vec2(250.0, y), "Rename") {
+                next = match selected.clone() {
+                    Some(slot) => self.try_action(common_state, SlotAction::Rename(slot, name.clone())),
+                    None => self.pick_first(),
+                };
+            }
+            if root_ui().button(vec2(330.0, y), "Copy") {
+                next = match selected {
+                    Some(slot) => self.try_action(common_state, SlotAction::Copy(slot, name.clone())),
+                    None => self.pick_first(),
+                };
+            }
+            if root_ui().button(vec2(390.0, y), "Import") {
+                next = match clipboard_get() {
+                    Some(blob) => self.try_action(common_state, SlotAction::Import(name, blob)),
+                    None => {
+                        self.msg = String::from("Copy an exported game to the clipboard first.");
+                        None
+                    },
+                };
+            }
+            if root_ui().button(vec2(450.0, y), "Back") {
+                next = Some(GameState::MainMenu);
+            }
+        }
+        y += ROW_HEIGHT;
+        root_ui().label(vec2(20.0, y), &self.msg);
+        next
+    }
+
+    /// Asks for a slot to be picked first.
+    fn pick_first(&mut self) -> Option<GameState> {
+        self.msg = String::from("Pick a slot first.");
+        None
+    }
+
+    /// Tries the action without overwriting, asking first if it would.
+    fn try_action(&mut self, common_state: &mut CommonState, action: SlotAction)
+    -> Option<GameState> {
+        // A game saving over its own slot doesn't need asking.
+        let own_slot = matches!(&action, SlotAction::Save(slot) if *slot == common_state.save_slot);
+        match self.perform(common_state, action.clone(), own_slot) {
+            Err(SaveError::SlotExists(_)) => {
+                self.pending = Some(Pending::Overwrite(action));
+                None
+            },
+            result => self.finish(result),
+        }
+    }
+
+    /// Does the action, returning a message for the player and whether to leave the
+    /// screen.
+    fn perform(&mut self, common_state: &mut CommonState, action: SlotAction, overwrite: bool)
+    -> Result<(String, bool), SaveError> {
+        match action {
+            SlotAction::Save(slot) => write_slot(&slot, common_state, overwrite)
+                .map(|_| (format!("Saved to {}.", slot), false)),
+            SlotAction::New(slot) => {
+                let mut new_state = CommonState::new(slot.clone());
+                write_slot(&slot, &mut new_state, overwrite)?;
+                *common_state = new_state;
+                Ok((format!("Started a new game in {}.", slot), true))
+            },
+            SlotAction::Import(slot, blob) => {
+                let mut imported = import_state(&blob)?;
+                write_slot(&slot, &mut imported, overwrite)?;
+                *common_state = imported;
+                Ok((format!("Imported {}'s game to {}.", common_state.player_name, slot), true))
+            },
+            SlotAction::Rename(from, to) => rename_slot(&from, &to, overwrite)
+                .map(|_| (format!("Renamed {} to {}.", from, to), false)),
+            SlotAction::Copy(from, to) => copy_slot(&from, &to, overwrite)
+                .map(|_| (format!("Copied {} to {}.", from, to), false)),
+        }
+    }
+
+    /// Shows how the action went and refreshes the list.
+    fn finish(&mut self, result: Result<(String, bool), SaveError>) -> Option<GameState> {
+        self.refresh();
+        match result {
+            Ok((msg, leave)) => {
+                self.msg = msg;
+                leave.then_some(GameState::MainMenu)
+            },
+            Err(err) => {
+                self.msg = err.to_string();
+                None
+            },
+        }
+    }
+}
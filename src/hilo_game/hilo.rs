@@ -0,0 +1,204 @@
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+
+use crate::{cards::deck::{Card, Deck}, game::{entropy, GameCommonData}, money::Money};
+
+/// The share of the bet each call returns on average.
+pub const HILO_RETURN: f64 = 0.95;
+/// When the shoe gets this low, it's reshuffled before the next round.
+pub const HILO_RESHUFFLE_AT: usize = 10;
+/// The entropy peeking at the next card costs.
+pub const HILO_PEEK_BITS: f64 = 0.5;
+
+/// # Call
+/// 
+/// Whether the player thinks the next card will be higher or lower.
+/// 
+/// Ties always lose.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Call {
+    Higher,
+    Lower,
+}
+
+impl Call {
+    /// # Wins
+    /// 
+    /// Whether the next card wins this call against the current card.
+    pub fn wins(&self, current: &Card, next: &Card) -> bool {
+        match self {
+            Call::Higher => next.rank > current.rank,
+            Call::Lower => next.rank < current.rank,
+        }
+    }
+}
+
+/// # Hi-Lo
+/// 
+/// Guess whether the next card is higher or lower than the current one. Each correct
+/// call multiplies the payout by the odds of the call, and the player can cash out
+/// at any point. One wrong call and everything is lost.
+#[derive(Debug)]
+pub struct HiLo {
+    /// The shoe cards are drawn from.
+    pub deck: Deck,
+    /// The card currently face up.
+    pub current: Option<Card>,
+    /// The current payout multiplier, built up from every correct call.
+    pub multiplier: f64,
+    /// The number of correct calls in a row this bet.
+    pub streak: u32,
+    /// The chance of the streak so far, the product of every call's chance.
+    pub streak_chance: f64,
+    /// The current state of the game.
+    pub state: HiLoState,
+    /// Shared common Data
+    pub base: GameCommonData,
+}
+
+#[derive(Debug)]
+pub enum HiLoState {
+    /// No active bet, the last card and result are shown.
+    Hold,
+    /// A bet is running, calls can be made until the player cashes out or misses.
+    InBet,
+}
+
+impl HiLo {
+    /// Create a new Hi-Lo game with a fresh single deck.
+    pub fn new<R: Rng>(rng: &mut R) -> Self {
+        Self {
+            deck: Deck::new(rng, 1),
+            current: None,
+            multiplier: 1.0,
+            streak: 0,
+            streak_chance: 1.0,
+            state: HiLoState::Hold,
+            base: GameCommonData::new("Hi-Lo".to_string(), Money::from_pence(1), Money::from_pence(100),
+                0.0, Duration::from_secs(60)),
+        }
+    }
+
+    /// # Call Chance
+    /// 
+    /// The exact chance the call wins, given the cards left in the shoe.
+    pub fn call_chance(&self, call: Call) -> f64 {
+        if let Some(current) = self.current {
+            self.deck.chance(|next| call.wins(&current, next))
+        } else {
+            0.0
+        }
+    }
+
+    /// # Call Payout
+    /// 
+    /// How much a winning call multiplies the payout by, HILO_RETURN / P(call).
+    /// 
+    /// A call that can't win pays nothing.
+    pub fn call_payout(&self, call: Call) -> f64 {
+        let chance = self.call_chance(call);
+        if chance > 0.0 {
+            HILO_RETURN / chance
+        } else {
+            0.0
+        }
+    }
+
+    /// # Force Cost
+    /// 
+    /// The entropy cost to force the next card to win the call, the surprisal of
+    /// the call winning.
+    /// 
+    /// Returns None if there's no card left that could win.
+    pub fn force_cost(&self, call: Call) -> Option<f64> {
+        let chance = self.call_chance(call);
+        if chance > 0.0 {
+            Some(entropy(chance))
+        } else {
+            None
+        }
+    }
+
+    /// # Streak Bits
+    /// 
+    /// The cumulative improbability of the current streak, in bits.
+    pub fn streak_bits(&self) -> f64 {
+        entropy(self.streak_chance)
+    }
+
+    /// # Bet
+    /// 
+    /// Starts a bet, dealing the first card face up. Reshuffles if the shoe is low.
+    pub fn bet<R: Rng>(&mut self, rng: &mut R) {
+        if self.deck.remaining() <= HILO_RESHUFFLE_AT {
+            self.deck.reshuffle(rng);
+        }
+        self.current = Some(self.deck.draw(rng));
+        self.multiplier = 1.0;
+        self.streak = 0;
+        self.streak_chance = 1.0;
+        self.base.bet_start = Some(Instant::now());
+    }
+
+    /// # Make Call
+    /// 
+    /// Makes a call, drawing the next card. If forced, the next card is drawn from
+    /// the cards that win the call.
+    /// 
+    /// Returns whether the call won and the surprisal of the call's result.
+    pub fn make_call<R: Rng>(&mut self, rng: &mut R, call: Call, forced: bool) -> (bool, f64) {
+        let current = self.current.expect("A call needs a face up card.");
+        let chance = self.call_chance(call);
+        let next = if forced {
+            self.deck.draw_matching(rng, |next| call.wins(&current, next))
+                .unwrap_or_else(|| self.deck.draw(rng))
+        } else {
+            self.deck.draw(rng)
+        };
+        if self.deck.remaining() == 0 {
+            // The new card stays face up, so it's left out of the fresh shoe.
+            self.deck.reshuffle_without(rng, &[next]);
+        }
+        self.current = Some(next);
+        let won = call.wins(&current, &next);
+        if won {
+            self.multiplier *= HILO_RETURN / chance;
+            self.streak += 1;
+            self.streak_chance *= chance;
+            (true, entropy(chance))
+        } else {
+            (false, entropy(1.0 - chance))
+        }
+    }
+
+    /// # End Bet
+    /// 
+    /// Closes out the bet, resetting the streak.
+    pub fn end_bet(&mut self) {
+        self.multiplier = 1.0;
+        self.streak = 0;
+        self.streak_chance = 1.0;
+        self.base.bet_start = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::deck::{Rank, Suit};
+
+    #[test]
+    fn the_face_up_card_is_left_out_of_a_fresh_shoe() {
+        let mut rng = rand::rng();
+        let mut hilo = HiLo::new(&mut rng);
+        let last = Card { rank: Rank::King, suit: Suit::Spades };
+        hilo.current = Some(Card { rank: Rank::Two, suit: Suit::Hearts });
+        hilo.deck.cards = vec![last];
+        let (won, _) = hilo.make_call(&mut rng, Call::Higher, false);
+        assert!(won);
+        assert_eq!(hilo.current, Some(last));
+        assert_eq!(hilo.deck.remaining(), hilo.deck.size() - 1);
+        assert!(!hilo.deck.cards.contains(&last));
+    }
+}
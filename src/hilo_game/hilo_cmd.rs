@@ -0,0 +1,210 @@
+use std::{io::{stdin, stdout}, time::Instant};
+
+use crossterm::{style::Print, terminal, ExecutableCommand};
+use rand::Rng;
+
+use crate::{autosave::AutosaveEvent, common_state::CommonState, game::BetResult, hilo_game::hilo::{Call, HiLo, HiLoState, HILO_PEEK_BITS, HILO_RETURN}, machine::{machine::{Machine, Manipulation}, research::Research}, money::{looks_like_money, Money}};
+
+pub fn select_screen<R: Rng>(common_state: &mut CommonState, hilo: &mut HiLo,
+rng: &mut R) -> Option<HiLoState> {
+    match hilo.state {
+        HiLoState::Hold => {
+            holding_screen(common_state, hilo)
+        },
+        HiLoState::InBet => {
+            in_bet(common_state, hilo, rng)
+        },
+    }
+}
+
+/// # Print Odds
+/// 
+/// Prints what's left in the shoe, and the odds, payout, and entropy price of each 
+/// call against it.
+fn print_odds(hilo: &HiLo, machine: &Machine) {
+    let counts = hilo.deck.rank_counts().iter()
+        .map(|c| format!("{:>3}", c)).collect::<Vec<String>>().join("");
+    stdout().execute(Print(format!("Left:  A  2  3  4  5  6  7  8  9 10  J  Q  K\n     {}\n", counts))).unwrap();
+    for (name, call) in [("Higher", Call::Higher), ("Lower", Call::Lower)] {
+        let price = hilo.force_cost(call).map_or(String::from("--"),
+            |cost| format!("{:.2} b", machine.entropy_cost(Manipulation::Elimination, cost)));
+        stdout().execute(Print(format!("{}: {:.2}% -> x{:.2}\tForce: {}\n", name,
+            hilo.call_chance(call) * 100.0, hilo.call_payout(call), price))).unwrap();
+    }
+}
+
+/// # Holding Screen
+/// 
+/// Shows the last card and lets the player change their bet before dealing.
+pub fn holding_screen(common_state: &mut CommonState, hilo: &mut HiLo) -> Option<HiLoState> {
+    stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
+    let mut msg = String::new();
+    loop {
+        let kicked_out = hilo.base.kickout_update(Instant::now());
+        stdout().execute(Print(format!("\t\t!!!Hi-Lo!!!\nCommands: D -> Deal | Q -> Exit | Enter £sd to change Bet\nBet Min: {} | Bet Max: {}\n",
+            hilo.base.bet_min, hilo.base.bet_max))).unwrap();
+        stdout().execute(Print(format!("Money: {}\tEntropy: {} b\tSuspicion: {}\n", common_state.money, common_state.entropy, hilo.base.suspicion))).unwrap();
+        stdout().execute(Print(format!("Current Bet: {}\tCards Left: {}/{}\n", hilo.base.current_bet,
+            hilo.deck.remaining(), hilo.deck.size()))).unwrap();
+        if let Some(card) = hilo.current {
+            stdout().execute(Print(format!("\t\tLast Card: {}\n", card))).unwrap();
+        }
+        if kicked_out {
+            stdout().execute(Print(format!("!!! Kicked Out! Come back in {:.0} s !!!\n",
+                hilo.base.kickout_time_remaining()))).unwrap();
+        }
+        stdout().execute(Print(format!("{}\n", msg))).unwrap();
+        let mut buff = String::new();
+        stdin().read_line(&mut buff).unwrap();
+        buff = buff.trim().to_lowercase();
+        let amount = buff.parse::<Money>();
+        if let Ok(bet) = amount {
+            if let Err(err) = hilo.base.set_bet(bet, common_state.money) {
+                msg = err.to_string();
+            }
+        } else if buff == "d" {
+            let stake = hilo.base.current_bet;
+            match common_state.try_bet(&mut hilo.base, stake, Instant::now()) {
+                Ok(()) => {
+                    hilo.state = HiLoState::InBet;
+                    return Some(HiLoState::InBet);
+                },
+                Err(err) => msg = err.to_string(),
+            }
+        } else if buff == "q" {
+            return None;
+        } else {
+            msg = match amount {
+                Err(err) if looks_like_money(&buff) => err,
+                _ => String::from("Invalid Command."),
+            };
+        }
+        stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
+    }
+}
+
+/// # In Bet
+/// 
+/// Deals the first card, then takes calls until the player cashes out or misses.
+pub fn in_bet<R: Rng>(common_state: &mut CommonState, hilo: &mut HiLo,
+rng: &mut R) -> Option<HiLoState> {
+    stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
+    // The stake was taken when the bet was placed.
+    let stake = hilo.base.current_bet;
+    hilo.bet(rng);
+    let mut msg = String::new();
+    let mut won = false;
+    // Whether the next card's been peeked at, a call made knowing it isn't harvested.
+    let mut peeked = false;
+    loop {
+        let peek = if common_state.machine.researched(Research::Peek) {
+            format!(" | P -> Peek at the next Card ({:.2} b)",
+                common_state.machine.entropy_cost(Manipulation::Reroll, HILO_PEEK_BITS))
+        } else {
+            String::new()
+        };
+        stdout().execute(Print(format!("\t\t!!!Hi-Lo!!!\nCommands: H -> Higher | L -> Lower | FH/FL -> Force Higher/Lower{} | C -> Cash Out\n", peek))).unwrap();
+        stdout().execute(Print(format!("Money: {}\tEntropy: {} b\tSuspicion: {}\n", common_state.money, common_state.entropy, hilo.base.suspicion))).unwrap();
+        stdout().execute(Print(format!("Bet: {}\tStreak: {} ({:.2} b)\tMultiplier: x{:.2}\tCash Out: {}\n",
+            stake, hilo.streak, hilo.streak_bits(), hilo.multiplier,
+            stake.mul_ratio(hilo.base.suspicion_payout(hilo.multiplier))))).unwrap();
+        stdout().execute(Print(format!("Cards Left: {}/{}\n", hilo.deck.remaining(), hilo.deck.size()))).unwrap();
+        stdout().execute(Print(format!("\t\t{}\n", hilo.current.unwrap()))).unwrap();
+        print_odds(hilo, &common_state.machine);
+        stdout().execute(Print(format!("{}\n", msg))).unwrap();
+        let mut buff = String::new();
+        stdin().read_line(&mut buff).unwrap();
+        buff = buff.trim().to_lowercase();
+        let mut malfunction = String::new();
+        let (call, mut forced) = match buff.as_str() {
+            "h" => (Call::Higher, false),
+            "l" => (Call::Lower, false),
+            "fh" => (Call::Higher, true),
+            "fl" => (Call::Lower, true),
+            "c" => {
+                won = true;
+                break;
+            },
+            "p" if common_state.machine.researched(Research::Peek) => {
+                msg = match common_state.spend_manipulation(Manipulation::Reroll, HILO_PEEK_BITS,
+                    "Hi-Lo peek", rng) {
+                    Ok(made) => {
+                        hilo.base.add_suspicion(made.suspicion_spike());
+                        match hilo.deck.peek().filter(|_| made.took_effect()) {
+                            Some(next) => {
+                                peeked = true;
+                                made.report(&format!("The next card is {}. Peeked", next))
+                            },
+                            None => made.report("Saw nothing"),
+                        }
+                    },
+                    Err(err) => err.to_string(),
+                };
+                stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
+                continue;
+            },
+            _ => {
+                msg = String::from("Invalid Command.");
+                stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
+                continue;
+            },
+        };
+        if forced {
+            match hilo.force_cost(call) {
+                None => {
+                    msg = String::from("No card left can win that call.");
+                    stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
+                    continue;
+                },
+                Some(cost) => match common_state.spend_manipulation(Manipulation::Elimination, cost,
+                    "Hi-Lo force", rng) {
+                    Ok(made) => {
+                        hilo.base.add_suspicion(made.suspicion_spike());
+                        // A misfire leaves the card to chance.
+                        forced = made.took_effect();
+                        malfunction = made.malfunction.map_or(String::new(), |m| format!("{} ", m));
+                    },
+                    Err(err) => {
+                        msg = err.to_string();
+                        stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
+                        continue;
+                    },
+                },
+            }
+        }
+        let (hit, bits) = hilo.make_call(rng, call, forced);
+        if !forced && !peeked {
+            // Only natural results are harvested.
+            common_state.harvest(bits);
+        }
+        peeked = false;
+        if !hit {
+            break;
+        }
+        // The longer the streak, the more they watch you.
+        hilo.base.add_suspicion(bits);
+        msg = format!("{}Correct! It was {}.", malfunction, hilo.current.unwrap());
+        if hilo.base.kickout_roll(rng, Instant::now()) {
+            // Caught, the stake is forfeit.
+            common_state.forfeit_bet(&mut hilo.base);
+            common_state.autosave.request(AutosaveEvent::Kickout);
+            hilo.end_bet();
+            hilo.state = HiLoState::Hold;
+            return Some(HiLoState::Hold);
+        }
+        stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
+    }
+    // finalize financial gains if cashed out.
+    let calls = hilo.streak as i32 + if won { 0 } else { 1 };
+    let result = if calls == 0 {
+        // Cashed out before making a call, nothing was risked so it's a push.
+        BetResult { won: false, payout: 1.0, win_chance: 0.0, expected_return: 1.0 }
+    } else {
+        BetResult { won, payout: if won { hilo.multiplier } else { 0.0 },
+            win_chance: hilo.streak_chance, expected_return: HILO_RETURN.powi(calls) }
+    };
+    common_state.settle_bet(&mut hilo.base, result);
+    hilo.end_bet();
+    hilo.state = HiLoState::Hold;
+    Some(HiLoState::Hold)
+}
@@ -0,0 +1,2 @@
+pub mod hilo;
+pub mod hilo_cmd;
@@ -0,0 +1,280 @@
+use std::time::{Duration, Instant};
+
+use rand::{seq::index, Rng};
+
+use crate::{game::{entropy, hypergeometric, GameCommonData, BET_DURATION}, money::Money};
+
+/// The balls in the bingo cage, 1 through 75.
+pub const BINGO_BALLS: u8 = 75;
+/// How many balls are called each game.
+pub const BINGO_DRAWS: u8 = 40;
+/// The most cards a player can play at once.
+pub const BINGO_MAX_CARDS: usize = 6;
+/// The share of each card's bet the lines return on average.
+pub const BINGO_RETURN: f64 = 0.9;
+/// The number of lines on a card. 5 rows, 5 columns, and 2 diagonals.
+const BINGO_LINES: usize = 12;
+
+/// # Bingo Card
+/// 
+/// A standard 5x5 card. Column B holds 1-15, I 16-30, N 31-45, G 46-60, and O 61-75.
+/// The center square is free, and stored as 0.
+#[derive(Debug, Clone)]
+pub struct BingoCard {
+    /// The numbers on the card, by [row][column].
+    pub numbers: [[u8; 5]; 5],
+}
+
+impl BingoCard {
+    /// # Generate
+    /// 
+    /// Generates a random card.
+    pub fn generate<R: Rng>(rng: &mut R) -> Self {
+        let mut numbers = [[0; 5]; 5];
+        for col in 0..5 {
+            let picks = index::sample(rng, 15, 5);
+            for (row, pick) in numbers.iter_mut().zip(picks.iter()) {
+                row[col] = (col * 15 + pick + 1) as u8;
+            }
+        }
+        numbers[2][2] = 0;
+        Self { numbers }
+    }
+
+    /// # Lines
+    /// 
+    /// All 12 lines of the card, rows, then columns, then the two diagonals.
+    /// 
+    /// The free center is left out, so lines through it only have 4 numbers.
+    pub fn lines(&self) -> Vec<Vec<u8>> {
+        let mut lines = Vec::with_capacity(BINGO_LINES);
+        for row in 0..5 {
+            lines.push((0..5).map(|col| self.numbers[row][col]).collect());
+        }
+        for col in 0..5 {
+            lines.push((0..5).map(|row| self.numbers[row][col]).collect());
+        }
+        lines.push((0..5).map(|i| self.numbers[i][i]).collect());
+        lines.push((0..5).map(|i| self.numbers[i][4 - i]).collect());
+        lines.into_iter()
+            .map(|line: Vec<u8>| line.into_iter().filter(|n| *n != 0).collect())
+            .collect()
+    }
+
+    /// # Completed Lines
+    /// 
+    /// The lines which have every number called.
+    pub fn completed_lines(&self, drawn: &[u8]) -> Vec<Vec<u8>> {
+        self.lines().into_iter()
+            .filter(|line| line.iter().all(|n| drawn.contains(n)))
+            .collect()
+    }
+
+    /// # Payout Rate
+    /// 
+    /// The payout rate of the card for the given balls called, the sum of the
+    /// payouts of each completed line.
+    pub fn payout_rate(&self, drawn: &[u8]) -> f64 {
+        self.completed_lines(drawn).iter()
+            .map(|line| BingoHall::line_payout(line.len() as u8))
+            .sum()
+    }
+}
+
+/// # Bingo Hall
+/// 
+/// A hall of bingo, playing multiple cards at once. Each card is bet on separately,
+/// and every line completed on a card pays out.
+#[derive(Debug)]
+pub struct BingoHall {
+    /// The cards being played.
+    pub cards: Vec<BingoCard>,
+    /// The balls called for the current or last game, in call order.
+    pub drawn: Vec<u8>,
+    /// Whether the current game's balls have been altered with entropy.
+    pub forced: bool,
+    /// The current state of the game.
+    pub state: BingoState,
+    /// Shared common Data. The bet is per card.
+    pub base: GameCommonData,
+}
+
+#[derive(Debug)]
+pub enum BingoState {
+    /// No active bet, cards can be changed and the last game is shown.
+    Hold,
+    /// The balls have been called and the bet is open. Swaps can be made until
+    /// the player settles or the timer runs out.
+    InBet,
+}
+
+impl Default for BingoHall {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BingoHall {
+    /// Create a new Bingo Hall with no cards.
+    pub fn new() -> Self {
+        Self {
+            cards: vec![],
+            drawn: vec![],
+            forced: false,
+            state: BingoState::Hold,
+            base: GameCommonData::new("Bingo Hall".to_string(), Money::from_pence(1), Money::from_pence(50),
+                0.0, Duration::from_secs(60)),
+        }
+    }
+
+    /// # Deal Cards
+    /// 
+    /// Replaces the cards being played with the given number of new cards.
+    pub fn deal_cards<R: Rng>(&mut self, rng: &mut R, count: usize) -> Result<(), String> {
+        if count == 0 || count > BINGO_MAX_CARDS {
+            return Err(format!("Must play between 1 and {} cards.", BINGO_MAX_CARDS));
+        }
+        self.cards = (0..count).map(|_| BingoCard::generate(rng)).collect();
+        Ok(())
+    }
+
+    /// # Total Stake
+    /// 
+    /// The total bet across all cards.
+    pub fn total_stake(&self) -> Money {
+        self.base.current_bet * self.cards.len() as i64
+    }
+
+    /// # Line Chance
+    /// 
+    /// The exact chance of a line of the given length being completed, which is
+    /// the chance that every number in it is among the balls called.
+    pub fn line_chance(length: u8) -> f64 {
+        hypergeometric(BINGO_BALLS as u32, length as u32, BINGO_DRAWS as u32, length as u32)
+    }
+
+    /// # Line Payout
+    /// 
+    /// The payout rate of a completed line of the given length.
+    /// 
+    /// Each of the 12 lines gets an equal share of the return, so a line pays
+    /// BINGO_RETURN / (12 * P(line)). As the payout of a card is the sum of its lines,
+    /// the expected return of every card is exactly BINGO_RETURN.
+    pub fn line_payout(length: u8) -> f64 {
+        BINGO_RETURN / (BINGO_LINES as f64 * BingoHall::line_chance(length))
+    }
+
+    /// # Payout Rate
+    /// 
+    /// The payout rate across all cards, relative to the bet on a single card.
+    pub fn payout_rate(&self) -> f64 {
+        self.cards.iter().map(|card| card.payout_rate(&self.drawn)).sum()
+    }
+
+    /// # Win Chance
+    /// 
+    /// The chance a single card completes at least one line. Approximated as
+    /// the chance of any line, treating the lines as independent.
+    pub fn win_chance() -> f64 {
+        1.0 - (1.0 - BingoHall::line_chance(4)).powi(2)
+            * (1.0 - BingoHall::line_chance(5)).powi(10)
+    }
+
+    /// # Entropy Gained
+    /// 
+    /// The entropy gained from the game, equal to the surprisal of every completed
+    /// line across the cards.
+    pub fn entropy_gained(&self) -> f64 {
+        self.cards.iter()
+            .flat_map(|card| card.completed_lines(&self.drawn))
+            .map(|line| entropy(BingoHall::line_chance(line.len() as u8)))
+            .sum()
+    }
+
+    /// # Bet Time Remaining
+    /// 
+    /// The time remaining on a bingo bet. If no ongoing bet, or it's run out, 0.0.
+    pub fn bet_time_remaining(&self) -> f64 {
+        self.base.bet_time_remaining(BET_DURATION).unwrap_or(0.0)
+    }
+
+    /// # Bet
+    /// 
+    /// Calls the balls and starts the bet timer.
+    pub fn bet<R: Rng>(&mut self, rng: &mut R) {
+        self.drawn = index::sample(rng, BINGO_BALLS as usize, BINGO_DRAWS as usize)
+            .iter().map(|i| i as u8 + 1).collect();
+        self.forced = false;
+        self.base.bet_start = Some(Instant::now());
+    }
+
+    /// # Swap Cost
+    /// 
+    /// The entropy cost to swap a called ball out for one that wasn't called.
+    /// 
+    /// Costs the surprisal of each line the swap completes, with a minimum of the
+    /// surprisal of the new ball being called at all.
+    pub fn swap_cost(&self, out: u8, into: u8) -> Result<f64, String> {
+        if !self.drawn.contains(&out) {
+            return Err(format!("{} wasn't called.", out));
+        }
+        if into == 0 || into > BINGO_BALLS {
+            return Err(format!("Balls must be between 1 and {}.", BINGO_BALLS));
+        }
+        if self.drawn.contains(&into) {
+            return Err(format!("{} was already called.", into));
+        }
+        let swapped: Vec<u8> = self.drawn.iter()
+            .map(|&d| if d == out { into } else { d })
+            .collect();
+        let completed: f64 = self.cards.iter()
+            .flat_map(|card| card.completed_lines(&swapped).into_iter()
+                .filter(|line| !line.iter().all(|n| self.drawn.contains(n))))
+            .map(|line| entropy(BingoHall::line_chance(line.len() as u8)))
+            .sum();
+        let minimum = entropy(BINGO_DRAWS as f64 / BINGO_BALLS as f64);
+        Ok(completed.max(minimum))
+    }
+
+    /// # Swap
+    /// 
+    /// Swaps a called ball for one that wasn't called. Returns the cost of the swap.
+    /// 
+    /// Does not spend the entropy, only reports the cost.
+    pub fn swap(&mut self, out: u8, into: u8) -> Result<f64, String> {
+        let cost = self.swap_cost(out, into)?;
+        for ball in self.drawn.iter_mut() {
+            if *ball == out {
+                *ball = into;
+            }
+        }
+        self.forced = true;
+        Ok(cost)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_card_returns_the_bingo_return() {
+        let card = BingoCard::generate(&mut rand::rng());
+        let ev: f64 = card.lines().iter()
+            .map(|line| {
+                let length = line.len() as u8;
+                BingoHall::line_chance(length) * BingoHall::line_payout(length)
+            })
+            .sum();
+        assert!((ev - BINGO_RETURN).abs() < 1e-9, "a card returned {}", ev);
+    }
+
+    #[test]
+    fn lines_through_the_free_center_are_shorter() {
+        let card = BingoCard::generate(&mut rand::rng());
+        let lengths: Vec<usize> = card.lines().iter().map(|line| line.len()).collect();
+        assert_eq!(lengths.len(), BINGO_LINES);
+        assert_eq!(lengths.iter().filter(|&&length| length == 4).count(), 4);
+        assert_eq!(lengths.iter().filter(|&&length| length == 5).count(), 8);
+    }
+}
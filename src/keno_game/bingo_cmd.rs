@@ -0,0 +1,193 @@
+use std::{io::{stdin, stdout}, time::Instant};
+
+use crossterm::{style::Print, terminal, ExecutableCommand};
+use rand::Rng;
+
+use crate::{autosave::AutosaveEvent, common_state::CommonState, game::BetResult, machine::machine::Manipulation, keno_game::bingo::{BingoCard, BingoHall, BingoState, BINGO_MAX_CARDS, BINGO_RETURN}, money::{looks_like_money, Money}};
+
+pub fn select_screen<R: Rng>(common_state: &mut CommonState, hall: &mut BingoHall,
+rng: &mut R) -> Option<BingoState> {
+    match hall.state {
+        BingoState::Hold => {
+            holding_screen(common_state, hall, rng)
+        },
+        BingoState::InBet => {
+            in_bet(common_state, hall, rng)
+        },
+    }
+}
+
+/// # Print Cards
+/// 
+/// Prints the cards side by side, with called numbers in brackets and the free
+/// center as FF.
+fn print_cards(cards: &[BingoCard], drawn: &[u8]) {
+    let mut out = String::new();
+    for _ in cards {
+        out += "  B   I   N   G   O      ";
+    }
+    out += "\n";
+    for row in 0..5 {
+        for card in cards {
+            for col in 0..5 {
+                let number = card.numbers[row][col];
+                if number == 0 {
+                    out += " FF ";
+                } else if drawn.contains(&number) {
+                    out += format!("[{:>2}]", number).as_str();
+                } else {
+                    out += format!(" {:>2} ", number).as_str();
+                }
+            }
+            out += "     ";
+        }
+        out += "\n";
+    }
+    stdout().execute(Print(out)).unwrap();
+}
+
+/// # Holding Screen
+/// 
+/// Shows the cards and last game, and lets the player change their cards and bet.
+pub fn holding_screen<R: Rng>(common_state: &mut CommonState, hall: &mut BingoHall,
+rng: &mut R) -> Option<BingoState> {
+    stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
+    let mut msg = String::new();
+    loop {
+        let kicked_out = hall.base.kickout_update(Instant::now());
+        stdout().execute(Print(format!("\t\t!!!Bingo Hall!!!\nCommands: N # -> Deal # New Cards | D -> Call Balls | Q -> Exit | Enter £sd to change Bet per Card\nBet Min: {} | Bet Max: {}\n",
+            hall.base.bet_min, hall.base.bet_max))).unwrap();
+        stdout().execute(Print(format!("Money: {}\tEntropy: {} b\tSuspicion: {}\n", common_state.money, common_state.entropy, hall.base.suspicion))).unwrap();
+        stdout().execute(Print(format!("Bet per Card: {}\tTotal Bet: {}\n", hall.base.current_bet, hall.total_stake()))).unwrap();
+        stdout().execute(Print(format!("Line Pays: 4 numbers -> x{:.2} | 5 numbers -> x{:.2}\n",
+            BingoHall::line_payout(4), BingoHall::line_payout(5)))).unwrap();
+        if kicked_out {
+            stdout().execute(Print(format!("!!! Kicked Out! Come back in {:.0} s !!!\n",
+                hall.base.kickout_time_remaining()))).unwrap();
+        }
+        print_cards(&hall.cards, &hall.drawn);
+        stdout().execute(Print(format!("{}\n", msg))).unwrap();
+        let mut buff = String::new();
+        stdin().read_line(&mut buff).unwrap();
+        buff = buff.trim().to_lowercase();
+        let mut args = buff.split_whitespace();
+        let command = args.next().unwrap_or("");
+        let amount = buff.parse::<Money>();
+        if let Ok(bet) = amount {
+            if let Err(err) = hall.base.set_bet(bet, common_state.money) {
+                msg = err.to_string();
+            }
+        } else if command == "n" {
+            let count = args.next().and_then(|a| a.parse::<usize>().ok()).unwrap_or(0);
+            msg = hall.deal_cards(rng, count).err().unwrap_or_default();
+            hall.drawn.clear();
+        } else if command == "d" {
+            if hall.cards.is_empty() {
+                msg = format!("Deal between 1 and {} cards first.", BINGO_MAX_CARDS);
+            } else {
+                let stake = hall.total_stake();
+                match common_state.try_bet(&mut hall.base, stake, Instant::now()) {
+                    Ok(()) => {
+                        hall.state = BingoState::InBet;
+                        return Some(BingoState::InBet);
+                    },
+                    Err(err) => msg = err.to_string(),
+                }
+            }
+        } else if command == "q" {
+            return None;
+        } else {
+            msg = match amount {
+                Err(err) if looks_like_money(&buff) => err,
+                _ => String::from("Invalid Command."),
+            };
+        }
+        stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
+    }
+}
+
+/// # In Bet
+/// 
+/// Calls the balls, then lets the player swap called balls with entropy until
+/// they settle or the bet timer runs out.
+pub fn in_bet<R: Rng>(common_state: &mut CommonState, hall: &mut BingoHall,
+rng: &mut R) -> Option<BingoState> {
+    stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
+    // The stake was taken when the bet was placed.
+    let stake = hall.total_stake();
+    // call the balls
+    hall.bet(rng);
+    // Whether anything's been swapped, leaving nothing natural to harvest.
+    let mut swapped = false;
+    let mut msg = String::new();
+    loop {
+        stdout().execute(Print("\t\t!!!Bingo Hall!!!\nCommands: S <called> <new> -> Swap a called ball | C -> Collect and end bet\n")).unwrap();
+        stdout().execute(Print(format!("Money: {}\tEntropy: {} b\tSuspicion: {}\n", common_state.money, common_state.entropy, hall.base.suspicion))).unwrap();
+        stdout().execute(Print(format!("Total Bet: {}\tEntropy on Result: {:.2}\n", stake,
+            if swapped { 0.0 } else { common_state.harvest_value(hall.entropy_gained()) }))).unwrap();
+        stdout().execute(Print(format!("Time Remaining: {:.0} s\n", hall.bet_time_remaining()))).unwrap();
+        stdout().execute(Print(format!("Called: {:?}\n", hall.drawn))).unwrap();
+        print_cards(&hall.cards, &hall.drawn);
+        stdout().execute(Print(format!("Payout: x{:.2} per card bet\n",
+            hall.base.suspicion_payout(hall.payout_rate())))).unwrap();
+        stdout().execute(Print(format!("{}\n", msg))).unwrap();
+        let mut buff = String::new();
+        stdin().read_line(&mut buff).unwrap();
+        buff = buff.trim().to_lowercase();
+        // Bets close once time is up, no matter what was asked for.
+        if hall.bet_time_remaining() == 0.0 {
+            break;
+        }
+        let mut args = buff.split_whitespace();
+        let command = args.next().unwrap_or("");
+        if command == "s" {
+            let numbers: Vec<u8> = args.filter_map(|a| a.parse::<u8>().ok()).collect();
+            if numbers.len() != 2 {
+                msg = String::from("Swap needs the called ball and the ball to replace it.");
+            } else {
+                match hall.swap_cost(numbers[0], numbers[1]) {
+                    Ok(cost) => match common_state.spend_manipulation(Manipulation::Selection, cost,
+                        "Bingo Hall swap", rng) {
+                        Ok(made) => {
+                            if made.took_effect() {
+                                hall.swap(numbers[0], numbers[1]).unwrap();
+                                swapped = true;
+                                hall.base.add_suspicion(cost);
+                            }
+                            hall.base.add_suspicion(made.suspicion_spike());
+                            msg = made.report(&format!("Swapped {} for {}", numbers[0], numbers[1]));
+                            if hall.base.kickout_roll(rng, Instant::now()) {
+                                // Caught, the stakes are forfeit.
+                                common_state.forfeit_bet(&mut hall.base);
+                                common_state.autosave.request(AutosaveEvent::Kickout);
+                                hall.state = BingoState::Hold;
+                                return Some(BingoState::Hold);
+                            }
+                        },
+                        Err(err) => msg = err.to_string(),
+                    },
+                    Err(err) => msg = err,
+                }
+            }
+        } else if command == "c" {
+            break;
+        } else {
+            msg = String::from("Invalid Command.");
+        }
+        stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
+    }
+    // Only natural calls are harvested.
+    if !swapped {
+        common_state.harvest(hall.entropy_gained());
+    }
+    // finalize financial gains, each card pays on its own lines.
+    let bet = hall.base.current_bet;
+    for card in hall.cards.iter() {
+        let rate = card.payout_rate(&hall.drawn);
+        let result = BetResult { won: rate > 0.0,
+            payout: rate, win_chance: BingoHall::win_chance(), expected_return: BINGO_RETURN };
+        common_state.settle_bet_part(&mut hall.base, bet, result);
+    }
+    hall.state = BingoState::Hold;
+    Some(BingoState::Hold)
+}
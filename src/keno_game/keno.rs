@@ -0,0 +1,269 @@
+use std::time::{Duration, Instant};
+
+use rand::{seq::index, Rng};
+
+use crate::{game::{entropy, hypergeometric, GameCommonData, BET_DURATION}, money::Money};
+
+/// The numbers in the keno pool, 1 through 80.
+pub const KENO_NUMBERS: u8 = 80;
+/// How many numbers are drawn each game.
+pub const KENO_DRAWS: u8 = 20;
+/// The most spots a player can pick on one ticket.
+pub const KENO_MAX_SPOTS: u8 = 10;
+/// The share of each bet the paytables return on average.
+pub const KENO_RETURN: f64 = 0.9;
+
+/// # Keno
+/// 
+/// Pick between 1 and 10 numbers out of 80, then 20 are drawn. The more of your
+/// picks that are drawn, the more it pays out.
+#[derive(Debug)]
+pub struct Keno {
+    /// The numbers the player has picked. Sorted.
+    pub picks: Vec<u8>,
+    /// The numbers drawn for the current or last game.
+    pub drawn: Vec<u8>,
+    /// The payout rate for each number of hits on the current ticket, indexed by hits.
+    pub paytable: Vec<f64>,
+    /// Whether the current draw has been altered with entropy.
+    pub forced: bool,
+    /// The current state of the game.
+    pub state: KenoState,
+    /// Shared common Data
+    pub base: GameCommonData,
+}
+
+#[derive(Debug)]
+pub enum KenoState {
+    /// No active bet, picks can be changed and the last draw is shown.
+    Hold,
+    /// The numbers have been drawn and the bet is open. Swaps can be made until
+    /// the player settles or the timer runs out.
+    InBet,
+}
+
+impl Default for Keno {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Keno {
+    /// Create a new Keno game, with no picks made yet.
+    pub fn new() -> Self {
+        Self {
+            picks: vec![],
+            drawn: vec![],
+            paytable: vec![],
+            forced: false,
+            state: KenoState::Hold,
+            base: GameCommonData::new("Keno".to_string(), Money::from_pence(1), Money::from_pence(100),
+                0.0, Duration::from_secs(60)),
+        }
+    }
+
+    /// # Set Picks
+    /// 
+    /// Sets the player's picks, rebuilding the paytable to match.
+    /// 
+    /// Picks must be unique, in 1 to 80, and there must be 1 to 10 of them.
+    pub fn set_picks(&mut self, mut picks: Vec<u8>) -> Result<(), String> {
+        picks.sort();
+        picks.dedup();
+        if picks.is_empty() || picks.len() > KENO_MAX_SPOTS as usize {
+            return Err(format!("Must pick between 1 and {} unique numbers.", KENO_MAX_SPOTS));
+        }
+        if picks.iter().any(|&p| p == 0 || p > KENO_NUMBERS) {
+            return Err(format!("Picks must be between 1 and {}.", KENO_NUMBERS));
+        }
+        self.paytable = Keno::paytable(picks.len() as u8);
+        self.picks = picks;
+        Ok(())
+    }
+
+    /// # Quick Pick
+    /// 
+    /// Picks the given number of spots at random.
+    pub fn quick_pick<R: Rng>(&mut self, rng: &mut R, spots: u8) -> Result<(), String> {
+        let picks = index::sample(rng, KENO_NUMBERS as usize,
+            (spots.min(KENO_MAX_SPOTS)) as usize)
+            .iter().map(|i| i as u8 + 1).collect();
+        self.set_picks(picks)
+    }
+
+    /// # Hit Chance
+    /// 
+    /// The exact chance of hitting exactly `hits` of `spots` picks in a draw.
+    pub fn hit_chance(spots: u8, hits: u8) -> f64 {
+        hypergeometric(KENO_NUMBERS as u32, spots as u32, KENO_DRAWS as u32, hits as u32)
+    }
+
+    /// # Paytable
+    /// 
+    /// Builds the paytable for a ticket with the given number of spots.
+    /// 
+    /// Any hit count of at least half the spots pays. Each paying tier is given an
+    /// equal share of the return, so the payout for a tier is
+    /// KENO_RETURN / (paying tiers * P(hits)). This makes the expected return of
+    /// every ticket exactly KENO_RETURN.
+    pub fn paytable(spots: u8) -> Vec<f64> {
+        let min_hits = spots.div_ceil(2).max(1);
+        let tiers = (spots - min_hits + 1) as f64;
+        (0..=spots).map(|hits| {
+            if hits < min_hits {
+                0.0
+            } else {
+                KENO_RETURN / (tiers * Keno::hit_chance(spots, hits))
+            }
+        }).collect()
+    }
+
+    /// # Hits
+    /// 
+    /// How many of the picks are in the current draw.
+    pub fn hits(&self) -> u8 {
+        self.picks.iter().filter(|p| self.drawn.contains(p)).count() as u8
+    }
+
+    /// # Payout Rate
+    /// 
+    /// The payout rate for the current draw, before suspicion.
+    pub fn payout_rate(&self) -> f64 {
+        self.paytable.get(self.hits() as usize).copied().unwrap_or(0.0)
+    }
+
+    /// # Win Chance
+    /// 
+    /// The chance that the current ticket pays anything.
+    pub fn win_chance(&self) -> f64 {
+        let spots = self.picks.len() as u8;
+        self.paytable.iter().enumerate()
+            .filter(|(_, pay)| **pay > 0.0)
+            .map(|(hits, _)| Keno::hit_chance(spots, hits as u8))
+            .sum()
+    }
+
+    /// # Entropy Gained
+    /// 
+    /// The entropy gained from the draw, equal to the surprisal of the number of
+    /// hits the draw landed on.
+    pub fn entropy_gained(&self) -> f64 {
+        entropy(Keno::hit_chance(self.picks.len() as u8, self.hits()))
+    }
+
+    /// # Bet Time Remaining
+    /// 
+    /// The time remaining on a keno bet. If no ongoing bet, or it's run out, 0.0.
+    pub fn bet_time_remaining(&self) -> f64 {
+        self.base.bet_time_remaining(BET_DURATION).unwrap_or(0.0)
+    }
+
+    /// # Bet
+    /// 
+    /// Draws the numbers and starts the bet timer.
+    /// 
+    /// Returns the number of hits.
+    pub fn bet<R: Rng>(&mut self, rng: &mut R) -> u8 {
+        let mut drawn: Vec<u8> = index::sample(rng, KENO_NUMBERS as usize, KENO_DRAWS as usize)
+            .iter().map(|i| i as u8 + 1).collect();
+        drawn.sort();
+        self.drawn = drawn;
+        self.forced = false;
+        self.base.bet_start = Some(Instant::now());
+        self.hits()
+    }
+
+    /// # Swap Cost
+    /// 
+    /// The entropy cost to swap a drawn number out for one that wasn't drawn.
+    /// 
+    /// The cost is the surprisal the swap adds to the draw, log2(P(hits before) /
+    /// P(hits after)), so a run of swaps costs the same as going straight to the hits
+    /// they end on. Swaps to likelier hit counts are free, and swaps that don't change
+    /// the hits are refused.
+    pub fn swap_cost(&self, out: u8, into: u8) -> Result<f64, String> {
+        if !self.drawn.contains(&out) {
+            return Err(format!("{} wasn't drawn.", out));
+        }
+        if into == 0 || into > KENO_NUMBERS {
+            return Err(format!("Numbers must be between 1 and {}.", KENO_NUMBERS));
+        }
+        if self.drawn.contains(&into) {
+            return Err(format!("{} was already drawn.", into));
+        }
+        let before = self.hits();
+        let mut hits = before;
+        if self.picks.contains(&out) {
+            hits -= 1;
+        }
+        if self.picks.contains(&into) {
+            hits += 1;
+        }
+        if hits == before {
+            return Err(String::from("That swap wouldn't change the hits."));
+        }
+        let spots = self.picks.len() as u8;
+        Ok((entropy(Keno::hit_chance(spots, hits)) - entropy(Keno::hit_chance(spots, before)))
+            .max(0.0))
+    }
+
+    /// # Swap
+    /// 
+    /// Swaps a drawn number for one that wasn't drawn. Returns the cost of the swap.
+    /// 
+    /// Does not spend the entropy, only reports the cost.
+    pub fn swap(&mut self, out: u8, into: u8) -> Result<f64, String> {
+        let cost = self.swap_cost(out, into)?;
+        self.drawn.retain(|&d| d != out);
+        self.drawn.push(into);
+        self.drawn.sort();
+        self.forced = true;
+        Ok(cost)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A ticket of the given picks with the given numbers drawn.
+    fn ticket(picks: &[u8], drawn: &[u8]) -> Keno {
+        let mut keno = Keno::new();
+        keno.set_picks(picks.to_vec()).unwrap();
+        keno.drawn = drawn.to_vec();
+        keno
+    }
+
+    #[test]
+    fn every_paytable_returns_the_keno_return() {
+        for spots in 1..=KENO_MAX_SPOTS {
+            let ev: f64 = Keno::paytable(spots).iter().enumerate()
+                .map(|(hits, pay)| Keno::hit_chance(spots, hits as u8) * pay)
+                .sum();
+            assert!((ev - KENO_RETURN).abs() < 1e-9, "{} spots returned {}", spots, ev);
+        }
+    }
+
+    #[test]
+    fn swaps_that_change_nothing_are_refused() {
+        let keno = ticket(&[1, 2, 3, 4], &[1, 10, 11]);
+        assert!(keno.swap_cost(10, 12).is_err());
+        assert!(keno.swap_cost(1, 2).is_err());
+        assert!(keno.swap_cost(10, 2).is_ok());
+    }
+
+    #[test]
+    fn swaps_cost_the_same_as_going_straight_there() {
+        let mut keno = ticket(&[1, 2, 3, 4], &[1, 10, 11, 12]);
+        let spots = keno.picks.len() as u8;
+        let straight = entropy(Keno::hit_chance(spots, 4)) - entropy(Keno::hit_chance(spots, 1));
+        let mut total = 0.0;
+        for (out, into) in [(10, 2), (11, 3), (12, 4)] {
+            total += keno.swap(out, into).unwrap();
+        }
+        assert_eq!(keno.hits(), 4);
+        assert!((total - straight).abs() < 1e-9);
+        // Going back to likelier hit counts is free.
+        assert_eq!(keno.swap_cost(4, 12), Ok(0.0));
+    }
+}
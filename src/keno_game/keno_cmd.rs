@@ -0,0 +1,200 @@
+use std::{io::{stdin, stdout}, time::Instant};
+
+use crossterm::{style::Print, terminal, ExecutableCommand};
+use rand::Rng;
+
+use crate::{autosave::AutosaveEvent, common_state::CommonState, game::BetResult, machine::machine::Manipulation, keno_game::keno::{Keno, KenoState, KENO_MAX_SPOTS, KENO_NUMBERS, KENO_RETURN}, money::{looks_like_money, Money}};
+
+pub fn select_screen<R: Rng>(common_state: &mut CommonState, keno: &mut Keno,
+rng: &mut R) -> Option<KenoState> {
+    match keno.state {
+        KenoState::Hold => {
+            holding_screen(common_state, keno, rng)
+        },
+        KenoState::InBet => {
+            in_bet(common_state, keno, rng)
+        },
+    }
+}
+
+/// # Print Ticket
+/// 
+/// Prints the 80 numbers of the keno board, marking picks with *, drawn numbers
+/// with brackets, and hits with both.
+fn print_ticket(keno: &Keno) {
+    let mut board = String::new();
+    for number in 1..=KENO_NUMBERS {
+        let picked = keno.picks.contains(&number);
+        let drawn = keno.drawn.contains(&number);
+        let mark = if picked { "*" } else { " " };
+        if drawn {
+            board += format!("[{:>2}{}]", number, mark).as_str();
+        } else {
+            board += format!(" {:>2}{} ", number, mark).as_str();
+        }
+        if number % 10 == 0 {
+            board += "\n";
+        }
+    }
+    stdout().execute(Print(board)).unwrap();
+}
+
+/// # Print Paytable
+/// 
+/// Prints the payout rate for each paying number of hits on the current ticket.
+fn print_paytable(keno: &Keno) {
+    let mut table = String::from("Paytable: ");
+    for (hits, pay) in keno.paytable.iter().enumerate() {
+        if *pay > 0.0 {
+            table += format!("{} hits -> x{:.2} | ", hits, pay).as_str();
+        }
+    }
+    stdout().execute(Print(format!("{}\n", table))).unwrap();
+}
+
+/// # Holding Screen
+/// 
+/// Shows the ticket and last draw, and lets the player change their picks and bet.
+pub fn holding_screen<R: Rng>(common_state: &mut CommonState, keno: &mut Keno,
+rng: &mut R) -> Option<KenoState> {
+    stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
+    let mut msg = String::new();
+    loop {
+        let kicked_out = keno.base.kickout_update(Instant::now());
+        stdout().execute(Print(format!("\t\t!!!Keno!!!\nCommands: P # # .. -> Pick Numbers | R # -> Quick Pick # Spots | D -> Draw | Q -> Exit | Enter £sd to change Bet\nBet Min: {} | Bet Max: {}\n",
+            keno.base.bet_min, keno.base.bet_max))).unwrap();
+        stdout().execute(Print(format!("Money: {}\tEntropy: {} b\tSuspicion: {}\n", common_state.money, common_state.entropy, keno.base.suspicion))).unwrap();
+        stdout().execute(Print(format!("Current Bet: {}\n", keno.base.current_bet))).unwrap();
+        if kicked_out {
+            stdout().execute(Print(format!("!!! Kicked Out! Come back in {:.0} s !!!\n",
+                keno.base.kickout_time_remaining()))).unwrap();
+        }
+        print_paytable(keno);
+        print_ticket(keno);
+        stdout().execute(Print(format!("{}\n", msg))).unwrap();
+        let mut buff = String::new();
+        stdin().read_line(&mut buff).unwrap();
+        buff = buff.trim().to_lowercase();
+        let mut args = buff.split_whitespace();
+        let command = args.next().unwrap_or("");
+        let amount = buff.parse::<Money>();
+        if let Ok(bet) = amount {
+            if let Err(err) = keno.base.set_bet(bet, common_state.money) {
+                msg = err.to_string();
+            }
+        } else if command == "p" {
+            let picks: Result<Vec<u8>, _> = args.map(|a| a.parse::<u8>()).collect();
+            msg = match picks {
+                Ok(picks) => keno.set_picks(picks).err().unwrap_or_default(),
+                Err(_) => format!("Picks must be numbers between 1 and {}.", KENO_NUMBERS),
+            };
+        } else if command == "r" {
+            let spots = args.next().and_then(|a| a.parse::<u8>().ok()).unwrap_or(0);
+            msg = keno.quick_pick(rng, spots).err().unwrap_or_default();
+        } else if command == "d" {
+            if keno.picks.is_empty() {
+                msg = format!("Pick between 1 and {} numbers first.", KENO_MAX_SPOTS);
+            } else {
+                let stake = keno.base.current_bet;
+                match common_state.try_bet(&mut keno.base, stake, Instant::now()) {
+                    Ok(()) => {
+                        keno.state = KenoState::InBet;
+                        return Some(KenoState::InBet);
+                    },
+                    Err(err) => msg = err.to_string(),
+                }
+            }
+        } else if command == "q" {
+            return None;
+        } else {
+            msg = match amount {
+                Err(err) if looks_like_money(&buff) => err,
+                _ => String::from("Invalid Command."),
+            };
+        }
+        stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
+    }
+}
+
+/// # In Bet
+/// 
+/// Draws the numbers, then lets the player swap drawn numbers with entropy until
+/// they settle or the bet timer runs out.
+pub fn in_bet<R: Rng>(common_state: &mut CommonState, keno: &mut Keno,
+rng: &mut R) -> Option<KenoState> {
+    stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
+    // The stake was taken when the bet was placed.
+    let stake = keno.base.current_bet;
+    // draw the numbers
+    keno.bet(rng);
+    // Whether anything's been swapped, leaving nothing natural to harvest.
+    let mut swapped = false;
+    let mut msg = String::new();
+    loop {
+        stdout().execute(Print("\t\t!!!Keno!!!\nCommands: S <drawn> <new> -> Swap a drawn number | C -> Collect and end bet\n")).unwrap();
+        stdout().execute(Print(format!("Money: {}\tEntropy: {} b\tSuspicion: {}\n", common_state.money, common_state.entropy, keno.base.suspicion))).unwrap();
+        stdout().execute(Print(format!("Current Bet: {}\tEntropy on Result: {:.2}\n", stake,
+            if swapped { 0.0 } else { common_state.harvest_value(keno.entropy_gained()) }))).unwrap();
+        stdout().execute(Print(format!("Time Remaining: {:.0} s\n", keno.bet_time_remaining()))).unwrap();
+        print_paytable(keno);
+        print_ticket(keno);
+        stdout().execute(Print(format!("Hits: {} -> x{:.2}\n", keno.hits(),
+            keno.base.suspicion_payout(keno.payout_rate())))).unwrap();
+        stdout().execute(Print(format!("{}\n", msg))).unwrap();
+        let mut buff = String::new();
+        stdin().read_line(&mut buff).unwrap();
+        buff = buff.trim().to_lowercase();
+        // Bets close once time is up, no matter what was asked for.
+        if keno.bet_time_remaining() == 0.0 {
+            break;
+        }
+        let mut args = buff.split_whitespace();
+        let command = args.next().unwrap_or("");
+        if command == "s" {
+            let numbers: Vec<u8> = args.filter_map(|a| a.parse::<u8>().ok()).collect();
+            if numbers.len() != 2 {
+                msg = String::from("Swap needs the drawn number and the number to replace it.");
+            } else {
+                match keno.swap_cost(numbers[0], numbers[1]) {
+                    Ok(cost) => match common_state.spend_manipulation(Manipulation::Selection, cost,
+                        "Keno swap", rng) {
+                        Ok(made) => {
+                            if made.took_effect() {
+                                keno.swap(numbers[0], numbers[1]).unwrap();
+                                swapped = true;
+                                keno.base.add_suspicion(cost);
+                            }
+                            keno.base.add_suspicion(made.suspicion_spike());
+                            msg = made.report(&format!("Swapped {} for {}", numbers[0], numbers[1]));
+                            if keno.base.kickout_roll(rng, Instant::now()) {
+                                // Caught, the stake is forfeit.
+                                common_state.forfeit_bet(&mut keno.base);
+                                common_state.autosave.request(AutosaveEvent::Kickout);
+                                keno.state = KenoState::Hold;
+                                return Some(KenoState::Hold);
+                            }
+                        },
+                        Err(err) => msg = err.to_string(),
+                    },
+                    Err(err) => msg = err,
+                }
+            }
+        } else if command == "c" {
+            break;
+        } else {
+            msg = String::from("Invalid Command.");
+        }
+        stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
+    }
+    // Only a natural draw is harvested.
+    if !swapped {
+        common_state.harvest(keno.entropy_gained());
+    }
+    // finalize financial gains.
+    let rate = keno.payout_rate();
+    let result = BetResult { won: rate > 0.0, payout: rate,
+        win_chance: keno.win_chance(), expected_return: KENO_RETURN };
+    common_state.settle_bet(&mut keno.base, result);
+    keno.state = KenoState::Hold;
+    Some(KenoState::Hold)
+}
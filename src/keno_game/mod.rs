@@ -0,0 +1,4 @@
+pub mod keno;
+pub mod keno_cmd;
+pub mod bingo;
+pub mod bingo_cmd;
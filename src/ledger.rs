@@ -0,0 +1,142 @@
+use std::{collections::VecDeque, fmt, time::Duration};
+
+use crate::{money::Money, save::{SaveError, Section}};
+
+/// How many transactions the ledger keeps before the oldest are dropped.
+pub const LEDGER_LENGTH: usize = 500;
+
+/// # Spend Error
+/// 
+/// Why money or entropy couldn't be spent, or a bet couldn't be placed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpendError {
+    /// There isn't enough money to cover it.
+    InsufficientFunds { needed: Money, available: Money },
+    /// There isn't enough entropy to cover it.
+    InsufficientEntropy { needed: f64, available: f64 },
+    /// The bet is outside the game's limits.
+    BetOutOfRange { bet: Money, min: Money, max: Money },
+    /// The game has kicked the player out, for this many more seconds.
+    KickedOut { remaining: f64 },
+}
+
+impl fmt::Display for SpendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpendError::InsufficientFunds { needed, available } =>
+                write!(f, "Not enough money!! That needs {}, you have {}.", needed, available),
+            SpendError::InsufficientEntropy { needed, available } =>
+                write!(f, "Not enough entropy! That costs {:.2} b, you have {:.2} b.", needed,
+                    available),
+            SpendError::BetOutOfRange { min, max, .. } =>
+                write!(f, "Bet must be within bounds! ({} to {})", min, max),
+            SpendError::KickedOut { remaining } =>
+                write!(f, "They won't let you play while kicked out. ({:.0} s left)", remaining),
+        }
+    }
+}
+
+impl std::error::Error for SpendError {}
+
+/// # Transaction Kind
+/// 
+/// What a transaction changed, and by how much. Spending is negative.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransactionKind {
+    Money(Money),
+    Entropy(f64),
+}
+
+/// # Transaction
+/// 
+/// A single change to the player's money or entropy.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transaction {
+    /// The game time it happened at.
+    pub time: Duration,
+    pub kind: TransactionKind,
+    /// What it was for, "Keno bet", "Hi-Lo force", and the like.
+    pub reason: String,
+}
+
+impl fmt::Display for Transaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let change = match self.kind {
+            TransactionKind::Money(m) if m.is_negative() => format!("{}", m),
+            TransactionKind::Money(m) => format!("+{}", m),
+            TransactionKind::Entropy(b) => format!("{:+.2} b", b),
+        };
+        write!(f, "[{:.0} s] {}: {}", self.time.as_secs_f64(), self.reason, change)
+    }
+}
+
+/// # Ledger
+/// 
+/// The log of every change to the player's money and entropy, newest last.
+/// 
+/// Only the last LEDGER_LENGTH are kept.
+#[derive(Debug, Default)]
+pub struct Ledger {
+    pub transactions: VecDeque<Transaction>,
+}
+
+impl Ledger {
+    /// # Record
+    /// 
+    /// Adds a transaction to the log, dropping the oldest if it's full.
+    pub fn record(&mut self, time: Duration, kind: TransactionKind, reason: &str) {
+        if self.transactions.len() >= LEDGER_LENGTH {
+            self.transactions.pop_front();
+        }
+        self.transactions.push_back(Transaction { time, kind, reason: reason.to_string() });
+    }
+
+    /// # Recent
+    /// 
+    /// The last few transactions, newest first.
+    pub fn recent(&self, count: usize) -> impl Iterator<Item = &Transaction> {
+        self.transactions.iter().rev().take(count)
+    }
+
+    /// # Save Section
+    /// 
+    /// Writes the ledger to a [ledger] save section, a transaction per line as
+    /// time,kind,change,reason.
+    pub fn save_section(&self) -> Section {
+        let mut section = Section::new("ledger");
+        for t in self.transactions.iter() {
+            let (kind, change) = match t.kind {
+                TransactionKind::Money(m) => ("money", m.save_str()),
+                TransactionKind::Entropy(b) => ("entropy", b.to_string()),
+            };
+            section.set("transaction", format!("{},{},{},{}", t.time.as_secs_f64(), kind,
+                change, t.reason));
+        }
+        section
+    }
+
+    /// # Load Section
+    /// 
+    /// Reads the ledger from a section written by save_section.
+    pub fn load_section(section: &Section) -> Result<Self, SaveError> {
+        let mut ledger = Self::default();
+        for value in section.get_all("transaction") {
+            let transaction = Self::parse_transaction(value)
+                .ok_or_else(|| section.bad_value("transaction", value))?;
+            ledger.transactions.push_back(transaction);
+        }
+        Ok(ledger)
+    }
+
+    /// Reads a single time,kind,change,reason transaction.
+    fn parse_transaction(value: &str) -> Option<Transaction> {
+        let mut fields = value.splitn(4, ',');
+        let time = Duration::try_from_secs_f64(fields.next()?.parse().ok()?).ok()?;
+        let kind = match (fields.next()?, fields.next()?) {
+            ("money", change) => TransactionKind::Money(Money::load_str(change)?),
+            ("entropy", change) => TransactionKind::Entropy(change.parse().ok()?),
+            _ => return None,
+        };
+        Some(Transaction { time, kind, reason: fields.next()?.to_string() })
+    }
+}
@@ -0,0 +1,211 @@
+use std::{fmt, time::Duration};
+
+use crate::{money::Money, save::{SaveError, Section}};
+
+/// How long each contract gives to finish it, in game time.
+pub const CONTRACT_TIME: Duration = Duration::from_secs(15 * 60);
+/// How many times harder each contract is than the last.
+pub const CONTRACT_GROWTH: f64 = 1.3;
+/// The share of the entropy cap the first entropy delivery asks for. Later ones ask
+/// for more, but never past the cap.
+pub const CONTRACT_ENTROPY_SHARE: f64 = 0.3;
+/// The most bits of surprisal a feat asks for.
+pub const FEAT_BITS_MAX: f64 = 10.0;
+
+/// # Contract Goal
+/// 
+/// What the Machine wants delivered, and how much of it has been.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContractGoal {
+    /// Deliver this much entropy to the machine.
+    Entropy { needed: f64, delivered: f64 },
+    /// Deliver this much money to the machine.
+    Money { needed: Money, delivered: Money },
+    /// Win a bet that had at most a 1 in 2^bits chance.
+    Feat { bits: f64, done: bool },
+}
+
+impl ContractGoal {
+    /// # Met
+    pub fn met(&self) -> bool {
+        match self {
+            ContractGoal::Entropy { needed, delivered } => delivered >= needed,
+            ContractGoal::Money { needed, delivered } => delivered >= needed,
+            ContractGoal::Feat { done, .. } => *done,
+        }
+    }
+}
+
+impl fmt::Display for ContractGoal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContractGoal::Entropy { needed, delivered } =>
+                write!(f, "Deliver {:.2} b of entropy ({:.2} b delivered)", needed, delivered),
+            ContractGoal::Money { needed, delivered } =>
+                write!(f, "Deliver {} ({} delivered)", needed, delivered),
+            ContractGoal::Feat { bits, .. } =>
+                write!(f, "Win a bet at 1 in {:.0} odds or longer", 2f64.powf(*bits)),
+        }
+    }
+}
+
+/// # Contract
+/// 
+/// A demand from the Machine, with a game time deadline. Meeting it pays the reward,
+/// missing it costs the penalty.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Contract {
+    pub goal: ContractGoal,
+    /// The game time it has to be met by.
+    pub deadline: Duration,
+    /// The machine levels granted for meeting it.
+    pub reward_levels: u64,
+    /// The money paid for meeting it.
+    pub reward_money: Money,
+    /// The money fined for missing it, as much as the player has.
+    pub penalty_money: Money,
+    /// The machine levels taken for missing it.
+    pub penalty_levels: u64,
+}
+
+impl Contract {
+    /// # Issue
+    /// 
+    /// The Machine's next contract, the given number since the first. Contracts take
+    /// turns asking for entropy, money and feats, each harder and better paid than
+    /// the last.
+    pub fn issue(number: u32, entropy_cap: f64, now: Duration) -> Self {
+        let scale = CONTRACT_GROWTH.powi(number as i32);
+        let goal = match number % 3 {
+            0 => ContractGoal::Entropy {
+                needed: (entropy_cap * CONTRACT_ENTROPY_SHARE * scale).min(entropy_cap).round(),
+                delivered: 0.0 },
+            1 => ContractGoal::Money { needed: Money::from_shillings(5).mul_ratio(scale),
+                delivered: Money::ZERO },
+            _ => ContractGoal::Feat { bits: (3.0 + number as f64 / 3.0).floor().min(FEAT_BITS_MAX),
+                done: false },
+        };
+        let reward_money = Money::from_shillings(4).mul_ratio(scale);
+        Self {
+            goal,
+            deadline: now + CONTRACT_TIME,
+            reward_levels: 2 + number as u64,
+            reward_money,
+            penalty_money: reward_money.mul_ratio(0.5),
+            penalty_levels: 1 + number as u64 / 2,
+        }
+    }
+
+    /// # Time Left
+    /// 
+    /// The game time left before the deadline.
+    pub fn time_left(&self, now: Duration) -> Duration {
+        self.deadline.saturating_sub(now)
+    }
+}
+
+impl fmt::Display for Contract {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}. Reward: {} levels and {}. Penalty: {} levels and {}.", self.goal,
+            self.reward_levels, self.reward_money, self.penalty_levels, self.penalty_money)
+    }
+}
+
+/// # Contracts
+/// 
+/// The Machine's demands on the player: the contract they're working on, and how
+/// the ones before went.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Contracts {
+    /// The contract being worked on. A new one is issued once it's over.
+    pub active: Option<Contract>,
+    pub completed: u32,
+    pub failed: u32,
+    /// What happened to the last contract, for the player to see. Not saved.
+    pub news: Option<String>,
+}
+
+impl Contracts {
+    /// # Issued
+    /// 
+    /// How many contracts have been issued and finished, which sets the next one.
+    pub fn issued(&self) -> u32 {
+        self.completed + self.failed
+    }
+
+    /// # Summary
+    /// 
+    /// The running contract and the time left on it, for the screens to show.
+    pub fn summary(&self, now: Duration) -> String {
+        match &self.active {
+            Some(contract) => format!("Contract: {} Time Left: {:.0} s", contract,
+                contract.time_left(now).as_secs_f64()),
+            None => String::from("Contract: None running."),
+        }
+    }
+
+    /// # Save Section
+    /// 
+    /// Writes the contracts to a [contracts] save section.
+    pub fn save_section(&self) -> Section {
+        let mut section = Section::new("contracts");
+        section.set("completed", self.completed);
+        section.set("failed", self.failed);
+        if let Some(contract) = &self.active {
+            match contract.goal {
+                ContractGoal::Entropy { needed, delivered } => {
+                    section.set("goal", "entropy");
+                    section.set("needed", needed);
+                    section.set("delivered", delivered);
+                },
+                ContractGoal::Money { needed, delivered } => {
+                    section.set("goal", "money");
+                    section.set("needed", needed.save_str());
+                    section.set("delivered", delivered.save_str());
+                },
+                ContractGoal::Feat { bits, done } => {
+                    section.set("goal", "feat");
+                    section.set("needed", bits);
+                    section.set("delivered", done);
+                },
+            }
+            section.set("deadline", contract.deadline.as_secs_f64());
+            section.set("reward_levels", contract.reward_levels);
+            section.set("reward_money", contract.reward_money.save_str());
+            section.set("penalty_money", contract.penalty_money.save_str());
+            section.set("penalty_levels", contract.penalty_levels);
+        }
+        section
+    }
+
+    /// # Load Section
+    /// 
+    /// Reads the contracts from a section written by save_section.
+    pub fn load_section(section: &Section) -> Result<Self, SaveError> {
+        let active = match section.get("goal") {
+            None => None,
+            Some(goal) => Some(Contract {
+                goal: match goal {
+                    "entropy" => ContractGoal::Entropy { needed: section.parse("needed")?,
+                        delivered: section.parse("delivered")? },
+                    "money" => ContractGoal::Money { needed: section.parse_money("needed")?,
+                        delivered: section.parse_money("delivered")? },
+                    "feat" => ContractGoal::Feat { bits: section.parse("needed")?,
+                        done: section.parse("delivered")? },
+                    _ => return Err(section.bad_value("goal", goal)),
+                },
+                deadline: section.parse_secs("deadline")?,
+                reward_levels: section.parse("reward_levels")?,
+                reward_money: section.parse_money("reward_money")?,
+                penalty_money: section.parse_money("penalty_money")?,
+                penalty_levels: section.parse("penalty_levels")?,
+            }),
+        };
+        Ok(Self {
+            active,
+            completed: section.parse("completed")?,
+            failed: section.parse("failed")?,
+            news: None,
+        })
+    }
+}
\ No newline at end of file
@@ -1,27 +1,745 @@
+use std::{fmt, time::Duration};
+
+use rand::Rng;
+
+use crate::{machine::{contract::Contracts, research::{Research, EXCHANGER_BOOST, RESONATOR_SHARE}}, money::Money, save::{SaveError, Section}};
+
+/// What the player must keep back when investing, so they can still bet.
+pub const INVEST_RESERVE: Money = Money::from_pence(1);
+/// The entropy the machine holds before any levels are bought.
+pub const BASE_ENTROPY_CAP: f64 = 100.0;
+/// The share of what a level cost that's paid back when it's sold.
+pub const SELL_BACK_SHARE: f64 = 0.5;
+/// The highest level the machine can be upgraded to.
+pub const MAX_LEVEL: u64 = 250;
+/// The entropy the machine regenerates each second at level 0.
+pub const REGEN_BASE: f64 = 0.005;
+/// How much each machine level adds to regeneration, as a share of the base.
+pub const REGEN_PER_LEVEL: f64 = 0.02;
+/// The share of the entropy cap above which stored entropy starts leaking.
+pub const LEAK_THRESHOLD: f64 = 0.9;
+/// The share of the entropy above the threshold that leaks away each second.
+pub const LEAK_RATE: f64 = 0.002;
+/// How much of the leak is left by each component level, as they seal the machine.
+pub const LEAK_SEAL: f64 = 0.97;
+/// The most time the machine runs for while the game is closed.
+pub const OFFLINE_RUN_MAX: Duration = Duration::from_secs(8 * 60 * 60);
+/// How far past the cap the machine can be overcharged, as a share of the cap.
+pub const OVERCHARGE_MAX: f64 = 0.5;
+/// The chance of a malfunction with the machine overcharged all the way.
+pub const OVERCHARGE_RISK_MAX: f64 = 0.5;
+/// The heat the machine can take before it risks malfunctioning.
+pub const HEAT_SAFE: f64 = 8.0;
+/// The chance of a malfunction at twice the safe heat, or hotter.
+pub const HEAT_RISK_MAX: f64 = 0.5;
+/// The heat the machine sheds each second with no cooling upgrades.
+pub const COOLING_BASE: f64 = 0.05;
+/// How much each cooling level adds to the heat shed, as a share of the base.
+pub const COOLING_PER_LEVEL: f64 = 0.25;
+/// What the first cooling level costs.
+pub const COOLING_FIRST_COST: Money = Money::from_shillings(5);
+/// How many times more each cooling level costs than the last.
+pub const COOLING_GROWTH: f64 = 1.6;
+/// The most cooling levels the machine can have.
+pub const COOLING_MAX: u32 = 20;
+/// The share of stored entropy lost when the machine vents.
+pub const VENT_SHARE: f64 = 0.25;
+/// The bits of suspicion a table gets from a malfunction drawing its eye.
+pub const SUSPICION_SPIKE_BITS: f64 = 10.0;
+/// The longest stretch the machine is run for in one step.
+const RUN_STEP: Duration = Duration::from_secs(1);
+
+/// # Machine Tier
+/// 
+/// A stretch of the machine's upgrade curve, unlocked by reaching its first level.
+/// Each level of a tier costs growth times the one before, starting from the base
+/// cost, and adds the same amount of entropy cap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MachineTier {
+    pub name: &'static str,
+    /// The milestone level that unlocks the tier.
+    pub first_level: u64,
+    /// What the tier's first level costs.
+    pub base_cost: Money,
+    pub growth: f64,
+    pub cap_per_level: f64,
+}
+
+/// # Machine Tiers
+/// 
+/// The upgrade curve, in order of first level. Each tier gives more cap a level,
+/// but costs more for it than the last, so every bit of cap is dearer than the one
+/// before. The last tier runs to the max level.
+pub const MACHINE_TIERS: [MachineTier; 5] = [
+    MachineTier { name: "Tinkerer's Bench", first_level: 0, base_cost: Money::from_pence(2),
+        growth: 1.02, cap_per_level: 1.0 },
+    MachineTier { name: "Workshop Engine", first_level: 25, base_cost: Money::from_shillings(1),
+        growth: 1.03, cap_per_level: 2.0 },
+    MachineTier { name: "Difference Engine", first_level: 60, base_cost: Money::from_shillings(10),
+        growth: 1.04, cap_per_level: 4.0 },
+    MachineTier { name: "Analytical Engine", first_level: 100, base_cost: Money::from_pounds(5),
+        growth: 1.05, cap_per_level: 6.0 },
+    MachineTier { name: "Improbability Drive", first_level: 150, base_cost: Money::from_pounds(200),
+        growth: 1.06, cap_per_level: 8.0 },
+];
+
+/// # Balance Row
+/// 
+/// One level of the upgrade curve, as shown in the balance report.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BalanceRow {
+    /// The level bought.
+    pub level: u64,
+    pub tier: &'static str,
+    /// What the level costs.
+    pub cost: Money,
+    /// The entropy cap once the level is bought.
+    pub cap: f64,
+    /// What every level up to and including this one costs together.
+    pub spent: Money,
+    /// What each bit of cap this level adds costs.
+    pub marginal: Money,
+    /// What each bit of cap above the base has cost on average.
+    pub average: Money,
+}
+
+impl BalanceRow {
+    /// The heading for the report's columns.
+    pub const HEADER: &str = "Level | Tier | Cost | Cap | Total Spent | Cost per Bit | Average per Bit";
+}
+
+impl fmt::Display for BalanceRow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} | {} | {} | {} | {} | {} | {}", self.level, self.tier, self.cost, self.cap,
+            self.spent, self.marginal, self.average)
+    }
+}
+/// How much of a manipulation's entropy cost is left by each level of its component.
+pub const COMPONENT_DISCOUNT: f64 = 0.95;
+/// The least share of a manipulation's entropy cost components can bring it down to.
+pub const COMPONENT_COST_FLOOR: f64 = 0.25;
+
+/// # Manipulation
+/// 
+/// The classes of manipulation the machine can make, each with its own component.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Manipulation {
+    /// Running a random outcome again, like reflipping a coin.
+    Reroll,
+    /// Ruling out the losing outcomes and leaving the rest to chance, like forcing a
+    /// Hi-Lo call, a Baccarat result, or a match result.
+    Elimination,
+    /// Picking the exact outcome, like forcing a coin's side, swapping a drawn number,
+    /// or nudging the wheel.
+    Selection,
+}
+
+impl Manipulation {
+    pub const ALL: [Manipulation; 3] = [Manipulation::Reroll, Manipulation::Elimination,
+        Manipulation::Selection];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Manipulation::Reroll => "Reroll",
+            Manipulation::Elimination => "Elimination",
+            Manipulation::Selection => "Selection",
+        }
+    }
+
+    /// # Parse
+    /// 
+    /// Reads a manipulation class from its name or first letter.
+    pub fn parse(s: &str) -> Option<Self> {
+        Manipulation::ALL.into_iter().find(|kind| {
+            let name = kind.name().to_lowercase();
+            s == name || s == &name[..1]
+        })
+    }
+
+    /// # Cost Curve
+    /// 
+    /// What the component's first level costs, and how many times more each level 
+    /// after costs than the last. Picking the exact outcome is worth the most.
+    pub fn cost_curve(&self) -> (Money, f64) {
+        match self {
+            Manipulation::Reroll => (Money::from_shillings(1), 1.5),
+            Manipulation::Elimination => (Money::from_shillings(2), 1.6),
+            Manipulation::Selection => (Money::from_shillings(4), 1.75),
+        }
+    }
+
+    /// Where the component's level goes in the save.
+    fn save_key(&self) -> &'static str {
+        match self {
+            Manipulation::Reroll => "reroll",
+            Manipulation::Elimination => "elimination",
+            Manipulation::Selection => "selection",
+        }
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// # Malfunction
+/// 
+/// What goes wrong when an overheated or overcharged machine slips.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Malfunction {
+    /// The manipulation didn't take, though the entropy is spent.
+    Misfire,
+    /// The machine vented this much of its stored entropy.
+    Vent(f64),
+    /// The racket drew the table's eye, adding this many bits of suspicion.
+    SuspicionSpike(f64),
+}
+
+impl fmt::Display for Malfunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Malfunction::Misfire => write!(f, "The machine misfired, the manipulation didn't take!"),
+            Malfunction::Vent(bits) => write!(f, "The machine vented {:.2} b!", bits),
+            Malfunction::SuspicionSpike(_) => write!(f, "The machine's racket drew the table's eye!"),
+        }
+    }
+}
+
+/// # Manipulated
+/// 
+/// A manipulation the machine made, what it cost, and how it went.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Manipulated {
+    /// The entropy spent on it.
+    pub spent: f64,
+    pub malfunction: Option<Malfunction>,
+}
+
+impl Manipulated {
+    /// # Took Effect
+    /// 
+    /// Whether the manipulation happened, rather than misfiring.
+    pub fn took_effect(&self) -> bool {
+        self.malfunction != Some(Malfunction::Misfire)
+    }
+
+    /// # Suspicion Spike
+    /// 
+    /// The bits of suspicion a malfunction added to the table, if any.
+    pub fn suspicion_spike(&self) -> f64 {
+        match self.malfunction {
+            Some(Malfunction::SuspicionSpike(bits)) => bits,
+            _ => 0.0,
+        }
+    }
+
+    /// # Report
+    /// 
+    /// What the player is told about the manipulation, with what it cost. Done is
+    /// what it did, if it didn't misfire.
+    pub fn report(&self, done: &str) -> String {
+        match self.malfunction {
+            Some(Malfunction::Misfire) => format!("{} {:.2} b spent for nothing.",
+                Malfunction::Misfire, self.spent),
+            Some(malfunction) => format!("{} for {:.2} b. {}", done, self.spent, malfunction),
+            None => format!("{} for {:.2} b.", done, self.spent),
+        }
+    }
+}
+
 /// # Machine
 /// 
 /// The Machine is the Improbability machine you are working for.
 #[derive(Debug)]
 pub struct Machine {
-    /// The current level of the building. 
-    /// 
-    /// Currently, this just converts money to entropy cap at a 2->1 ratio.
-    pub level: f64,
-    // Extra level/efficiency boosters would go here.
-    // Efficiency would be for rerolls, elimination, and selection. 
+    /// The current level of the building, which sets the entropy cap along the
+    /// upgrade curve.
+    pub level: u64,
+    /// The level of the component for each manipulation class, in the order of
+    /// Manipulation::ALL. Each level makes its class of manipulation cheaper.
+    pub components: [u32; 3],
+    /// How hot the machine has run. Every bit spent on manipulation heats it, and it
+    /// cools over game time.
+    pub heat: f64,
+    /// The level of the machine's cooling, which sheds heat faster.
+    pub cooling: u32,
+    /// Everything the machine has researched, in the order it was.
+    pub research: Vec<Research>,
+    /// What the machine wants from the player, and how they've done at it.
+    pub contracts: Contracts,
+    /// What's been paid for the machine's levels, less what's gone with levels sold or
+    /// lost. Levels granted by contracts, or carried over from saves that didn't 
+    /// track it, were never paid for, so they add nothing.
+    pub invested: Money,
 }
 
 impl Machine {
-    pub fn new(level: f64) -> Self {
+    pub fn new(level: u64) -> Self {
         Self { 
-            level
+            level,
+            components: [0; 3],
+            heat: 0.0,
+            cooling: 0,
+            research: vec![],
+            contracts: Contracts::default(),
+            invested: Money::ZERO,
         }
     }
 
+    /// # Tier At
+    /// 
+    /// The tier a level is bought in.
+    pub fn tier_at(level: u64) -> &'static MachineTier {
+        MACHINE_TIERS.iter().rev().find(|tier| tier.first_level <= level)
+            .unwrap_or(&MACHINE_TIERS[0])
+    }
+
+    /// # Tier
+    /// 
+    /// The tier the machine has reached.
+    pub fn tier(&self) -> &'static MachineTier {
+        Self::tier_at(self.level)
+    }
+
+    /// # Next Tier
+    /// 
+    /// The tier the machine unlocks next, if there's one left.
+    pub fn next_tier(&self) -> Option<&'static MachineTier> {
+        MACHINE_TIERS.iter().find(|tier| tier.first_level > self.level)
+    }
+
+    /// # Unlocked Since
+    /// 
+    /// The tier the machine is in now, if it was unlocked since the given level.
+    pub fn unlocked_since(&self, before: u64) -> Option<&'static MachineTier> {
+        Some(self.tier()).filter(|tier| tier.first_level > before)
+    }
+
+    /// # Level Cost
+    /// 
+    /// What buying the given level costs, going from it to the one after.
+    pub fn level_cost(level: u64) -> Money {
+        let tier = Self::tier_at(level);
+        tier.base_cost.mul_ratio(tier.growth.powi((level - tier.first_level) as i32))
+    }
+
+    /// # Cap At
+    /// 
+    /// The entropy cap of a machine at the given level.
+    pub fn cap_at(level: u64) -> f64 {
+        BASE_ENTROPY_CAP + (0..level).map(|l| Self::tier_at(l).cap_per_level).sum::<f64>()
+    }
+
     /// The maximum Entropy that can be stored by the Improbability Machine.
     /// 
-    /// Entropy Cap is equal to 100 + Level.
+    /// Entropy Cap is the base cap, plus what each level bought added.
     pub fn entropy_cap(&self) -> f64 {
-        self.level + 100.0
+        Self::cap_at(self.level)
+    }
+
+    /// # Regen Rate
+    /// 
+    /// The entropy the machine regenerates each second, growing with its level.
+    pub fn regen_rate(&self) -> f64 {
+        REGEN_BASE * (1.0 + self.level as f64 * REGEN_PER_LEVEL)
+    }
+
+    /// # Leak Rate
+    /// 
+    /// The share of entropy above the leak threshold lost each second. Every
+    /// component level seals the machine a little more.
+    pub fn leak_rate(&self) -> f64 {
+        LEAK_RATE * LEAK_SEAL.powi(self.components.iter().sum::<u32>() as i32)
+    }
+
+    /// # Run
+    /// 
+    /// Runs the machine for a stretch of game time, starting with the given entropy
+    /// stored. Regeneration fills it up to the cap, and anything stored above the leak
+    /// threshold leaks away. Returns the entropy regenerated and leaked.
+    pub fn run(&self, entropy: f64, elapsed: Duration) -> (f64, f64) {
+        let cap = self.entropy_cap();
+        let threshold = cap * LEAK_THRESHOLD;
+        let (mut regen, mut leak) = (0.0, 0.0);
+        let mut stored = entropy;
+        let mut left = elapsed;
+        while !left.is_zero() {
+            let step = left.min(RUN_STEP);
+            left -= step;
+            let secs = step.as_secs_f64();
+            let gained = (self.regen_rate() * secs).min(cap - stored).max(0.0);
+            stored += gained;
+            regen += gained;
+            let lost = (stored - threshold).max(0.0) * (self.leak_rate() * secs).min(1.0);
+            stored -= lost;
+            leak += lost;
+        }
+        (regen, leak)
+    }
+
+    /// # Upgrade Cost
+    /// 
+    /// What the next given number of levels cost, up to the max level.
+    pub fn upgrade_cost(&self, levels: u64) -> Money {
+        (self.level..self.level.saturating_add(levels).min(MAX_LEVEL))
+            .fold(Money::ZERO, |total, level| total + Self::level_cost(level))
+    }
+
+    /// # Levels For
+    /// 
+    /// How many whole levels an investment buys, and what they cost. Anything left
+    /// over isn't spent.
+    pub fn levels_for(&self, invest: Money) -> (u64, Money) {
+        let mut cost = Money::ZERO;
+        let mut level = self.level;
+        while level < MAX_LEVEL && cost + Self::level_cost(level) <= invest {
+            cost += Self::level_cost(level);
+            level += 1;
+        }
+        (level - self.level, cost)
     }
-}
\ No newline at end of file
+
+    /// # Check Invest
+    /// 
+    /// Checks what an investment would buy, keeping the reserve back from the money
+    /// available. Returns the levels and their cost, or why it can't be made.
+    pub fn check_invest(&self, invest: Money, money: Money) -> Result<(u64, Money), String> {
+        if self.level >= MAX_LEVEL {
+            return Err(String::from("The machine can't be upgraded any further."));
+        }
+        if invest.checked_add(INVEST_RESERVE).is_none_or(|needed| needed > money) {
+            return Err(format!("Cannot invest that much, must have at least {} left.",
+                INVEST_RESERVE));
+        }
+        match self.levels_for(invest) {
+            (0, _) => Err(format!("Too little to buy a level, the next costs {}.",
+                self.upgrade_cost(1))),
+            bought => Ok(bought),
+        }
+    }
+
+    /// What the levels from one level up to another cost on the curve.
+    fn curve_cost(from: u64, to: u64) -> Money {
+        (from..to).fold(Money::ZERO, |total, level| total + Self::level_cost(level))
+    }
+
+    /// # Cost Basis
+    /// 
+    /// What was paid for the top given number of levels. The investment is shared
+    /// out over the levels by what each costs on the curve, so levels that were 
+    /// never paid for thin out what each is worth.
+    pub fn cost_basis(&self, levels: u64) -> Money {
+        let total = Self::curve_cost(0, self.level);
+        if total.is_zero() {
+            return Money::ZERO;
+        }
+        let top = Self::curve_cost(self.level.saturating_sub(levels), self.level);
+        self.invested.mul_ratio(top.ratio(total)).min(self.invested)
+    }
+
+    /// # Sell Value
+    /// 
+    /// What selling the top given number of levels pays back, a share of what was
+    /// paid for them.
+    pub fn sell_value(&self, levels: u64) -> Money {
+        self.cost_basis(levels).mul_ratio(SELL_BACK_SHARE)
+    }
+
+    /// # Drop Levels
+    /// 
+    /// Takes the top given number of levels off the machine, along with what was paid
+    /// for them.
+    pub fn drop_levels(&mut self, levels: u64) {
+        let levels = levels.min(self.level);
+        self.invested -= self.cost_basis(levels);
+        self.level -= levels;
+    }
+
+    /// # Check Sell
+    /// 
+    /// Checks the machine has the levels to sell. Returns what they'd pay back, or why
+    /// they can't be sold.
+    pub fn check_sell(&self, levels: u64) -> Result<Money, String> {
+        if levels == 0 {
+            Err(String::from("Pick at least one level to sell."))
+        } else if levels > self.level {
+            Err(format!("The machine only has {} levels to sell.", self.level))
+        } else {
+            Ok(self.sell_value(levels))
+        }
+    }
+
+    /// # Balance Report
+    /// 
+    /// Every level of the upgrade curve, with what it costs and the money-to-cap 
+    /// ratio it's bought at. For checking the curve is balanced.
+    pub fn balance_report() -> Vec<BalanceRow> {
+        let mut spent = Money::ZERO;
+        (0..MAX_LEVEL).map(|level| {
+            let tier = Self::tier_at(level);
+            let cost = Self::level_cost(level);
+            let cap = Self::cap_at(level + 1);
+            spent += cost;
+            BalanceRow {
+                level: level + 1,
+                tier: tier.name,
+                cost,
+                cap,
+                spent,
+                marginal: cost.mul_ratio(1.0 / tier.cap_per_level),
+                average: spent.mul_ratio(1.0 / (cap - BASE_ENTROPY_CAP)),
+            }
+        }).collect()
+    }
+
+    /// # Level From Save
+    /// 
+    /// Reads a saved level. Levels used to be saved as decimals, and bought far more
+    /// cheaply, so they're rounded down, held to the max level, and sell back for nothing.
+    pub fn level_from_save(value: &str) -> Option<u64> {
+        value.trim().parse::<f64>().ok().filter(|level| *level >= 0.0)
+            .map(|level| (level as u64).min(MAX_LEVEL))
+    }
+
+    /// # Component Level
+    pub fn component_level(&self, kind: Manipulation) -> u32 {
+        self.components[kind.index()]
+    }
+
+    /// # Cost Share
+    /// 
+    /// The share of a manipulation's entropy cost left after its component's discount.
+    pub fn cost_share(&self, kind: Manipulation) -> f64 {
+        COMPONENT_DISCOUNT.powi(self.component_level(kind) as i32).max(COMPONENT_COST_FLOOR)
+    }
+
+    /// # Entropy Cost
+    /// 
+    /// What a manipulation of the given surprisal costs, with the discounts taken off.
+    pub fn entropy_cost(&self, kind: Manipulation, bits: f64) -> f64 {
+        let tuned = if self.researched(Research::TunedResonators) { RESONATOR_SHARE } else { 1.0 };
+        bits * self.cost_share(kind) * tuned
+    }
+
+    /// # Component Cost
+    /// 
+    /// What the next level of the component costs, along its cost curve.
+    pub fn component_cost(&self, kind: Manipulation) -> Money {
+        let (first, growth) = kind.cost_curve();
+        first.mul_ratio(growth.powi(self.component_level(kind) as i32))
+    }
+
+    /// # Check Component
+    /// 
+    /// Checks the component's next level can be bought, keeping the reserve back from
+    /// the money available. Returns what it costs, or why it can't be bought.
+    pub fn check_component(&self, kind: Manipulation, money: Money) -> Result<Money, String> {
+        let cost = self.component_cost(kind);
+        if self.cost_share(kind) <= COMPONENT_COST_FLOOR {
+            Err(format!("The {} component can't get any better.", kind.name()))
+        } else if cost.checked_add(INVEST_RESERVE).is_none_or(|needed| needed > money) {
+            Err(format!("The next {} level costs {}, and at least {} must be left.",
+                kind.name(), cost, INVEST_RESERVE))
+        } else {
+            Ok(cost)
+        }
+    }
+
+    /// # Overcharge Cap
+    /// 
+    /// The most entropy the machine can hold, overcharged past its cap.
+    pub fn overcharge_cap(&self) -> f64 {
+        self.entropy_cap() * (1.0 + OVERCHARGE_MAX)
+    }
+
+    /// # Cooling Rate
+    /// 
+    /// The heat the machine sheds each second.
+    pub fn cooling_rate(&self) -> f64 {
+        let boost = if self.researched(Research::HeatExchangers) { EXCHANGER_BOOST } else { 1.0 };
+        COOLING_BASE * (1.0 + self.cooling as f64 * COOLING_PER_LEVEL) * boost
+    }
+
+    /// # Cool
+    /// 
+    /// Sheds heat for a stretch of game time.
+    pub fn cool(&mut self, elapsed: Duration) {
+        self.heat = (self.heat - self.cooling_rate() * elapsed.as_secs_f64()).max(0.0);
+    }
+
+    /// # Malfunction Chance
+    /// 
+    /// The chance a manipulation goes wrong with the given entropy stored. It grows 
+    /// the further the machine is overcharged and the hotter it runs past safe.
+    pub fn malfunction_chance(&self, entropy: f64) -> f64 {
+        let cap = self.entropy_cap();
+        let overcharge = ((entropy - cap) / (cap * OVERCHARGE_MAX)).clamp(0.0, 1.0);
+        let overheat = ((self.heat - HEAT_SAFE) / HEAT_SAFE).clamp(0.0, 1.0);
+        1.0 - (1.0 - overcharge * OVERCHARGE_RISK_MAX) * (1.0 - overheat * HEAT_RISK_MAX)
+    }
+
+    /// # Manipulate
+    /// 
+    /// Heats the machine for the bits spent on a manipulation, then rolls for a
+    /// malfunction at the risk it's running at. A vent takes its share of the given
+    /// entropy, which the caller removes.
+    pub fn manipulate<R: Rng>(&mut self, rng: &mut R, spent: f64, entropy: f64)
+    -> Option<Malfunction> {
+        self.heat += spent;
+        if !rng.random_bool(self.malfunction_chance(entropy)) {
+            return None;
+        }
+        Some(match rng.random_range(0..3) {
+            0 => Malfunction::Misfire,
+            1 => {
+                // Venting lets some of the heat out with it.
+                self.heat /= 2.0;
+                Malfunction::Vent(entropy * VENT_SHARE)
+            },
+            _ => Malfunction::SuspicionSpike(SUSPICION_SPIKE_BITS),
+        })
+    }
+
+    /// # Status
+    /// 
+    /// A readout of the machine's heat, charge and risk, with the given entropy stored.
+    pub fn status(&self, entropy: f64) -> String {
+        let cap = self.entropy_cap();
+        let charge = if entropy > cap {
+            format!("Overcharged {:.2} b", entropy - cap)
+        } else {
+            format!("Charged {:.0}%", entropy / cap * 100.0)
+        };
+        format!("Heat: {:.2} / {:.0}  {}  Malfunction Risk: {:.0}%", self.heat, HEAT_SAFE, charge,
+            self.malfunction_chance(entropy) * 100.0)
+    }
+
+    /// # Cooling Cost
+    /// 
+    /// What the next cooling level costs, along the same kind of curve as components.
+    pub fn cooling_cost(&self) -> Money {
+        COOLING_FIRST_COST.mul_ratio(COOLING_GROWTH.powi(self.cooling as i32))
+    }
+
+    /// # Check Cooling
+    /// 
+    /// Checks the next cooling level can be bought, keeping the reserve back from the
+    /// money available. Returns what it costs, or why it can't be bought.
+    pub fn check_cooling(&self, money: Money) -> Result<Money, String> {
+        let cost = self.cooling_cost();
+        if self.cooling >= COOLING_MAX {
+            Err(String::from("The machine's cooling can't get any better."))
+        } else if cost.checked_add(INVEST_RESERVE).is_none_or(|needed| needed > money) {
+            Err(format!("The next cooling level costs {}, and at least {} must be left.", cost,
+                INVEST_RESERVE))
+        } else {
+            Ok(cost)
+        }
+    }
+
+    /// # Researched
+    pub fn researched(&self, research: Research) -> bool {
+        self.research.contains(&research)
+    }
+
+    /// # Check Research
+    /// 
+    /// Checks the research is open, not done yet and with everything it needs done.
+    pub fn check_research(&self, research: Research) -> Result<(), String> {
+        let missing: Vec<&str> = research.project().requires.iter()
+            .filter(|needed| !self.researched(**needed)).map(|needed| needed.name()).collect();
+        if self.researched(research) {
+            Err(format!("{} is already researched.", research.name()))
+        } else if !missing.is_empty() {
+            Err(format!("{} needs {} first.", research.name(), missing.join(" and ")))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// # Save Section
+    /// 
+    /// Writes the machine to a [machine] save section.
+    pub fn save_section(&self) -> Section {
+        let mut section = Section::new("machine");
+        section.set("level", self.level);
+        for kind in Manipulation::ALL {
+            section.set(kind.save_key(), self.component_level(kind));
+        }
+        section.set("heat", self.heat);
+        section.set("cooling", self.cooling);
+        section.set("invested", self.invested.save_str());
+        section
+    }
+
+    /// # Load Section
+    /// 
+    /// Reads the machine from a section written by save_section.
+    pub fn load_section(section: &Section) -> Result<Self, SaveError> {
+        let mut machine = Self::new(section.parse_with("level", Self::level_from_save)?);
+        for kind in Manipulation::ALL {
+            machine.components[kind.index()] = section.parse_or(kind.save_key(), 0)?;
+        }
+        machine.heat = section.parse_or("heat", 0.0)?;
+        machine.cooling = section.parse_or("cooling", 0)?;
+        // Saves from before the investment was kept don't say what the levels cost, 
+        // so they sell back for nothing.
+        machine.invested = section.get("invested").map(|_| section.parse_money("invested"))
+            .transpose()?
+            .unwrap_or_default();
+        Ok(machine)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn investing_more_than_there_is_fails() {
+        let machine = Machine::new(0);
+        let money = Money::from_pounds(10);
+        assert!(machine.check_invest(money, money).is_err());
+        assert!(machine.check_invest(Money::from_farthings(i64::MAX), money).is_err());
+        assert!(machine.check_invest(money - INVEST_RESERVE, money).is_ok());
+    }
+
+    #[test]
+    fn investing_buys_whole_levels() {
+        let machine = Machine::new(3);
+        let cost = machine.upgrade_cost(4);
+        assert_eq!(machine.levels_for(cost), (4, cost));
+        let short = cost - Money::from_farthings(1);
+        assert_eq!(machine.levels_for(short), (3, machine.upgrade_cost(3)));
+        assert_eq!(Machine::new(MAX_LEVEL).levels_for(cost), (0, Money::ZERO));
+    }
+
+    #[test]
+    fn selling_pays_back_a_share_of_what_was_paid() {
+        let mut machine = Machine::new(0);
+        machine.invested = machine.upgrade_cost(10);
+        machine.level = 10;
+        assert_eq!(machine.cost_basis(10), machine.invested);
+        assert_eq!(machine.cost_basis(3), Machine::curve_cost(7, 10));
+        assert_eq!(machine.check_sell(10), Ok(machine.invested.mul_ratio(SELL_BACK_SHARE)));
+        assert!(machine.check_sell(0).is_err());
+        assert!(machine.check_sell(11).is_err());
+
+        let paid = machine.invested;
+        machine.drop_levels(3);
+        assert_eq!(machine.level, 7);
+        assert_eq!(machine.invested, paid - Machine::curve_cost(7, 10));
+        machine.drop_levels(20);
+        assert_eq!((machine.level, machine.invested), (0, Money::ZERO));
+    }
+
+    #[test]
+    fn granted_levels_thin_out_what_each_is_worth() {
+        let mut machine = Machine::new(10);
+        assert_eq!(machine.sell_value(10), Money::ZERO);
+        machine.invested = Machine::curve_cost(0, 10).mul_ratio(0.5);
+        let basis = machine.cost_basis(4);
+        let full = Machine::curve_cost(6, 10).mul_ratio(0.5);
+        assert!((basis.farthings() - full.farthings()).abs() <= 1);
+    }
+}
@@ -1,41 +1,241 @@
-use std::io::stdout;
+use std::io::{stdin, stdout};
 
 use crossterm::{style::Print, terminal, ExecutableCommand};
 
-use crate::common_state::{self, CommonState};
+use crate::{common_state::CommonState, machine::{machine::{BalanceRow, Machine, Manipulation, LEAK_THRESHOLD}, research::{Funding, RESEARCH_TREE}}, money::Money};
 
+/// # Machine Screen
+/// 
+/// Shows the Improbability Machine, and lets the player invest money in raising its
+/// entropy cap, or in its components and cooling, or sell levels back, until they
+/// return to game select.
 pub fn machine_screen(common_state: &mut CommonState) {
-    stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
     let mut msg = String::new();
     loop {
+        common_state.tick();
+        stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
         stdout().execute(Print("\tImprobability Machine Screen\n")).unwrap();
         stdout().execute(Print(
-            format!("Money: ${}\tEntropy: {} b\n\n", common_state.money, common_state.entropy)
+            format!("Money: {}\tEntropy: {} b\n\n", common_state.money, common_state.entropy)
         )).unwrap();
         stdout().execute(
-            Print("Commands: R -> Return to Game Select | Enter Number to increase Entropy Capacity\n")
+            Print("Commands: R -> Return to Game Select | Enter £sd to increase Entropy Capacity |
+            C <R/E/S> -> Upgrade the Reroll, Elimination, or Selection Component |
+            S # -> Sell # Levels back | H -> Upgrade Cooling | T -> Research | B -> Balance Report |
+            D -> Deliver to the Contract | M -> Entropy Market\n")
         ).unwrap();
         stdout().execute(
             Print(format!("Entropy Max: {}\tMachine Level: {}\n", 
             common_state.machine.entropy_cap(), 
             common_state.machine.level))
         ).unwrap();
+        stdout().execute(Print(format!("{}\n", common_state.machine.status(common_state.entropy))))
+            .unwrap();
+        stdout().execute(Print(format!("{}\tMet: {}\tMissed: {}\n",
+            common_state.machine.contracts.summary(common_state.game_time()),
+            common_state.machine.contracts.completed, common_state.machine.contracts.failed))).unwrap();
+        stdout().execute(Print(format!("Cooling: Level {}\tSheds: {:.2} heat/s\tNext Level: {}\n",
+            common_state.machine.cooling, common_state.machine.cooling_rate(),
+            common_state.machine.cooling_cost()))).unwrap();
+        stdout().execute(Print(format!("Regeneration: {:.3} b/s\tLeakage: {:.2}%/s above {:.0} b\n",
+            common_state.machine.regen_rate(), common_state.machine.leak_rate() * 100.0,
+            common_state.machine.entropy_cap() * LEAK_THRESHOLD))).unwrap();
+        stdout().execute(Print(format!("Tier: {}\tNext Tier: {}\n", common_state.machine.tier().name,
+            common_state.machine.next_tier().map_or(String::from("--"),
+                |tier| format!("{} at Level {}", tier.name, tier.first_level))))).unwrap();
+        stdout().execute(Print(format!("Next Level: {}\tNext 10 Levels: {}\tSell 1 Level: {}\n",
+            common_state.machine.upgrade_cost(1), common_state.machine.upgrade_cost(10),
+            common_state.machine.sell_value(1)))).unwrap();
+        for kind in Manipulation::ALL {
+            stdout().execute(Print(format!("{} Component: Level {}\tCost Share: {:.0}%\tNext Level: {}\n",
+                kind.name(), common_state.machine.component_level(kind),
+                common_state.machine.cost_share(kind) * 100.0,
+                common_state.machine.component_cost(kind)))).unwrap();
+        }
         stdout().execute(Print(
             format!("{}\n\n", msg)
         )).unwrap();
         let mut buff = String::new();
-        buff = buff.trim_end().to_string();
-        if let Ok(invest) = buff.parse::<f64>() {
-            if invest + 1.0 > common_state.money {
-                // Must have at least 1 dollar left to continue betting.
-                msg = String::from("Cannot invest that much, must have at least $1 left.");
+        stdin().read_line(&mut buff).unwrap();
+        buff = buff.trim().to_lowercase();
+        if buff == "r" {
+            return;
+        }
+        if buff == "h" {
+            msg = match common_state.upgrade_cooling() {
+                Ok(cost) => format!("Upgraded the cooling for {}.", cost),
+                Err(err) => err,
+            };
+            continue;
+        }
+        if buff == "d" {
+            msg = match common_state.deliver_contract() {
+                Ok(delivered) => common_state.machine.contracts.news.take()
+                    .map_or(delivered.clone(), |news| format!("{} {}", delivered, news)),
+                Err(err) => err,
+            };
+            continue;
+        }
+        if buff == "m" {
+            market_screen(common_state);
+            msg = String::new();
+            continue;
+        }
+        if buff == "t" {
+            research_screen(common_state);
+            msg = String::new();
+            continue;
+        }
+        if buff == "b" {
+            balance_report();
+            continue;
+        }
+        // Commands are whole words, so coin names like "sovereign" still invest.
+        let mut words = buff.split_whitespace();
+        let (command, arg) = (words.next().unwrap_or(""), words.next().unwrap_or(""));
+        if command == "s" {
+            msg = match arg.parse::<u64>() {
+                Ok(levels) => match common_state.sell_machine(levels) {
+                    Ok(refund) => format!("Sold {} levels back for {}.", levels, refund),
+                    Err(err) => err,
+                },
+                Err(_) => String::from("Give the number of levels to sell, like S 5."),
+            };
+            continue;
+        }
+        if command == "c" {
+            msg = match Manipulation::parse(arg) {
+                Some(kind) => match common_state.upgrade_component(kind) {
+                    Ok(cost) => format!("Upgraded the {} component for {}.", kind.name(), cost),
+                    Err(err) => err,
+                },
+                None => String::from("Pick a component: C R, C E, or C S."),
+            };
+            continue;
+        }
+        msg = match buff.parse::<Money>() {
+            Ok(invest) => {
+                let before = common_state.machine.level;
+                match common_state.upgrade_machine(invest) {
+                    Ok((levels, cost)) => match common_state.machine.unlocked_since(before) {
+                        Some(tier) => format!("Gained {} levels of entropy for {}. The machine is now a {}!",
+                            levels, cost, tier.name),
+                        None => format!("Gained {} levels of entropy for {}.", levels, cost),
+                    },
+                    Err(err) => err,
+                }
+            },
+            Err(err) => err,
+        };
+    }
+}
+
+/// # Balance Report
+/// 
+/// Prints every level of the upgrade curve, until the player presses enter.
+fn balance_report() {
+    stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
+    stdout().execute(Print(format!("\tMachine Balance Report\n{}\n", BalanceRow::HEADER))).unwrap();
+    for row in Machine::balance_report() {
+        stdout().execute(Print(format!("{}\n", row))).unwrap();
+    }
+    stdout().execute(Print("Press Enter to return.\n")).unwrap();
+    let mut buff = String::new();
+    stdin().read_line(&mut buff).unwrap();
+}
+
+/// # Research Screen
+/// 
+/// Shows the research tree, and lets the player fund research with money or entropy
+/// until they return to the machine.
+fn research_screen(common_state: &mut CommonState) {
+    let mut msg = String::new();
+    loop {
+        stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
+        stdout().execute(Print("\tMachine Research\n")).unwrap();
+        stdout().execute(Print(
+            format!("Money: {}\tEntropy: {} b\n\n", common_state.money, common_state.entropy)
+        )).unwrap();
+        stdout().execute(
+            Print("Commands: R -> Return to the Machine | # M -> Fund # with Money | # E -> Fund # with Entropy\n")
+        ).unwrap();
+        for (i, project) in RESEARCH_TREE.iter().enumerate() {
+            let status = if common_state.machine.researched(project.research) {
+                String::from("Done")
             } else {
-                let upgrade = (invest / 2.0).floor();
-                let expense = upgrade * 2.0;
-                msg = String::from(format!("Gained {} levels of entropy.", upgrade));
-                common_state.money -= expense;
-                common_state.machine.level += upgrade;
-            }
+                common_state.machine.check_research(project.research)
+                    .map_or_else(|_| String::from("Locked"), |_| String::from("Open"))
+            };
+            stdout().execute(Print(format!("({}) [{}] {}\n", i + 1, status, project))).unwrap();
+        }
+        stdout().execute(Print(format!("{}\n\n", msg))).unwrap();
+        let mut buff = String::new();
+        stdin().read_line(&mut buff).unwrap();
+        buff = buff.trim().to_lowercase();
+        if buff == "r" {
+            return;
+        }
+        let mut parts = buff.split_whitespace();
+        let project = parts.next().and_then(|n| n.parse::<usize>().ok())
+            .and_then(|n| n.checked_sub(1)).and_then(|i| RESEARCH_TREE.get(i));
+        let funding = match parts.next() {
+            Some("m") => Some(Funding::Money),
+            Some("e") => Some(Funding::Entropy),
+            _ => None,
+        };
+        msg = match (project, funding) {
+            (Some(project), Some(funding)) => match common_state.fund_research(project.research, funding) {
+                Ok(()) => format!("Researched {}.", project.name),
+                Err(err) => err,
+            },
+            _ => String::from("Pick a project and how to fund it, like 1 M or 1 E."),
+        };
+    }
+}
+
+/// # Market Screen
+/// 
+/// Shows the entropy market, and lets the player sell entropy for money or buy it
+/// back until they return to the machine.
+fn market_screen(common_state: &mut CommonState) {
+    let mut msg = String::new();
+    loop {
+        common_state.tick();
+        stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
+        stdout().execute(Print("\tEntropy Market\n")).unwrap();
+        stdout().execute(Print(
+            format!("Money: {}\tEntropy: {} b\n\n", common_state.money, common_state.entropy)
+        )).unwrap();
+        stdout().execute(
+            Print("Commands: R -> Return to the Machine | S # -> Sell # Bits | B # -> Buy # Bits\n")
+        ).unwrap();
+        stdout().execute(Print(format!("{}\n", common_state.market.summary(common_state.game_time()))))
+            .unwrap();
+        stdout().execute(Print(format!("{}\n\n", msg))).unwrap();
+        let mut buff = String::new();
+        stdin().read_line(&mut buff).unwrap();
+        buff = buff.trim().to_lowercase();
+        if buff == "r" {
+            return;
         }
+        let (selling, bits) = match buff.split_at_checked(1) {
+            Some(("s", bits)) => (true, bits.trim().parse::<f64>()),
+            Some(("b", bits)) => (false, bits.trim().parse::<f64>()),
+            _ => {
+                msg = String::from("Sell or buy a number of bits, like S 10 or B 10.");
+                continue;
+            },
+        };
+        msg = match (selling, bits) {
+            (true, Ok(bits)) => match common_state.sell_entropy(bits) {
+                Ok(value) => format!("Sold {:.2} b for {}.", bits, value),
+                Err(err) => err,
+            },
+            (false, Ok(bits)) => match common_state.buy_entropy(bits) {
+                Ok(cost) => format!("Bought {:.2} b for {}.", bits, cost),
+                Err(err) => err,
+            },
+            (_, Err(_)) => String::from("Give the number of bits, like S 10 or B 10."),
+        };
     }
 }
\ No newline at end of file
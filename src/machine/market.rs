@@ -0,0 +1,337 @@
+use std::{fmt, time::Duration};
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::{money::Money, save::{SaveError, Section}};
+
+/// What a bit of entropy is worth on a calm market nobody has traded on.
+pub const MARKET_BASE_PRICE: Money = Money::from_pence(6);
+/// The share taken off the price for selling, and put on for buying.
+pub const MARKET_SPREAD: f64 = 0.15;
+/// How many bits of the player's net trading it takes to move the price by a
+/// factor of e. Selling pushes it down, buying pushes it up.
+pub const MARKET_DEPTH: f64 = 200.0;
+/// How long the market takes to forget half of the player's trading, in game time.
+pub const MARKET_MEMORY: Duration = Duration::from_secs(10 * 60);
+/// How long each market event lasts, in game time.
+pub const MARKET_EVENT_TIME: Duration = Duration::from_secs(5 * 60);
+/// How long a market day is, in game time. The trading limits reset each day.
+pub const MARKET_DAY: Duration = Duration::from_secs(60 * 60);
+/// The most bits that can be sold in a market day.
+pub const DAILY_SELL_LIMIT: f64 = 200.0;
+/// The most bits that can be bought in a market day.
+pub const DAILY_BUY_LIMIT: f64 = 100.0;
+
+/// # Market Event
+/// 
+/// What's happening on the entropy market, which moves the price for a while.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarketEvent {
+    Calm,
+    /// Too much entropy about, it's cheap.
+    Glut,
+    /// Entropy is scarce, it's dear.
+    Shortage,
+    /// Speculators are buying up everything.
+    Boom,
+    /// The bottom has fallen out of the market.
+    Crash,
+}
+
+impl MarketEvent {
+    /// # Roll
+    /// 
+    /// The event for the given period of the market, drawn from its seed so it's the
+    /// same however and whenever the game gets there.
+    pub fn roll(seed: u64, period: u64) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed ^ period);
+        match rng.random_range(0..20) {
+            0..10 => MarketEvent::Calm,
+            10..14 => MarketEvent::Glut,
+            14..18 => MarketEvent::Shortage,
+            18 => MarketEvent::Boom,
+            _ => MarketEvent::Crash,
+        }
+    }
+
+    /// # Factor
+    /// 
+    /// How many times the base price entropy goes for during the event.
+    pub fn factor(&self) -> f64 {
+        match self {
+            MarketEvent::Calm => 1.0,
+            MarketEvent::Glut => 0.7,
+            MarketEvent::Shortage => 1.4,
+            MarketEvent::Boom => 2.0,
+            MarketEvent::Crash => 0.4,
+        }
+    }
+}
+
+impl fmt::Display for MarketEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MarketEvent::Calm => write!(f, "Calm"),
+            MarketEvent::Glut => write!(f, "Entropy glut"),
+            MarketEvent::Shortage => write!(f, "Entropy shortage"),
+            MarketEvent::Boom => write!(f, "Speculative boom"),
+            MarketEvent::Crash => write!(f, "Market crash"),
+        }
+    }
+}
+
+/// # Market
+/// 
+/// The entropy market, where bits are sold for money and bought back, at a price
+/// that moves with the market's events and the player's own trading. Trades are
+/// limited each market day.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Market {
+    /// Seeds the market's events.
+    pub seed: u64,
+    /// The player's net trading in bits sold, as of pressure_at. It fades as the
+    /// market forgets it.
+    pub pressure: f64,
+    /// The game time the pressure was last faded to.
+    pub pressure_at: Duration,
+    /// The market day the limits are counted for.
+    pub day: u64,
+    pub sold_today: f64,
+    pub bought_today: f64,
+}
+
+impl Market {
+    pub fn new(seed: u64) -> Self {
+        Self { seed, ..Self::default() }
+    }
+
+    /// # Event
+    /// 
+    /// What's happening on the market at the given game time.
+    pub fn event(&self, now: Duration) -> MarketEvent {
+        MarketEvent::roll(self.seed, (now.as_secs_f64() / MARKET_EVENT_TIME.as_secs_f64()) as u64)
+    }
+
+    /// # Pressure Now
+    /// 
+    /// The player's net trading, faded to the given game time.
+    pub fn pressure_now(&self, now: Duration) -> f64 {
+        let since = now.saturating_sub(self.pressure_at).as_secs_f64();
+        self.pressure * 0.5f64.powf(since / MARKET_MEMORY.as_secs_f64())
+    }
+
+    /// # Price
+    /// 
+    /// What a bit is worth at the given game time, before the spread. Never less than
+    /// a farthing.
+    pub fn price(&self, now: Duration) -> Money {
+        let ratio = self.event(now).factor() * (-self.pressure_now(now) / MARKET_DEPTH).exp();
+        MARKET_BASE_PRICE.mul_ratio(ratio).max(Money::from_farthings(1))
+    }
+
+    /// # Sell Price
+    /// 
+    /// What the market pays for a bit.
+    pub fn sell_price(&self, now: Duration) -> Money {
+        self.price(now).mul_ratio(1.0 - MARKET_SPREAD)
+    }
+
+    /// # Buy Price
+    /// 
+    /// What the market charges for a bit.
+    pub fn buy_price(&self, now: Duration) -> Money {
+        self.price(now).mul_ratio(1.0 + MARKET_SPREAD)
+    }
+
+    /// What the given bits are worth before the spread, traded at the given game time.
+    /// Bits sold are positive, bought negative. The price moves as the trade goes 
+    /// through, so it's worth the average along the way, and a trade can't be undone
+    /// for a profit.
+    fn trade_value(&self, bits: f64, now: Duration) -> Money {
+        let pressure = self.pressure_now(now);
+        let moved = MARKET_DEPTH * ((-pressure / MARKET_DEPTH).exp()
+            - (-(pressure + bits) / MARKET_DEPTH).exp()) / bits;
+        MARKET_BASE_PRICE.mul_ratio(self.event(now).factor() * moved * bits.abs())
+    }
+
+    /// The market day of the given game time.
+    fn day_of(now: Duration) -> u64 {
+        (now.as_secs_f64() / MARKET_DAY.as_secs_f64()) as u64
+    }
+
+    /// # Sell Left
+    /// 
+    /// How many more bits can be sold today.
+    pub fn sell_left(&self, now: Duration) -> f64 {
+        if Self::day_of(now) != self.day {
+            DAILY_SELL_LIMIT
+        } else {
+            (DAILY_SELL_LIMIT - self.sold_today).max(0.0)
+        }
+    }
+
+    /// # Buy Left
+    /// 
+    /// How many more bits can be bought today.
+    pub fn buy_left(&self, now: Duration) -> f64 {
+        if Self::day_of(now) != self.day {
+            DAILY_BUY_LIMIT
+        } else {
+            (DAILY_BUY_LIMIT - self.bought_today).max(0.0)
+        }
+    }
+
+    /// # Check Sell
+    /// 
+    /// Checks the bits can be sold, out of the entropy available, and returns what
+    /// they'd fetch. Big sales push the price down as they go.
+    pub fn check_sell(&self, bits: f64, entropy: f64, now: Duration) -> Result<Money, String> {
+        if bits.is_nan() || bits <= 0.0 {
+            return Err(String::from("Give the number of bits to sell."));
+        }
+        if bits > entropy {
+            return Err(format!("Only {:.2} b to sell.", entropy));
+        }
+        if bits > self.sell_left(now) {
+            return Err(format!("The market will only take {:.2} b more today.",
+                self.sell_left(now)));
+        }
+        Ok(self.trade_value(bits, now).mul_ratio(1.0 - MARKET_SPREAD))
+    }
+
+    /// # Check Buy
+    /// 
+    /// Checks the bits can be bought with the money available, keeping back the
+    /// given reserve, and returns what they'd cost. Big purchases push the price up as
+    /// they go.
+    pub fn check_buy(&self, bits: f64, money: Money, reserve: Money, now: Duration)
+    -> Result<Money, String> {
+        if bits.is_nan() || bits <= 0.0 {
+            return Err(String::from("Give the number of bits to buy."));
+        }
+        if bits > self.buy_left(now) {
+            return Err(format!("The market will only sell {:.2} b more today.",
+                self.buy_left(now)));
+        }
+        let cost = self.trade_value(-bits, now).mul_ratio(1.0 + MARKET_SPREAD);
+        if cost.is_zero() {
+            return Err(format!("Too little to buy, a bit costs {}.", self.buy_price(now)));
+        }
+        if cost.checked_add(reserve).is_none_or(|needed| needed > money) {
+            return Err(format!("{:.2} b costs {}, and at least {} must be left.", bits, cost,
+                reserve));
+        }
+        Ok(cost)
+    }
+
+    /// # Trade
+    /// 
+    /// Records a trade made at the given game time, counting it against the day's
+    /// limits and letting it move the price. Bits sold are positive, bought negative.
+    pub fn trade(&mut self, bits: f64, now: Duration) {
+        self.pressure = self.pressure_now(now) + bits;
+        self.pressure_at = now;
+        if Self::day_of(now) != self.day {
+            self.day = Self::day_of(now);
+            self.sold_today = 0.0;
+            self.bought_today = 0.0;
+        }
+        if bits > 0.0 {
+            self.sold_today += bits;
+        } else {
+            self.bought_today -= bits;
+        }
+    }
+
+    /// # Summary
+    /// 
+    /// The market's event, prices and what's left to trade today, for the screens to
+    /// show.
+    pub fn summary(&self, now: Duration) -> String {
+        format!("Market: {}  Sell: {} per b  Buy: {} per b  Left Today: {:.0} b to sell, {:.0} b to buy",
+            self.event(now), self.sell_price(now), self.buy_price(now), self.sell_left(now),
+            self.buy_left(now))
+    }
+
+    /// # Save Section
+    /// 
+    /// Writes the market to a [market] save section.
+    pub fn save_section(&self) -> Section {
+        let mut section = Section::new("market");
+        section.set("seed", self.seed);
+        section.set("pressure", self.pressure);
+        section.set("pressure_at", self.pressure_at.as_secs_f64());
+        section.set("day", self.day);
+        section.set("sold_today", self.sold_today);
+        section.set("bought_today", self.bought_today);
+        section
+    }
+
+    /// # Load Section
+    /// 
+    /// Reads the market from a section written by save_section.
+    pub fn load_section(section: &Section) -> Result<Self, SaveError> {
+        Ok(Self {
+            seed: section.parse("seed")?,
+            pressure: section.parse("pressure")?,
+            pressure_at: section.parse_secs("pressure_at")?,
+            day: section.parse("day")?,
+            sold_today: section.parse("sold_today")?,
+            bought_today: section.parse("bought_today")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The start of the first market period with the given event.
+    fn event_time(market: &Market, event: MarketEvent) -> Duration {
+        (0..).map(|period| MARKET_EVENT_TIME * period)
+            .find(|&now| market.event(now) == event)
+            .unwrap()
+    }
+
+    #[test]
+    fn selling_and_buying_back_loses_money() {
+        let mut market = Market::new(1);
+        let now = event_time(&market, MarketEvent::Calm);
+        let sold = market.check_sell(100.0, 100.0, now).unwrap();
+        market.trade(100.0, now);
+        assert!(market.price(now) < MARKET_BASE_PRICE);
+        let bought = market.check_buy(100.0, Money::from_pounds(100), Money::ZERO, now).unwrap();
+        assert!(bought > sold);
+        assert_eq!(market.trade_value(-100.0, now), Market::new(1).trade_value(100.0, now));
+    }
+
+    #[test]
+    fn big_trades_move_the_price_as_they_go() {
+        let market = Market::new(1);
+        let now = event_time(&market, MarketEvent::Calm);
+        let one = market.trade_value(1.0, now).pence();
+        assert!((one - MARKET_BASE_PRICE.pence()).abs() <= 0.25);
+        let hundred = market.trade_value(100.0, now).pence();
+        assert!(hundred < one * 100.0);
+        assert!(market.trade_value(-100.0, now).pence() > one * 100.0);
+    }
+
+    #[test]
+    fn events_scale_the_value() {
+        let market = Market::new(1);
+        let calm = market.trade_value(50.0, event_time(&market, MarketEvent::Calm));
+        for event in [MarketEvent::Glut, MarketEvent::Shortage, MarketEvent::Boom,
+            MarketEvent::Crash] {
+            let value = market.trade_value(50.0, event_time(&market, event));
+            assert!((value.ratio(calm) - event.factor()).abs() < 0.01, "{}", event);
+        }
+    }
+
+    #[test]
+    fn huge_buys_are_refused_without_overflowing() {
+        let market = Market::new(1);
+        let money = Money::from_farthings(i64::MAX);
+        assert!(market.check_buy(1.0, money, money, Duration::ZERO).is_err());
+    }
+}
\ No newline at end of file
@@ -1,2 +1,6 @@
+pub mod contract;
+#[allow(clippy::module_inception)]
 pub mod machine;
 pub mod machine_cmd;
+pub mod market;
+pub mod research;
\ No newline at end of file
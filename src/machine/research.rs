@@ -0,0 +1,156 @@
+use std::fmt;
+
+use crate::{money::Money, save::{SaveError, Section}};
+
+/// How much cheaper Tuned Resonators make every manipulation, as the share left.
+pub const RESONATOR_SHARE: f64 = 0.9;
+/// How much faster Heat Exchangers let the machine cool.
+pub const EXCHANGER_BOOST: f64 = 1.5;
+
+/// # Research
+/// 
+/// Everything the machine can research. Manipulations add to what games' menus
+/// offer, boosts improve the machine, and games open new tables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Research {
+    /// See the next outcome before it's drawn.
+    Peek,
+    /// Weight a coin toward heads.
+    Bias,
+    /// Hold off observing the coin, giving the bet more time.
+    DelayedObservation,
+    /// Get part of the stake back from a lost coin toss.
+    OutcomeInsurance,
+    /// Every manipulation costs less entropy.
+    TunedResonators,
+    /// The machine sheds heat faster.
+    HeatExchangers,
+    BingoHall,
+    Baccarat,
+    MoneyWheel,
+    Sportsbook,
+}
+
+impl Research {
+    /// The games that need researching, which saves from before research already had.
+    pub const GAMES: [Research; 4] = [Research::BingoHall, Research::Baccarat,
+        Research::MoneyWheel, Research::Sportsbook];
+
+    /// # Project
+    /// 
+    /// The research's place in the tree.
+    pub fn project(&self) -> &'static ResearchProject {
+        RESEARCH_TREE.iter().find(|project| project.research == *self)
+            .expect("Every research is in the tree.")
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.project().name
+    }
+
+    /// Where the research goes in the save.
+    fn save_key(&self) -> &'static str {
+        match self {
+            Research::Peek => "peek",
+            Research::Bias => "bias",
+            Research::DelayedObservation => "delayed_observation",
+            Research::OutcomeInsurance => "outcome_insurance",
+            Research::TunedResonators => "tuned_resonators",
+            Research::HeatExchangers => "heat_exchangers",
+            Research::BingoHall => "bingo_hall",
+            Research::Baccarat => "baccarat",
+            Research::MoneyWheel => "money_wheel",
+            Research::Sportsbook => "sportsbook",
+        }
+    }
+
+    fn load_key(key: &str) -> Option<Self> {
+        RESEARCH_TREE.iter().map(|project| project.research).find(|r| r.save_key() == key)
+    }
+}
+
+/// # Funding
+/// 
+/// What research is paid for with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Funding {
+    Money,
+    Entropy,
+}
+
+/// # Research Project
+/// 
+/// A node of the research tree, what it costs to fund with either money or entropy,
+/// and what has to be researched before it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResearchProject {
+    pub research: Research,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub money: Money,
+    pub entropy: f64,
+    pub requires: &'static [Research],
+}
+
+impl fmt::Display for ResearchProject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} ({} or {} b)", self.name, self.description, self.money, self.entropy)
+    }
+}
+
+/// # Research Tree
+/// 
+/// Every research project, in the order they're shown.
+pub const RESEARCH_TREE: [ResearchProject; 10] = [
+    ResearchProject { research: Research::Peek, name: "Peek",
+        description: "See the next coin flip or Hi-Lo card before it's drawn.",
+        money: Money::from_shillings(10), entropy: 20.0, requires: &[] },
+    ResearchProject { research: Research::Bias, name: "Bias",
+        description: "Weight a coin toward heads for the rest of the bet.",
+        money: Money::from_pounds(1), entropy: 40.0, requires: &[Research::Peek] },
+    ResearchProject { research: Research::DelayedObservation, name: "Delayed Observation",
+        description: "Hold off observing a coin, adding time to the bet.",
+        money: Money::from_pounds(1), entropy: 40.0, requires: &[Research::Peek] },
+    ResearchProject { research: Research::OutcomeInsurance, name: "Outcome Insurance",
+        description: "Get half the stake back from a lost coin toss.",
+        money: Money::from_pounds(2), entropy: 80.0, requires: &[Research::Bias] },
+    ResearchProject { research: Research::TunedResonators, name: "Tuned Resonators",
+        description: "Every manipulation costs 10% less entropy.",
+        money: Money::from_pounds(3), entropy: 120.0,
+        requires: &[Research::Bias, Research::DelayedObservation] },
+    ResearchProject { research: Research::HeatExchangers, name: "Heat Exchangers",
+        description: "The machine sheds heat 50% faster.",
+        money: Money::from_pounds(2), entropy: 80.0, requires: &[] },
+    ResearchProject { research: Research::BingoHall, name: "Bingo Hall",
+        description: "Open the Bingo Hall.",
+        money: Money::from_shillings(5), entropy: 10.0, requires: &[] },
+    ResearchProject { research: Research::Baccarat, name: "Baccarat",
+        description: "Open the Baccarat table.",
+        money: Money::from_shillings(10), entropy: 20.0, requires: &[Research::BingoHall] },
+    ResearchProject { research: Research::MoneyWheel, name: "Money Wheel",
+        description: "Open the Money Wheel.",
+        money: Money::from_pounds(1), entropy: 40.0, requires: &[Research::Baccarat] },
+    ResearchProject { research: Research::Sportsbook, name: "Sportsbook",
+        description: "Open the Sportsbook.",
+        money: Money::from_pounds(3), entropy: 120.0, requires: &[Research::MoneyWheel] },
+];
+
+/// # Save Research
+/// 
+/// Writes what's been researched to a [research] save section.
+pub fn save_research(research: &[Research]) -> Section {
+    let mut section = Section::new("research");
+    for done in research {
+        section.set("unlocked", done.save_key());
+    }
+    section
+}
+
+/// # Load Research
+/// 
+/// Reads what's been researched from a section written by save_research.
+pub fn load_research(section: &Section) -> Result<Vec<Research>, SaveError> {
+    section.get_all("unlocked")
+        .map(|key| Research::load_key(key).ok_or_else(|| section.bad_value("unlocked", key)))
+        .collect()
+}
\ No newline at end of file
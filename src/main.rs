@@ -1,18 +1,29 @@
 pub(crate) mod money;
 pub mod game;
+pub mod ledger;
 pub mod common_state;
 pub mod coin_game;
+pub mod keno_game;
+pub mod hilo_game;
+pub mod sports_game;
+pub mod baccarat_game;
+pub mod wheel_game;
+pub mod cards;
 pub mod gfx;
 pub mod machine;
 pub mod main_menu;
+pub mod save;
+pub mod save_slots;
+pub mod autosave;
+pub mod signing;
+pub mod export;
 
 use std::{env, time::{Duration, Instant}};
 
-use macroquad::prelude::*;
-use ::rand as stdrng;
-
+use macroquad::{prelude::*, ui::root_ui};
+use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
 use crate::{
-    coin_game::{coin_toss::CoinToss, coin_toss_cmd::select_screen}, common_state::{ButtonAction, CommonState}, gfx::coin::Coin, machine::machine::Machine, main_menu::main_menu};
+    autosave::Autosave, common_state::{ButtonAction, CommonState, GameState, DEFAULT_HARVEST_FRACTION}, gfx::{coin::Coin, machine::MachineScreen, slots::SlotsScreen}, save_slots::DEFAULT_SAVE_BACKUPS, ledger::Ledger, machine::{machine::Machine, market::Market}, main_menu::main_menu, money::Money};
 
 #[macroquad::main("Improbability Machine")]
 async fn main() {
@@ -20,18 +31,29 @@ async fn main() {
     let mode = &args[1];
     let start_time = Instant::now();
     let mut common_state = CommonState { 
-        money: 20.0*12.0, 
+        money: Money::from_pounds(1), 
         entropy: 100.0, 
         active_game: 0, 
-        current_bet: 10.0, 
+        current_bet: Money::from_pence(10), 
         button_clicked: ButtonAction::None,
-        machine: Machine::new(0.0) ,
+        machine: Machine::new(0) ,
+        machine_run_to: Duration::ZERO,
+        unlogged_run: (0.0, 0.0),
+        harvest_fraction: DEFAULT_HARVEST_FRACTION,
+        market: Market::new(::rand::random()),
         player_name: String::new(),
         last_prior_save: Instant::now(),
-        game_length: Duration::ZERO
+        game_length: Duration::ZERO,
+        league: None,
+        ledger: Ledger::default(),
+        escrow: Money::ZERO,
+        game_data: vec![],
+        save_slot: String::new(),
+        save_backups: DEFAULT_SAVE_BACKUPS,
+        tainted: false,
+        autosave: Autosave::new(),
+        rng: ChaCha8Rng::from_os_rng(),
     };
-    let mut coin_toss = CoinToss::new();
-    let mut rng = stdrng::rng();
 
     if mode == "cmd" {
         println!("\n\n\n\n\n\n\n\n");
@@ -40,45 +62,96 @@ async fn main() {
 
         main_menu(&mut common_state);
     } else if mode == "ui" {
-        let mut change = 0.0;
+        let change = 0.0;
 
         // load coin textures
         let coin = Coin::load_coin().await;
         build_textures_atlas();
 
-        let i = 0;
+        let mut game_state = GameState::MainMenu;
+        let mut slots = SlotsScreen::new();
+        let mut machine = MachineScreen::new();
+        let mut autosave_msg = String::new();
+        let mut contract_msg = String::new();
+
         loop {
             clear_background(DARKGRAY);
-            //draw_line(40.0, 40.0, 100.0, 200.0, 15.0, BLUE);
-            //draw_rectangle(screen_width() / 2.0 - 60.0, 100.0, 120.0, 60.0, GREEN);
-
-            let hi_point = Point {x: 20.0, y: 40.0};
-            let intro_dim: TextDimensions = draw_text("Hello, Macroquad!", 
-                hi_point.x, hi_point.y, 30.0, LIGHTGRAY);
-
-            draw_rectangle_lines(hi_point.x, hi_point.y-20.0, intro_dim.width, 
-                intro_dim.height+change, 4.0, BLACK);
-
-            // if change > 20.0 {
-            //     change = 0.0;
-            // } else {
-            //     change += 1.0;
-            // }
+            match game_state {
+                GameState::MainMenu | GameState::GameScreen => {
+                    //draw_line(40.0, 40.0, 100.0, 200.0, 15.0, BLUE);
+                    //draw_rectangle(screen_width() / 2.0 - 60.0, 100.0, 120.0, 60.0, GREEN);
+
+                    let hi_point = Point {x: 20.0, y: 40.0};
+                    let intro_dim: TextDimensions = draw_text("Hello, Macroquad!", 
+                        hi_point.x, hi_point.y, 30.0, LIGHTGRAY);
+
+                    draw_rectangle_lines(hi_point.x, hi_point.y-20.0, intro_dim.width, 
+                        intro_dim.height+change, 4.0, BLACK);
+
+                    draw_text(&format!("Money: {}  Entropy: {} b", common_state.money, 
+                        common_state.entropy), hi_point.x, hi_point.y + 40.0, 24.0, LIGHTGRAY);
+
+                    // if change > 20.0 {
+                    //     change = 0.0;
+                    // } else {
+                    //     change += 1.0;
+                    // }
             
-            let side = (Instant::now() - start_time).as_secs() % 2 == 1;
-
-            draw_texture(coin.flip_coin(start_time, Some(side)), screen_height() * 0.3, 
-                screen_width() * 0.3, WHITE);
-
-            let mouse = mouse_position();
-            draw_circle(mouse.0, mouse.1, 5.0, YELLOW);
+                    let side = (Instant::now() - start_time).as_secs() % 2 == 1;
+
+                    draw_texture(coin.flip_coin(start_time, Some(side)), screen_height() * 0.3, 
+                        screen_width() * 0.3, WHITE);
+
+                    let mouse = mouse_position();
+                    draw_circle(mouse.0, mouse.1, 5.0, YELLOW);
+
+                    if is_mouse_button_down(MouseButton::Left) {
+                        draw_circle(mouse.0, mouse.1, 2.0, GREEN);
+                    }
+                    if is_mouse_button_down(MouseButton::Right) {
+                        draw_circle(mouse.0, mouse.1, 2.0, BLUE);
+                    }
+
+                    if root_ui().button(vec2(20.0, screen_height() - 40.0), "Save Game") {
+                        slots.refresh();
+                        game_state = GameState::SaveScreen;
+                    }
+                    if root_ui().button(vec2(120.0, screen_height() - 40.0), "Load Game") {
+                        slots.refresh();
+                        game_state = GameState::LoadScreen;
+                    }
+                    if root_ui().button(vec2(220.0, screen_height() - 40.0), "Machine") {
+                        machine.msg.clear();
+                        game_state = GameState::MachineScreen;
+                    }
+                },
+                GameState::SaveScreen => {
+                    if let Some(next) = slots.draw(&mut common_state, true) {
+                        game_state = next;
+                    }
+                },
+                GameState::LoadScreen => {
+                    if let Some(next) = slots.draw(&mut common_state, false) {
+                        game_state = next;
+                    }
+                },
+                GameState::MachineScreen => {
+                    if let Some(next) = machine.draw(&mut common_state) {
+                        game_state = next;
+                    }
+                },
+            }
 
-            if is_mouse_button_down(MouseButton::Left) {
-                draw_circle(mouse.0, mouse.1, 2.0, GREEN);
+            common_state.tick();
+            if let Some(news) = common_state.machine.contracts.news.take() {
+                contract_msg = news;
             }
-            if is_mouse_button_down(MouseButton::Right) {
-                draw_circle(mouse.0, mouse.1, 2.0, BLUE);
+            draw_text(&contract_msg, 20.0, screen_height() - 80.0, 20.0, LIGHTGRAY);
+            // Autosaves are written off the frame loop, this only starts and checks them.
+            if let Some(report) = common_state.autosave_poll() {
+                autosave_msg = report;
             }
+            draw_text(&autosave_msg, 20.0, screen_height() - 60.0, 20.0, LIGHTGRAY);
 
             next_frame().await
         }
@@ -1,31 +1,19 @@
-use std::{fs::{self, File}, io::{stdin, stdout, Read, Write}, path::Path, time::{Duration, Instant}};
+use std::{io::{stdin, stdout}, time::{Duration, Instant}};
 
 use crossterm::{style::Print, terminal, ExecutableCommand};
-use ::rand as stdrng;
+use rand_chacha::ChaCha8Rng;
 
-use crate::{coin_game::{coin_toss::CoinToss, coin_toss_cmd}, common_state::{ButtonAction, CommonState}, machine::machine::Machine};
-
-static SAVE_PATH: &str = "./saves/save.txt";
+use crate::{autosave::AutosaveEvent, baccarat_game::{baccarat::Baccarat, baccarat_cmd}, coin_game::{coin_toss::CoinToss, coin_toss_cmd}, common_state::CommonState, export::{export_state, import_state, EXPORT_PREFIX}, game::GameCommonData, hilo_game::{hilo::HiLo, hilo_cmd}, keno_game::{bingo::BingoHall, bingo_cmd, keno::Keno, keno_cmd}, machine::{machine_cmd::machine_screen, research::Research}, save::SaveError, save_slots::{check_slot_name, copy_slot, delete_slot, list_backups, list_slots, read_slot, read_slot_tainted, rename_slot, restore_backup, write_slot, SlotInfo, SAVE_BACKUPS_MAX}, sports_game::sports_cmd, wheel_game::{wheel::MoneyWheel, wheel_cmd}};
 
 pub fn main_menu(common_state: &mut CommonState) {
     let mut msg = String::new();
-    let mut common_state = CommonState {
-        money: 100.0,
-        entropy: 100.0,
-        active_game: 0,
-        current_bet: 1.0,
-        button_clicked: ButtonAction::None,
-        machine: Machine::new(0.0),
-        player_name: String::from(""),
-        last_prior_save: Instant::now(),
-        game_length: Duration::ZERO
-    };
     loop {
         stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
         stdout().execute(Print("!!!!!!!!!! Improbability Machine !!!!!!!!!!\n\n")).unwrap();
         stdout().execute(Print("Commands:\n")).unwrap();
-        stdout().execute(Print("(N)ew Game (Overwrite's old save)\n")).unwrap();
-        stdout().execute(Print("(L)oad Save\n")).unwrap();
+        stdout().execute(Print("(N)ew Game\n")).unwrap();
+        stdout().execute(Print("(L)oad and Manage Saves\n")).unwrap();
+        stdout().execute(Print("(I)mport a Shared Game\n")).unwrap();
         stdout().execute(Print("(Q)uit\n\n")).unwrap();
         stdout().execute(Print(format!("{}\n\n", msg))).unwrap();
         let mut buff = String::new();
@@ -34,80 +22,412 @@ pub fn main_menu(common_state: &mut CommonState) {
         if buff.to_lowercase() == "q" {
             break;
         } else if buff.to_lowercase() == "n" {
-            // create new data and save over old.
-            stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
-            stdout().execute(Print("Please input your name:\n")).unwrap();
-            let mut buff = String::new();
-            stdin().read_line(&mut buff).unwrap();
-            common_state = new_save_file(buff);
-            common_state.last_prior_save = Instant::now();
-            game_menu(&mut common_state);
+            match new_game() {
+                Ok(Some(new_state)) => {
+                    *common_state = new_state;
+                    common_state.last_prior_save = Instant::now();
+                    game_menu(common_state);
+                },
+                Ok(None) => msg = String::from("New game cancelled."),
+                Err(err) => msg = err.to_string(),
+            }
+        } else if buff.to_lowercase() == "i" {
+            match import_game() {
+                Ok(Some(imported)) => {
+                    *common_state = imported;
+                    game_menu(common_state);
+                },
+                Ok(None) => msg = String::from("Import cancelled."),
+                Err(err) => msg = err.to_string(),
+            }
         } else if buff.to_lowercase() == "l" {
-            stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
-            stdout().execute(Print("----- Loading Save -----\n")).unwrap();
-            common_state = load_save();
-            common_state.last_prior_save = Instant::now();
-            game_menu(&mut common_state);
+            if slots_screen(common_state) {
+                game_menu(common_state);
+            }
         } else {
             msg = String::from("Command not recognized.");
         }
     }
 }
 
+/// # New Game
+/// 
+/// Asks for the player's name and a slot to save to, then saves the new game there.
+/// 
+/// Returns None if the slot is taken and the player won't overwrite it.
+fn new_game() -> Result<Option<CommonState>, SaveError> {
+    stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
+    stdout().execute(Print("Please input your name:\n")).unwrap();
+    let mut name = String::new();
+    stdin().read_line(&mut name).unwrap();
+    stdout().execute(Print("Name the save slot (letters, numbers, - and _):\n")).unwrap();
+    let mut slot = String::new();
+    stdin().read_line(&mut slot).unwrap();
+    let slot = slot.trim();
+    check_slot_name(slot)?;
+    let mut new_state = CommonState::new(name.trim().to_string());
+    Ok(ask_overwrite(|overwrite| write_slot(slot, &mut new_state, overwrite))?
+        .map(|_| new_state))
+}
+
+/// # Import Game
+/// 
+/// Asks for an exported game and a slot to save it to, then saves it there.
+/// 
+/// Returns None if the slot is taken and the player won't overwrite it.
+fn import_game() -> Result<Option<CommonState>, SaveError> {
+    stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
+    stdout().execute(Print(format!("Paste the exported game (starts with {}):\n", EXPORT_PREFIX))).unwrap();
+    let mut blob = String::new();
+    stdin().read_line(&mut blob).unwrap();
+    let mut imported = import_state(&blob)?;
+    stdout().execute(Print(format!("Imported {}'s game. Name the save slot (letters, numbers, - and _):\n",
+        imported.player_name))).unwrap();
+    let mut slot = String::new();
+    stdin().read_line(&mut slot).unwrap();
+    let slot = slot.trim();
+    check_slot_name(slot)?;
+    Ok(ask_overwrite(|overwrite| write_slot(slot, &mut imported, overwrite))?
+        .map(|_| imported))
+}
+
+/// # Slots Screen
+/// 
+/// Lists the save slots, and lets the player load, delete, rename and copy them. 
+/// Slots can be given by name or by their number in the list.
+/// 
+/// Returns true if a game was loaded.
+pub fn slots_screen(common_state: &mut CommonState) -> bool {
+    let mut msg = String::new();
+    loop {
+        let slots = list_slots();
+        stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
+        stdout().execute(Print("----- Save Slots -----\nCommands: L <slot> -> Load | D <slot> -> Delete | R <slot> <new> -> Rename | C <slot> <new> -> Copy | Q -> Back\n\n")).unwrap();
+        if slots.is_empty() {
+            stdout().execute(Print("No saves yet.\n")).unwrap();
+        }
+        for (i, slot) in slots.iter().enumerate() {
+            stdout().execute(Print(format!("({}) {}\n", i + 1, slot))).unwrap();
+        }
+        stdout().execute(Print(format!("\n{}\n", msg))).unwrap();
+        let mut buff = String::new();
+        stdin().read_line(&mut buff).unwrap();
+        buff = buff.trim().to_string();
+        let mut args = buff.split_whitespace();
+        let command = args.next().unwrap_or("").to_lowercase();
+        let slot = args.next().map(|arg| slot_name(&slots, arg));
+        let target = args.next();
+        let result = match (command.as_str(), slot.as_deref(), target) {
+            ("l", Some(slot), None) => match read_slot(slot).map(Some).or_else(|err| recover_slot(slot, err)) {
+                Ok(Some(loaded)) => {
+                    *common_state = loaded;
+                    return true;
+                },
+                Ok(None) => Ok(String::from("Nothing loaded.")),
+                Err(err) => Err(err),
+            },
+            ("d", Some(slot), None) => {
+                if confirm(&format!("Delete the save slot {}? It can't be undone.", slot)) {
+                    delete_slot(slot).map(|_| format!("Deleted {}.", slot))
+                } else {
+                    Ok(String::from("Nothing deleted."))
+                }
+            },
+            ("r", Some(slot), Some(to)) =>
+                ask_overwrite(|overwrite| rename_slot(slot, to, overwrite))
+                    .map(|done| done.map_or(String::from("Nothing renamed."),
+                        |_| format!("Renamed {} to {}.", slot, to))),
+            ("c", Some(slot), Some(to)) =>
+                ask_overwrite(|overwrite| copy_slot(slot, to, overwrite))
+                    .map(|done| done.map_or(String::from("Nothing copied."),
+                        |_| format!("Copied {} to {}.", slot, to))),
+            ("q", None, None) => return false,
+            _ => Ok(String::from("Invalid Command.")),
+        };
+        msg = result.unwrap_or_else(|err| err.to_string());
+    }
+}
+
+/// # Recover Slot
+/// 
+/// When a slot can't be read, offers its backups to load instead. A tampered slot
+/// can also be loaded as it is, as a tainted run. Returns None if the player picks
+/// none of them, and the original error if there's nothing to offer.
+fn recover_slot(slot: &str, err: SaveError) -> Result<Option<CommonState>, SaveError> {
+    let backups = list_backups(slot);
+    let tampered = err.is_tampered();
+    if matches!(err, SaveError::NoSuchSlot(_) | SaveError::BadSlotName(_))
+        || (backups.is_empty() && !tampered) {
+        return Err(err);
+    }
+    stdout().execute(Print(format!("{}\nBackups of {}, newest first:\n", err, slot))).unwrap();
+    if backups.is_empty() {
+        stdout().execute(Print("None.\n")).unwrap();
+    }
+    for backup in backups.iter() {
+        stdout().execute(Print(format!("({}) {}\n", backup.backup, backup))).unwrap();
+    }
+    if tampered {
+        stdout().execute(Print("(T) Load it anyway, as a tainted run kept off leaderboards and achievements.\n")).unwrap();
+    }
+    stdout().execute(Print("Enter a backup's number to recover it, or anything else to leave it.\n")).unwrap();
+    let mut buff = String::new();
+    stdin().read_line(&mut buff).unwrap();
+    let choice = buff.trim().to_lowercase();
+    match choice.parse::<usize>() {
+        Ok(backup) if backups.iter().any(|b| b.backup == backup && b.summary.is_ok()) =>
+            restore_backup(slot, backup).map(Some),
+        _ if tampered && choice == "t" => read_slot_tainted(slot).map(Some),
+        _ => Ok(None),
+    }
+}
+
+/// # Slot Name
+/// 
+/// The slot an argument names, either by its number in the list or by name.
+fn slot_name(slots: &[SlotInfo], arg: &str) -> String {
+    arg.parse::<usize>().ok()
+        .and_then(|i| slots.get(i.wrapping_sub(1)))
+        .map_or(arg.to_string(), |slot| slot.name.clone())
+}
+
+/// # Confirm
+/// 
+/// Asks the player a yes or no question.
+fn confirm(question: &str) -> bool {
+    stdout().execute(Print(format!("{} (Y/N)\n", question))).unwrap();
+    let mut buff = String::new();
+    stdin().read_line(&mut buff).unwrap();
+    buff.trim().to_lowercase() == "y"
+}
+
+/// # Ask Overwrite
+/// 
+/// Tries a slot action without overwriting, and if the slot it would write to is 
+/// taken, asks before trying again with overwriting. Returns None if the player 
+/// says no.
+fn ask_overwrite<T>(mut action: impl FnMut(bool) -> Result<T, SaveError>)
+-> Result<Option<T>, SaveError> {
+    match action(false) {
+        Err(SaveError::SlotExists(name)) => {
+            if confirm(&format!("There's already a save slot called {}. Overwrite it?", name)) {
+                action(true).map(Some)
+            } else {
+                Ok(None)
+            }
+        },
+        result => result.map(Some),
+    }
+}
+
 pub fn game_menu(common_state: &mut CommonState) {
-    let mut rng = stdrng::rng();
+    // Taken out for the games to use alongside the state, and put back before saving.
+    let mut rng = common_state.rng.clone();
     let mut msg = String::new();
     loop {
+        autosave(common_state, None, &rng, &mut msg);
         stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
         stdout().execute(Print("!!!!!!!!!! Improbability Machine !!!!!!!!!!\n\n")).unwrap();
         stdout().execute(Print(format!("{}\n\n", msg))).unwrap();
-        stdout().execute(Print(format!("Money: ${}\tEntropy: {} b\n", common_state.money, common_state.entropy))).unwrap();
+        stdout().execute(Print(format!("Money: {}\tEntropy: {} b\n", common_state.money, common_state.entropy))).unwrap();
+        stdout().execute(Print(format!("{}\n",
+            common_state.machine.contracts.summary(common_state.game_time())))).unwrap();
+        if common_state.tainted {
+            stdout().execute(Print("Tainted Run: loaded from a tampered save.\n")).unwrap();
+        }
         stdout().execute(Print("Game Commands:\n")).unwrap();
         stdout().execute(Print("(1) Coin Toss\n")).unwrap();
+        stdout().execute(Print("(2) Keno\n")).unwrap();
+        stdout().execute(Print(format!("(3) Bingo Hall{}\n", lock_tag(common_state, "3")))).unwrap();
+        stdout().execute(Print("(4) Hi-Lo\n")).unwrap();
+        stdout().execute(Print(format!("(5) Sportsbook{}\n", lock_tag(common_state, "5")))).unwrap();
+        stdout().execute(Print(format!("(6) Baccarat{}\n", lock_tag(common_state, "6")))).unwrap();
+        stdout().execute(Print(format!("(7) Money Wheel{}\n", lock_tag(common_state, "7")))).unwrap();
+        stdout().execute(Print("(M)achine\n")).unwrap();
+        stdout().execute(Print("(L)edger\n")).unwrap();
         stdout().execute(Print("(S)ave Game\n")).unwrap();
+        stdout().execute(Print("(E)xport -> Show a Code to Share this Game\n")).unwrap();
+        stdout().execute(Print(format!("(B)ackups # -> Keep # Backups of the Save (Keeping {})\n",
+            common_state.save_backups))).unwrap();
+        stdout().execute(Print(format!("(A)utosave # -> Autosave every # Minutes, 0 for Off ({})\n",
+            autosave_setting(common_state)))).unwrap();
+        stdout().execute(Print(format!("(H)arvest # -> Harvest #% of each Outcome's Surprisal ({:.0}%)\n",
+            common_state.harvest_fraction * 100.0))).unwrap();
         stdout().execute(Print("(Q) Return to Main Menu\n\n")).unwrap();
         let mut buff = String::new();
         stdin().read_line(&mut buff).unwrap();
         buff = buff.trim().to_string();
+        if let Some(research) = game_research(&buff)
+            && !common_state.machine.researched(research) {
+            msg = format!("Research the {} at the machine to open it.", research.name());
+            continue;
+        }
         if buff == "1" {
             let mut coin_toss = CoinToss::new();
+            common_state.restore_game(&mut coin_toss.base);
             common_state.current_bet = coin_toss.base.bet_min;
-            loop {
-                if let Some(res) = coin_toss_cmd::select_screen(common_state, &mut coin_toss, 
-                common_state.last_prior_save, &mut rng) {
-                    coin_toss.state = res;
-                } else {
-                    break;
-                }
+            while let Some(res) = coin_toss_cmd::select_screen(common_state, &mut coin_toss, 
+            common_state.last_prior_save, &mut rng) {
+                coin_toss.state = res;
+                autosave(common_state, Some(&coin_toss.base), &rng, &mut msg);
+            }
+            common_state.store_game(&coin_toss.base);
+            common_state.autosave.request(AutosaveEvent::LeftGame);
+        } else if buff == "2" {
+            let mut keno = Keno::new();
+            common_state.restore_game(&mut keno.base);
+            while let Some(res) = keno_cmd::select_screen(common_state, &mut keno, &mut rng) {
+                keno.state = res;
+                autosave(common_state, Some(&keno.base), &rng, &mut msg);
+            }
+            common_state.store_game(&keno.base);
+            common_state.autosave.request(AutosaveEvent::LeftGame);
+        } else if buff == "3" {
+            let mut hall = BingoHall::new();
+            common_state.restore_game(&mut hall.base);
+            while let Some(res) = bingo_cmd::select_screen(common_state, &mut hall, &mut rng) {
+                hall.state = res;
+                autosave(common_state, Some(&hall.base), &rng, &mut msg);
+            }
+            common_state.store_game(&hall.base);
+            common_state.autosave.request(AutosaveEvent::LeftGame);
+        } else if buff == "4" {
+            let mut hilo = HiLo::new(&mut rng);
+            common_state.restore_game(&mut hilo.base);
+            while let Some(res) = hilo_cmd::select_screen(common_state, &mut hilo, &mut rng) {
+                hilo.state = res;
+                autosave(common_state, Some(&hilo.base), &rng, &mut msg);
             }
+            common_state.store_game(&hilo.base);
+            common_state.autosave.request(AutosaveEvent::LeftGame);
+        } else if buff == "5" {
+            sports_cmd::sportsbook_screen(common_state, &mut rng);
+            common_state.autosave.request(AutosaveEvent::LeftGame);
+        } else if buff == "6" {
+            let mut table = Baccarat::new(&mut rng);
+            common_state.restore_game(&mut table.base);
+            while let Some(res) = baccarat_cmd::select_screen(common_state, &mut table, &mut rng) {
+                table.state = res;
+                autosave(common_state, Some(&table.base), &rng, &mut msg);
+            }
+            common_state.store_game(&table.base);
+            common_state.autosave.request(AutosaveEvent::LeftGame);
+        } else if buff == "7" {
+            let mut wheel = MoneyWheel::new();
+            common_state.restore_game(&mut wheel.base);
+            while let Some(res) = wheel_cmd::select_screen(common_state, &mut wheel, &mut rng) {
+                wheel.state = res;
+                autosave(common_state, Some(&wheel.base), &rng, &mut msg);
+            }
+            common_state.store_game(&wheel.base);
+            common_state.autosave.request(AutosaveEvent::LeftGame);
+        } else if buff.to_lowercase() == "m" {
+            machine_screen(common_state);
+            msg = String::new();
+        } else if buff.to_lowercase() == "l" {
+            msg = common_state.ledger.recent(10).map(|t| t.to_string())
+                .collect::<Vec<String>>().join("\n");
         } else if buff.to_lowercase() == "s" {
-            save_common_state(common_state);
-            msg = String::from("!!!!! Saved !!!!!!")
+            common_state.rng = rng.clone();
+            msg = match save_common_state(common_state) {
+                Ok(()) => String::from("!!!!! Saved !!!!!!"),
+                Err(err) => err.to_string(),
+            };
+        } else if buff.to_lowercase() == "e" {
+            common_state.rng = rng.clone();
+            msg = format!("Copy the whole line to share this game:\n{}", export_state(common_state));
+        } else if let Some(count) = buff.to_lowercase().strip_prefix("b ") {
+            msg = match count.trim().parse::<usize>() {
+                Ok(count) if count <= SAVE_BACKUPS_MAX => {
+                    common_state.save_backups = count;
+                    format!("Keeping {} backups from the next save.", count)
+                },
+                _ => format!("Backups must be from 0 to {}.", SAVE_BACKUPS_MAX),
+            };
+        } else if let Some(minutes) = buff.to_lowercase().strip_prefix("a ") {
+            msg = match minutes.trim().parse::<u64>() {
+                Ok(minutes) if minutes <= AUTOSAVE_MINUTES_MAX => {
+                    common_state.autosave.interval = Duration::from_secs(minutes * 60);
+                    format!("Autosave is {}.", autosave_setting(common_state))
+                },
+                _ => format!("Autosave must be every 0 to {} minutes.", AUTOSAVE_MINUTES_MAX),
+            };
+        } else if let Some(percent) = buff.to_lowercase().strip_prefix("h ") {
+            msg = match percent.trim().parse::<u32>() {
+                Ok(percent) if percent <= HARVEST_PERCENT_MAX => {
+                    common_state.harvest_fraction = percent as f64 / 100.0;
+                    format!("Harvesting {}% of each outcome's surprisal.", percent)
+                },
+                _ => format!("Harvest must be from 0 to {}%.", HARVEST_PERCENT_MAX),
+            };
         } else if buff.to_lowercase() == "q" {
+            common_state.rng = rng;
             return;
         }
     }
 }
 
-pub fn save_common_state(common_state: &mut CommonState) {
-    let path = Path::new(SAVE_PATH);
-    let mut file = File::create(path).unwrap();
-    file.write(common_state.save_str().as_bytes()).unwrap();
+/// # Game Research
+/// 
+/// The research the game picked from the menu needs, if it needs any.
+fn game_research(pick: &str) -> Option<Research> {
+    match pick {
+        "3" => Some(Research::BingoHall),
+        "5" => Some(Research::Sportsbook),
+        "6" => Some(Research::Baccarat),
+        "7" => Some(Research::MoneyWheel),
+        _ => None,
+    }
+}
+
+/// What's put after a game in the menu while it still needs researching.
+fn lock_tag(common_state: &CommonState, pick: &str) -> &'static str {
+    match game_research(pick) {
+        Some(research) if !common_state.machine.researched(research) => " [Locked: Research at the Machine]",
+        _ => "",
+    }
+}
+
+/// The longest the autosave interval can be set to, in minutes.
+const AUTOSAVE_MINUTES_MAX: u64 = 24 * 60;
+/// The most of an outcome's surprisal that can be harvested, in percent.
+const HARVEST_PERCENT_MAX: u32 = 100;
+
+/// # Autosave
+/// 
+/// Puts the game's data and the rng back into the state, runs the machine and its
+/// contracts, then autosaves if one's due. How the last autosave went is shown in 
+/// the message.
+fn autosave(common_state: &mut CommonState, game: Option<&GameCommonData>, rng: &ChaCha8Rng,
+msg: &mut String) {
+    if let Some(game) = game {
+        common_state.store_game(game);
+    }
+    common_state.rng = rng.clone();
+    common_state.tick();
+    if let Some(news) = common_state.machine.contracts.news.take() {
+        *msg = news;
+    }
+    if let Some(report) = common_state.autosave_poll() {
+        *msg = report;
+    }
 }
 
-pub fn new_save_file(player_name: String) -> CommonState {
-    let path = Path::new(SAVE_PATH);
-    let mut new_data = CommonState::new(player_name);
-    let mut file = File::create(path).unwrap();
-    file.write(new_data.save_str().as_bytes()).unwrap();
-    new_data
+/// # Autosave Setting
+/// 
+/// How often the game autosaves, for the menu.
+fn autosave_setting(common_state: &CommonState) -> String {
+    match common_state.autosave.interval.as_secs() / 60 {
+        0 if !common_state.autosave.enabled() => String::from("Off"),
+        0 => format!("every {} s", common_state.autosave.interval.as_secs()),
+        minutes => format!("every {} min", minutes),
+    }
 }
 
-pub fn load_save() -> CommonState {
-    let path = Path::new(SAVE_PATH);
-    let file = fs::read_to_string(path)
-        .expect("Could not read file.");
-    let mut new_state = CommonState::empty();
-    new_state.load_state(file);
-    new_state
+/// # Save Common State
+/// 
+/// Saves the game over its own slot.
+pub fn save_common_state(common_state: &mut CommonState) -> Result<(), SaveError> {
+    let slot = common_state.save_slot.clone();
+    write_slot(&slot, common_state, true)
 }
\ No newline at end of file
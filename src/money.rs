@@ -1,7 +1,522 @@
-pub(crate) fn to_lsd(pence: f64) -> String {
-    let l = (pence / 240.0).floor();
-    let s = (pence % 240.0 / 12.0).floor();
-    let d = (pence % 12.0).floor();
-    let f = (pence.fract() * 4.0).floor();
-    format!("£{} {}s {}d {}/4 f", l, s, d, f)
-}
\ No newline at end of file
+use std::{fmt, ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign}, str::FromStr};
+
+/// Farthings in a penny.
+pub const FARTHINGS_PER_PENNY: i64 = 4;
+/// Pence in a shilling.
+pub const PENCE_PER_SHILLING: i64 = 12;
+/// Shillings in a pound.
+pub const SHILLINGS_PER_POUND: i64 = 20;
+/// Farthings in a shilling.
+pub const FARTHINGS_PER_SHILLING: i64 = FARTHINGS_PER_PENNY * PENCE_PER_SHILLING;
+/// Farthings in a pound.
+pub const FARTHINGS_PER_POUND: i64 = FARTHINGS_PER_SHILLING * SHILLINGS_PER_POUND;
+
+/// A penny, for reading pence.
+const PENNY: Money = Money::from_pence(1);
+
+/// The coins and notes the parser knows by name, and what they're worth. Plurals
+/// are handled by the parser.
+const COIN_NAMES: [(&str, Money); 14] = [
+    ("farthing", Money::from_farthings(1)),
+    ("halfpenny", Money::from_farthings(2)),
+    ("ha'penny", Money::from_farthings(2)),
+    ("penny", Money::from_pence(1)),
+    ("pence", Money::from_pence(1)),
+    ("threepence", Money::from_pence(3)),
+    ("sixpence", Money::from_pence(6)),
+    ("shilling", Money::from_shillings(1)),
+    ("bob", Money::from_shillings(1)),
+    ("florin", Money::from_shillings(2)),
+    ("crown", Money::from_shillings(5)),
+    ("pound", Money::from_pounds(1)),
+    ("sovereign", Money::from_pounds(1)),
+    ("guinea", Money::from_shillings(21)),
+];
+
+/// # Money
+/// 
+/// An amount of money in £sd (pounds, shillings, pence), stored as a whole number
+/// of farthings so nothing is lost to floating point as it's passed around.
+/// 
+/// The smallest unit is the farthing (1/4 d), 12 d to the shilling, and 20 s to the
+/// pound.
+/// 
+/// Arithmetic between amounts is exact. Scaling by a ratio (payouts and the like)
+/// rounds down to the farthing, the house keeps the scraps.
+/// 
+/// Displays in £sd by default ("£1 4s 6¾d"), and in decimal pence with the
+/// alternate flag ("294.75d").
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Money(i64);
+
+impl Money {
+    pub const ZERO: Money = Money(0);
+    /// The largest amount that can be held, used for limits which have none.
+    pub const MAX: Money = Money(i64::MAX);
+
+    pub const fn from_farthings(farthings: i64) -> Self {
+        Money(farthings)
+    }
+
+    pub const fn from_pence(pence: i64) -> Self {
+        Money(pence * FARTHINGS_PER_PENNY)
+    }
+
+    pub const fn from_shillings(shillings: i64) -> Self {
+        Money(shillings * FARTHINGS_PER_SHILLING)
+    }
+
+    pub const fn from_pounds(pounds: i64) -> Self {
+        Money(pounds * FARTHINGS_PER_POUND)
+    }
+
+    /// # From LSD
+    /// 
+    /// Builds an amount from pounds, shillings, pence, and farthings. The parts
+    /// don't need to be in range, 30 s is £1 10s.
+    pub const fn from_lsd(pounds: i64, shillings: i64, pence: i64, farthings: i64) -> Self {
+        Money(pounds * FARTHINGS_PER_POUND + shillings * FARTHINGS_PER_SHILLING
+            + pence * FARTHINGS_PER_PENNY + farthings)
+    }
+
+    /// # From Pence (f64)
+    /// 
+    /// Converts a number of pence to the nearest farthing. For things which are still
+    /// measured in floating point, like old saves.
+    /// 
+    /// Returns None if the value isn't finite or is too large to hold.
+    pub fn from_pence_f64(pence: f64) -> Option<Self> {
+        let farthings = (pence * FARTHINGS_PER_PENNY as f64).round();
+        if farthings.is_finite() && farthings.abs() < i64::MAX as f64 {
+            Some(Money(farthings as i64))
+        } else {
+            None
+        }
+    }
+
+    pub const fn farthings(&self) -> i64 {
+        self.0
+    }
+
+    /// # Pence
+    /// 
+    /// The amount in pence, as a float. For ratios and display only, never feed it
+    /// back into money.
+    pub fn pence(&self) -> f64 {
+        self.0 as f64 / FARTHINGS_PER_PENNY as f64
+    }
+
+    /// # LSD
+    /// 
+    /// Splits the size of the amount into pounds, shillings, pence, and farthings.
+    /// The sign is dropped, check is_negative for it.
+    pub const fn lsd(&self) -> (u64, u64, u64, u64) {
+        let total = self.0.unsigned_abs();
+        (total / FARTHINGS_PER_POUND as u64,
+            total % FARTHINGS_PER_POUND as u64 / FARTHINGS_PER_SHILLING as u64,
+            total % FARTHINGS_PER_SHILLING as u64 / FARTHINGS_PER_PENNY as u64,
+            total % FARTHINGS_PER_PENNY as u64)
+    }
+
+    pub const fn is_negative(&self) -> bool {
+        self.0 < 0
+    }
+
+    pub const fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn checked_add(self, rhs: Money) -> Option<Money> {
+        match self.0.checked_add(rhs.0) {
+            Some(farthings) => Some(Money(farthings)),
+            None => None,
+        }
+    }
+
+    pub const fn checked_sub(self, rhs: Money) -> Option<Money> {
+        match self.0.checked_sub(rhs.0) {
+            Some(farthings) => Some(Money(farthings)),
+            None => None,
+        }
+    }
+
+    pub const fn checked_mul(self, rhs: i64) -> Option<Money> {
+        match self.0.checked_mul(rhs) {
+            Some(farthings) => Some(Money(farthings)),
+            None => None,
+        }
+    }
+
+    /// # Checked Mul Ratio
+    /// 
+    /// Scales the amount by a ratio, rounding down to the farthing.
+    /// 
+    /// Returns None if the ratio isn't finite or the result is too large to hold.
+    pub fn checked_mul_ratio(self, ratio: f64) -> Option<Money> {
+        let farthings = (self.0 as f64 * ratio).floor();
+        if farthings.is_finite() && farthings.abs() < i64::MAX as f64 {
+            Some(Money(farthings as i64))
+        } else {
+            None
+        }
+    }
+
+    /// # Mul Ratio
+    /// 
+    /// Scales the amount by a ratio, rounding down to the farthing.
+    /// 
+    /// # Panics
+    /// 
+    /// If the ratio isn't finite, or the result overflows.
+    pub fn mul_ratio(self, ratio: f64) -> Money {
+        self.checked_mul_ratio(ratio).expect("Money ratio overflowed.")
+    }
+
+    /// # Ratio
+    /// 
+    /// How many times the other amount goes into this one.
+    pub fn ratio(self, other: Money) -> f64 {
+        self.0 as f64 / other.0 as f64
+    }
+
+    /// # Save Str(ing)
+    /// 
+    /// The amount for save files, a whole number of farthings with an f on the end
+    /// so it can't be mistaken for the pence old saves used.
+    pub fn save_str(&self) -> String {
+        format!("{}f", self.0)
+    }
+
+    /// # Load Str(ing)
+    /// 
+    /// Reads an amount written by save_str, or the plain pence of old saves.
+    pub fn load_str(s: &str) -> Option<Money> {
+        let s = s.trim();
+        match s.strip_suffix('f') {
+            Some(farthings) => farthings.parse::<i64>().ok().map(Money),
+            None => s.parse::<f64>().ok().and_then(Money::from_pence_f64),
+        }
+    }
+}
+
+/// # Coin Value
+/// 
+/// The value of a named coin, singular or plural.
+fn coin_value(word: &str) -> Option<Money> {
+    let word = word.trim_end_matches(',');
+    let singular = if word == "pennies" {
+        "penny"
+    } else if word == "ha'pence" || word == "halfpence" {
+        "halfpenny"
+    } else {
+        word.strip_suffix('s').unwrap_or(word)
+    };
+    COIN_NAMES.iter().find(|(name, _)| *name == word || *name == singular)
+        .map(|(_, value)| *value)
+}
+
+/// # Looks Like Money
+/// 
+/// Whether the input was meant as an amount, so a failed read is worth explaining
+/// rather than being taken as a mistyped command. It starts with a digit, a point,
+/// £, or a minus, or names a coin.
+pub fn looks_like_money(s: &str) -> bool {
+    let input = s.trim().to_lowercase();
+    input.starts_with(|c: char| c.is_ascii_digit() || matches!(c, '.' | '£' | '-'))
+        || input.split_whitespace().any(|word| coin_value(word).is_some())
+}
+
+/// # Parse Whole
+/// 
+/// Reads a whole, non-negative number.
+fn parse_whole(s: &str, input: &str) -> Result<i64, String> {
+    if s.is_empty() || !s.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("Couldn't read \"{}\" as money.", input));
+    }
+    s.parse::<i64>().map_err(|_| format!("\"{}\" is far too much money.", input))
+}
+
+/// # Parse Units
+/// 
+/// Reads a whole, non-negative number of the given unit.
+fn parse_units(s: &str, unit: Money, input: &str) -> Result<Money, String> {
+    unit.checked_mul(parse_whole(s, input)?)
+        .ok_or_else(|| format!("\"{}\" is far too much money.", input))
+}
+
+/// # Parse Pence
+/// 
+/// Reads a number of pence, which may have a farthing fraction on the end, either
+/// as a glyph (6¾) or a decimal (6.75).
+fn parse_pence(s: &str, input: &str) -> Result<Money, String> {
+    for (glyph, farthings) in [('¼', 1), ('½', 2), ('¾', 3)] {
+        if let Some(whole) = s.strip_suffix(glyph) {
+            let pence = if whole.is_empty() { Money::ZERO } else { parse_units(whole, PENNY, input)? };
+            return Ok(pence + Money::from_farthings(farthings));
+        }
+    }
+    if let Some((whole, fraction)) = s.split_once('.') {
+        let farthings = match fraction.trim_end_matches('0') {
+            "" => 0,
+            "25" => 1,
+            "5" => 2,
+            "75" => 3,
+            _ => return Err(format!("\"{}\" isn't a whole number of farthings.", input)),
+        };
+        let pence = if whole.is_empty() { Money::ZERO } else { parse_units(whole, PENNY, input)? };
+        return Ok(pence + Money::from_farthings(farthings));
+    }
+    parse_units(s, PENNY, input)
+}
+
+/// # Parse Token
+/// 
+/// Reads a single written amount, like £1, 7s, 6d, 3¾d, 2/6, 2/-, or 3f.
+fn parse_token(token: &str) -> Result<Money, String> {
+    if let Some(pounds) = token.strip_prefix('£') {
+        return parse_units(pounds, Money::from_pounds(1), token);
+    }
+    if let Some((shillings, pence)) = token.split_once('/') {
+        let shillings = parse_units(shillings, Money::from_shillings(1), token)?;
+        let pence = match pence.trim_end_matches('d') {
+            "-" => Money::ZERO,
+            pence => parse_pence(pence, token)?,
+        };
+        return shillings.checked_add(pence)
+            .ok_or_else(|| format!("\"{}\" is far too much money.", token));
+    }
+    if let Some(shillings) = token.strip_suffix('s') {
+        return parse_units(shillings, Money::from_shillings(1), token);
+    }
+    if let Some(pence) = token.strip_suffix('d') {
+        return parse_pence(pence, token);
+    }
+    if let Some(farthings) = token.strip_suffix('f') {
+        return parse_whole(farthings, token).map(Money::from_farthings);
+    }
+    Err(format!("Couldn't read \"{}\" as money.", token))
+}
+
+impl FromStr for Money {
+    type Err = String;
+
+    /// # From Str
+    /// 
+    /// Reads an amount written the way the period would. Parts are added together,
+    /// and can be mixed freely.
+    /// 
+    /// - Bare numbers are pence: "30", "2.5"
+    /// - Pounds, shillings, and pence: "£1 2s 6d", "7s", "3¾d"
+    /// - Shillings and pence: "2/6", "10/-"
+    /// - Farthings: "3f", or the "3d 1/4 f" of older displays
+    /// - Coins by name: "a guinea", "3 florins", "half a crown", "a crown and sixpence"
+    fn from_str(s: &str) -> Result<Money, String> {
+        let input = s.trim().to_lowercase();
+        if input.is_empty() {
+            return Err(String::from("No amount given."));
+        }
+        if input.starts_with('-') {
+            return Err(String::from("Amounts can't be negative."));
+        }
+        let words: Vec<&str> = input.split_whitespace()
+            .filter(|w| *w != "and" && *w != ",")
+            .collect();
+        let mut total = Money::ZERO;
+        let mut i = 0;
+        while i < words.len() {
+            let word = words[i];
+            let next = words.get(i + 1).copied();
+            // A count or half, followed by a coin name.
+            let (count, halved) = match word {
+                "a" | "an" | "one" => (Some(1), false),
+                "half" => (Some(1), true),
+                _ => (word.parse::<i64>().ok(), false),
+            };
+            if let Some(count) = count {
+                // "half a crown", the a is optional.
+                let skip = if halved && matches!(next, Some("a") | Some("an")) { 1 } else { 0 };
+                let coin = words.get(i + 1 + skip).copied();
+                if let Some(value) = coin.and_then(coin_value) {
+                    let value = if halved {
+                        if value.farthings() % 2 != 0 {
+                            return Err(format!("There's no such thing as half a {}.",
+                                coin.unwrap_or_default()));
+                        }
+                        Money::from_farthings(value.farthings() / 2)
+                    } else {
+                        value
+                    };
+                    total = value.checked_mul(count)
+                        .and_then(|v| total.checked_add(v))
+                        .ok_or(format!("\"{}\" is far too much money.", s.trim()))?;
+                    i += 2 + skip;
+                    continue;
+                }
+                if halved || matches!(word, "a" | "an" | "one") {
+                    return Err(format!("\"{}\" needs a coin after it, like a florin.", word));
+                }
+            }
+            // The old display put farthings as "1/4 f".
+            let amount = if next == Some("f") && word.ends_with("/4") {
+                i += 1;
+                parse_whole(word.trim_end_matches("/4"), word).map(Money::from_farthings)?
+            } else if let Some(value) = coin_value(word) {
+                value
+            } else if word.starts_with(|c: char| c.is_ascii_digit() || c == '.')
+            && word.chars().all(|c| c.is_ascii_digit() || c == '.') {
+                parse_pence(word, word)?
+            } else {
+                parse_token(word)?
+            };
+            total = total.checked_add(amount)
+                .ok_or(format!("\"{}\" is far too much money.", s.trim()))?;
+            i += 1;
+        }
+        Ok(total)
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+
+    fn add(self, rhs: Money) -> Money {
+        self.checked_add(rhs).expect("Money overflowed.")
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, rhs: Money) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+
+    fn sub(self, rhs: Money) -> Money {
+        self.checked_sub(rhs).expect("Money overflowed.")
+    }
+}
+
+impl SubAssign for Money {
+    fn sub_assign(&mut self, rhs: Money) {
+        *self = *self - rhs;
+    }
+}
+
+impl Mul<i64> for Money {
+    type Output = Money;
+
+    fn mul(self, rhs: i64) -> Money {
+        self.checked_mul(rhs).expect("Money overflowed.")
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+
+    fn neg(self) -> Money {
+        Money(self.0.checked_neg().expect("Money overflowed."))
+    }
+}
+
+impl std::iter::Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        iter.fold(Money::ZERO, |acc, m| acc + m)
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.is_negative() { "-" } else { "" };
+        if f.alternate() {
+            return write!(f, "{}{}d", sign, self.pence().abs());
+        }
+        let (l, s, d, q) = self.lsd();
+        let farthing = ["", "¼", "½", "¾"][q as usize];
+        let mut parts = vec![];
+        if l > 0 {
+            parts.push(format!("£{}", l));
+        }
+        if s > 0 {
+            parts.push(format!("{}s", s));
+        }
+        if d > 0 || q > 0 || parts.is_empty() {
+            parts.push(format!("{}{}d", if d > 0 || q == 0 { d.to_string() } else { String::new() },
+                farthing));
+        }
+        write!(f, "{}{}", sign, parts.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Result<Money, String> {
+        s.parse::<Money>()
+    }
+
+    #[test]
+    fn parses_pounds_shillings_and_pence() {
+        assert_eq!(parse("£1 2s 6d"), Ok(Money::from_lsd(1, 2, 6, 0)));
+        assert_eq!(parse("7s"), Ok(Money::from_shillings(7)));
+        assert_eq!(parse("6d"), Ok(Money::from_pence(6)));
+        assert_eq!(parse("3¾d"), Ok(Money::from_lsd(0, 0, 3, 3)));
+        assert_eq!(parse("3.75d"), Ok(Money::from_lsd(0, 0, 3, 3)));
+        assert_eq!(parse("£2"), Ok(Money::from_pounds(2)));
+    }
+
+    #[test]
+    fn parses_bare_pence() {
+        assert_eq!(parse("30"), Ok(Money::from_pence(30)));
+        assert_eq!(parse("2.5"), Ok(Money::from_lsd(0, 0, 2, 2)));
+    }
+
+    #[test]
+    fn parses_shillings_and_pence_with_a_slash() {
+        assert_eq!(parse("2/6"), Ok(Money::from_lsd(0, 2, 6, 0)));
+        assert_eq!(parse("10/-"), Ok(Money::from_shillings(10)));
+        assert_eq!(parse("2/-"), Ok(Money::from_shillings(2)));
+    }
+
+    #[test]
+    fn parses_farthings() {
+        assert_eq!(parse("3f"), Ok(Money::from_farthings(3)));
+        assert_eq!(parse("3d 1/4 f"), Ok(Money::from_lsd(0, 0, 3, 1)));
+    }
+
+    #[test]
+    fn parses_coin_names() {
+        assert_eq!(parse("a guinea"), Ok(Money::from_shillings(21)));
+        assert_eq!(parse("half a crown"), Ok(Money::from_lsd(0, 2, 6, 0)));
+        assert_eq!(parse("half crown"), Ok(Money::from_lsd(0, 2, 6, 0)));
+        assert_eq!(parse("3 florins"), Ok(Money::from_shillings(6)));
+        assert_eq!(parse("2 pennies"), Ok(Money::from_pence(2)));
+        assert_eq!(parse("a crown and sixpence"), Ok(Money::from_lsd(0, 5, 6, 0)));
+        assert_eq!(parse("sovereign"), Ok(Money::from_pounds(1)));
+    }
+
+    #[test]
+    fn reads_back_what_it_displays() {
+        for farthings in [1, 2, 3, 4, 47, 48, 959, 960, 1234567] {
+            let money = Money::from_farthings(farthings);
+            assert_eq!(parse(&money.to_string()), Ok(money));
+        }
+    }
+
+    #[test]
+    fn rejects_bad_amounts() {
+        assert!(parse("").is_err());
+        assert!(parse("-6d").is_err());
+        assert!(parse("99999999999999999999").is_err());
+        assert!(parse("£9999999999999999").is_err());
+        assert!(parse("9999999999999999999s").is_err());
+        assert!(parse("3000000000000000000").is_err());
+        assert!(parse("999999999999999999/6").is_err());
+        assert!(parse("half a farthing").is_err());
+        assert!(parse("a").is_err());
+        assert!(parse("6.3d").is_err());
+        assert!(parse("x").is_err());
+    }
+}
@@ -0,0 +1,409 @@
+use std::{fmt, str::FromStr, time::{Duration, SystemTime, UNIX_EPOCH}};
+
+use crate::{export::EXPORT_PREFIX, money::Money, save_slots::SLOT_NAME_MAX};
+
+/// The first line of every save, so they can be told apart from the old CSV saves.
+pub const SAVE_HEADER: &str = "# Improbability Machine save";
+/// The version of the save format written. Version 1 was the old CSV line, and 
+/// version 2 wasn't signed.
+pub const SAVE_VERSION: u32 = 3;
+/// The first version of the save format that's always signed.
+pub const SIGNED_SAVE_VERSION: u32 = 3;
+
+/// # Save Error
+/// 
+/// Why a save couldn't be read, or a save slot couldn't be used.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SaveError {
+    /// The save file couldn't be read at all.
+    Io(String),
+    /// There's nothing in the save.
+    Empty,
+    /// The save was written by a newer version of the game.
+    UnsupportedVersion(u32),
+    /// A section the save needs isn't there.
+    MissingSection(String),
+    /// A section is missing one of its fields.
+    MissingField { section: String, key: String },
+    /// A field is there, but its value can't be read.
+    BadValue { section: String, key: String, value: String },
+    /// A line that's neither a section, a field, nor a comment.
+    BadLine { line: usize, text: String },
+    /// A field of an old CSV save couldn't be read.
+    BadLegacy(String),
+    /// The sports league's records couldn't be read.
+    BadLeague,
+    /// There's no save slot by that name.
+    NoSuchSlot(String),
+    /// There's already a save slot by that name.
+    SlotExists(String),
+    /// The name can't be used for a save slot.
+    BadSlotName(String),
+    /// A slot can't be copied or renamed onto itself.
+    SameSlot,
+    /// The save has no signature, so it can't be trusted.
+    Unsigned,
+    /// The save was changed after the game signed it.
+    Tampered,
+    /// The install's signing key can't be read.
+    BadInstallKey,
+    /// The text given to import isn't an export.
+    NotExport,
+    /// The export is damaged, or was cut short.
+    BadExport(String),
+}
+
+impl SaveError {
+    /// # Is Tampered
+    /// 
+    /// Whether the save could be read, but can't be trusted.
+    pub fn is_tampered(&self) -> bool {
+        matches!(self, SaveError::Unsigned | SaveError::Tampered)
+    }
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Io(err) => write!(f, "Couldn't get at the save file: {}", err),
+            SaveError::Empty => write!(f, "The save is empty."),
+            SaveError::UnsupportedVersion(version) =>
+                write!(f, "The save is version {}, but this build only reads up to version {}.",
+                    version, SAVE_VERSION),
+            SaveError::MissingSection(section) =>
+                write!(f, "The save has no [{}] section.", section),
+            SaveError::MissingField { section, key } =>
+                write!(f, "The [{}] section of the save is missing {}.", section, key),
+            SaveError::BadValue { section, key, value } =>
+                write!(f, "Couldn't read {} = \"{}\" in the [{}] section of the save.", key, value,
+                    section),
+            SaveError::BadLine { line, text } =>
+                write!(f, "Line {} of the save makes no sense: \"{}\"", line, text),
+            SaveError::BadLegacy(field) =>
+                write!(f, "Couldn't read the {} of the old style save.", field),
+            SaveError::BadLeague => write!(f, "Couldn't read the sports league in the save."),
+            SaveError::NoSuchSlot(name) => write!(f, "There's no save slot called {}.", name),
+            SaveError::SlotExists(name) => write!(f, "There's already a save slot called {}.", name),
+            SaveError::BadSlotName(name) =>
+                write!(f, "\"{}\" can't be a slot name, use up to {} letters, numbers, - and _.",
+                    name, SLOT_NAME_MAX),
+            SaveError::SameSlot => write!(f, "That's the same slot."),
+            SaveError::Unsigned => write!(f, "The save isn't signed by this install."),
+            SaveError::Tampered => write!(f, "The save has been changed outside the game."),
+            SaveError::BadInstallKey =>
+                write!(f, "The install's signing key is damaged, so saves can't be checked."),
+            SaveError::NotExport => write!(f, "That isn't an exported game, they start with {}",
+                EXPORT_PREFIX),
+            SaveError::BadExport(err) =>
+                write!(f, "The exported game is damaged or cut short: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+/// # Section
+/// 
+/// A named section of a save, holding key = value fields. Keys may repeat, for
+/// lists of records.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Section {
+    pub name: String,
+    pub entries: Vec<(String, String)>,
+}
+
+impl Section {
+    pub fn new(name: &str) -> Self {
+        Self { name: name.to_string(), entries: vec![] }
+    }
+
+    /// # Set
+    /// 
+    /// Adds a field to the section.
+    pub fn set(&mut self, key: &str, value: impl fmt::Display) {
+        self.entries.push((key.to_string(), value.to_string()));
+    }
+
+    /// # Get
+    /// 
+    /// The first value for the key, if there is one.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    /// # Get All
+    /// 
+    /// Every value for the key, in the order they were written.
+    pub fn get_all<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a str> {
+        self.entries.iter().filter(move |(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    /// # Parse With
+    /// 
+    /// Reads the key's value with the given parser, erroring if it's missing or
+    /// unreadable.
+    pub fn parse_with<T>(&self, key: &str, parse: impl FnOnce(&str) -> Option<T>)
+    -> Result<T, SaveError> {
+        let value = self.get(key).ok_or_else(|| SaveError::MissingField {
+            section: self.name.clone(), key: key.to_string() })?;
+        parse(value).ok_or_else(|| self.bad_value(key, value))
+    }
+
+    /// # Parse
+    /// 
+    /// Reads the key's value, erroring if it's missing or unreadable.
+    pub fn parse<T: FromStr>(&self, key: &str) -> Result<T, SaveError> {
+        self.parse_with(key, |v| v.parse().ok())
+    }
+
+    /// # Parse Or
+    /// 
+    /// Reads the key's value, or the default if it's missing. Fields added after a
+    /// save was written use this.
+    pub fn parse_or<T: FromStr>(&self, key: &str, default: T) -> Result<T, SaveError> {
+        match self.get(key) {
+            Some(_) => self.parse(key),
+            None => Ok(default),
+        }
+    }
+
+    /// # Parse Money
+    /// 
+    /// Reads a money value, as written by Money::save_str.
+    pub fn parse_money(&self, key: &str) -> Result<Money, SaveError> {
+        self.parse_with(key, Money::load_str)
+    }
+
+    /// # Parse Secs
+    /// 
+    /// Reads a duration, written in seconds.
+    pub fn parse_secs(&self, key: &str) -> Result<Duration, SaveError> {
+        self.parse_with(key, |v| v.parse::<f64>().ok()
+            .and_then(|secs| Duration::try_from_secs_f64(secs).ok()))
+    }
+
+    /// # Parse Wall Clock
+    /// 
+    /// Reads a moment, written by wall_clock.
+    pub fn parse_wall_clock(&self, key: &str) -> Result<SystemTime, SaveError> {
+        self.parse_with(key, |v| v.parse::<f64>().ok()
+            .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+            .and_then(|since| UNIX_EPOCH.checked_add(since)))
+    }
+
+    /// # Bad Value
+    /// 
+    /// The error for a value of this section that couldn't be read.
+    pub fn bad_value(&self, key: &str, value: &str) -> SaveError {
+        SaveError::BadValue { section: self.name.clone(), key: key.to_string(),
+            value: value.to_string() }
+    }
+}
+
+impl fmt::Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "[{}]", self.name)?;
+        for (key, value) in self.entries.iter() {
+            writeln!(f, "{} = {}", key, escape(value))?;
+        }
+        Ok(())
+    }
+}
+
+/// # Save File
+/// 
+/// A versioned save, made of sections.
+/// 
+/// ```text
+/// # Improbability Machine save
+/// version = 3
+/// 
+/// [common]
+/// player_name = Ada
+/// money = 960f
+/// ```
+/// 
+/// Values are everything after the " = ", with backslashes and line breaks escaped.
+/// Lines starting with # are comments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaveFile {
+    pub version: u32,
+    pub sections: Vec<Section>,
+}
+
+impl Default for SaveFile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SaveFile {
+    pub fn new() -> Self {
+        Self { version: SAVE_VERSION, sections: vec![] }
+    }
+
+    /// # Is Save File
+    /// 
+    /// Whether the text is in this format, rather than an old CSV save.
+    pub fn is_save_file(text: &str) -> bool {
+        text.trim_start().starts_with(SAVE_HEADER)
+    }
+
+    /// # Parse
+    /// 
+    /// Reads a save, checking its version can be read by this build.
+    pub fn parse(text: &str) -> Result<Self, SaveError> {
+        if text.trim().is_empty() {
+            return Err(SaveError::Empty);
+        }
+        let mut version = None;
+        let mut sections: Vec<Section> = vec![];
+        for (i, raw) in text.lines().enumerate() {
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let bad_line = || SaveError::BadLine { line: i + 1, text: raw.to_string() };
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                sections.push(Section::new(name.trim()));
+                continue;
+            }
+            let (key, value) = line.split_once(" = ")
+                .or_else(|| line.strip_suffix(" =").map(|key| (key, "")))
+                .ok_or_else(bad_line)?;
+            let value = unescape(value).ok_or_else(bad_line)?;
+            match sections.last_mut() {
+                Some(section) => section.entries.push((key.trim().to_string(), value)),
+                None if key.trim() == "version" => {
+                    version = Some(value.parse::<u32>().map_err(|_| bad_line())?);
+                },
+                None => return Err(bad_line()),
+            }
+        }
+        let version = version.ok_or(SaveError::MissingField { section: String::from("header"),
+            key: String::from("version") })?;
+        if version > SAVE_VERSION {
+            return Err(SaveError::UnsupportedVersion(version));
+        }
+        Ok(Self { version, sections })
+    }
+
+    /// # Section
+    /// 
+    /// The first section with the name, erroring if there isn't one.
+    pub fn section(&self, name: &str) -> Result<&Section, SaveError> {
+        self.sections.iter().find(|s| s.name == name)
+            .ok_or_else(|| SaveError::MissingSection(name.to_string()))
+    }
+
+    /// # Sections Named
+    /// 
+    /// Every section with the name, for sections that repeat.
+    pub fn sections_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Section> {
+        self.sections.iter().filter(move |s| s.name == name)
+    }
+}
+
+impl fmt::Display for SaveFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", SAVE_HEADER)?;
+        writeln!(f, "version = {}", self.version)?;
+        for section in self.sections.iter() {
+            writeln!(f)?;
+            write!(f, "{}", section)?;
+        }
+        Ok(())
+    }
+}
+
+/// # Wall Clock
+/// 
+/// A moment as seconds since the Unix epoch, the way saves keep the real time. 
+/// Instants only mean something while the program runs, so timers that should keep
+/// going after it's closed are saved this way.
+pub fn wall_clock(time: SystemTime) -> f64 {
+    time.duration_since(UNIX_EPOCH).map_or(0.0, |since| since.as_secs_f64())
+}
+
+/// Escapes backslashes and line breaks so the value stays on its line.
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('\n', "\\n").replace('\r', "\\r")
+}
+
+/// Undoes escape, returning None for an escape it doesn't know.
+fn unescape(value: &str) -> Option<String> {
+    let mut output = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            output.push(match chars.next()? {
+                '\\' => '\\',
+                'n' => '\n',
+                'r' => '\r',
+                _ => return None,
+            });
+        } else {
+            output.push(c);
+        }
+    }
+    Some(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_with_line_breaks_and_backslashes_read_back() {
+        let mut save = SaveFile::new();
+        let mut section = Section::new("common");
+        section.set("player_name", "Ada\\Lovelace\nof the\rMachine");
+        section.set("empty", "");
+        save.sections.push(section);
+        let read = SaveFile::parse(&save.to_string()).unwrap();
+        assert_eq!(read, save);
+        assert_eq!(read.section("common").unwrap().get("player_name"),
+            Some("Ada\\Lovelace\nof the\rMachine"));
+        assert_eq!(read.section("common").unwrap().get("empty"), Some(""));
+    }
+
+    #[test]
+    fn repeated_keys_and_sections_are_kept() {
+        let text = format!("{}\nversion = 2\n\n[game]\nname = Keno\nbet = 1\nbet = 2\n\n[game]\nname = Hi-Lo\n",
+            SAVE_HEADER);
+        let save = SaveFile::parse(&text).unwrap();
+        assert_eq!(save.version, 2);
+        assert_eq!(save.sections_named("game").count(), 2);
+        assert_eq!(save.section("game").unwrap().get_all("bet").collect::<Vec<_>>(), ["1", "2"]);
+    }
+
+    #[test]
+    fn rejects_bad_saves() {
+        assert_eq!(SaveFile::parse("  \n"), Err(SaveError::Empty));
+        assert_eq!(SaveFile::parse("[common]\nmoney = 1f\n"),
+            Err(SaveError::MissingField { section: String::from("header"),
+                key: String::from("version") }));
+        assert_eq!(SaveFile::parse(&format!("version = {}\n", SAVE_VERSION + 1)),
+            Err(SaveError::UnsupportedVersion(SAVE_VERSION + 1)));
+        assert_eq!(SaveFile::parse("version = 3\nmoney = 1f\n"),
+            Err(SaveError::BadLine { line: 2, text: String::from("money = 1f") }));
+        assert_eq!(SaveFile::parse("version = 3\n[common]\nmoney\n"),
+            Err(SaveError::BadLine { line: 3, text: String::from("money") }));
+        assert_eq!(SaveFile::parse("version = 3\n[common]\nname = a\\tb\n"),
+            Err(SaveError::BadLine { line: 3, text: String::from("name = a\\tb") }));
+        assert!(SaveFile::parse("version = three\n").is_err());
+    }
+
+    #[test]
+    fn fields_report_what_is_wrong_with_them() {
+        let mut section = Section::new("common");
+        section.set("entropy", "lots");
+        assert_eq!(section.parse::<f64>("entropy"), Err(section.bad_value("entropy", "lots")));
+        assert_eq!(section.parse::<f64>("money"),
+            Err(SaveError::MissingField { section: String::from("common"),
+                key: String::from("money") }));
+        assert_eq!(section.parse_or("money", 5), Ok(5));
+        section.set("game_length", "-1");
+        assert!(section.parse_secs("game_length").is_err());
+    }
+}
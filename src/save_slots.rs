@@ -0,0 +1,360 @@
+use std::{fs::{self, File}, io::{self, Write}, path::{Path, PathBuf}, sync::Mutex, time::{Duration, Instant}};
+
+use crate::{common_state::CommonState, money::Money, save::SaveError, signing::{sign_save, split_signature, verify_save}};
+
+/// The folder the save slots live in, one file per slot.
+pub const SAVE_DIR: &str = "./saves";
+/// The file extension of a save slot.
+pub const SAVE_EXTENSION: &str = "txt";
+/// The longest a slot name can be.
+pub const SLOT_NAME_MAX: usize = 32;
+/// How many backups of each slot are kept, unless the player picks otherwise.
+pub const DEFAULT_SAVE_BACKUPS: usize = 3;
+/// The most backups of a slot that can be kept.
+pub const SAVE_BACKUPS_MAX: usize = 9;
+
+/// Held while a slot is written, so an autosave and a save never write at once.
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// # Slot Summary
+/// 
+/// What's shown of a save slot in the slot list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlotSummary {
+    pub player_name: String,
+    pub money: Money,
+    pub entropy: f64,
+    pub game_length: Duration,
+    /// Whether the run was loaded from a tampered save.
+    pub tainted: bool,
+}
+
+impl std::fmt::Display for SlotSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} | {} | {:.2} b | {}", self.player_name, self.money, self.entropy,
+            format_duration(self.game_length))?;
+        if self.tainted {
+            write!(f, " | Tainted")?;
+        }
+        Ok(())
+    }
+}
+
+/// # Slot Info
+/// 
+/// A save slot, and its summary if it could be read.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlotInfo {
+    pub name: String,
+    pub summary: Result<SlotSummary, SaveError>,
+}
+
+impl std::fmt::Display for SlotInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.summary {
+            Ok(s) => write!(f, "{} | {}", self.name, s),
+            Err(err) => write!(f, "{} | Unreadable: {}", self.name, err),
+        }
+    }
+}
+
+/// # Backup Info
+/// 
+/// One of a slot's backups, and its summary if it could be read.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackupInfo {
+    /// Which backup it is, 1 being the newest.
+    pub backup: usize,
+    pub summary: Result<SlotSummary, SaveError>,
+}
+
+impl std::fmt::Display for BackupInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.summary {
+            Ok(s) => write!(f, "Backup {} | {}", self.backup, s),
+            Err(err) => write!(f, "Backup {} | Unreadable: {}", self.backup, err),
+        }
+    }
+}
+
+/// # Format Duration
+/// 
+/// A play time as hours, minutes, and seconds.
+pub fn format_duration(length: Duration) -> String {
+    let secs = length.as_secs();
+    format!("{}h {:02}m {:02}s", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+/// # Check Slot Name
+/// 
+/// Slot names become file names, so they're kept to letters, numbers, dashes and
+/// underscores.
+pub fn check_slot_name(name: &str) -> Result<(), SaveError> {
+    let valid = !name.is_empty() && name.len() <= SLOT_NAME_MAX
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(SaveError::BadSlotName(name.to_string()))
+    }
+}
+
+/// # Slot Path
+/// 
+/// Where the slot's file is.
+pub fn slot_path(name: &str) -> PathBuf {
+    PathBuf::from(SAVE_DIR).join(format!("{}.{}", name, SAVE_EXTENSION))
+}
+
+/// # Slot Exists
+pub fn slot_exists(name: &str) -> bool {
+    slot_path(name).is_file()
+}
+
+/// # Backup Path
+/// 
+/// Where a slot's backup is. Backup 1 is the newest.
+pub fn backup_path(name: &str, backup: usize) -> PathBuf {
+    PathBuf::from(SAVE_DIR).join(format!("{}.{}.bak{}", name, SAVE_EXTENSION, backup))
+}
+
+/// # List Backups
+/// 
+/// The backups of a slot, newest first, with a summary of what's in each.
+pub fn list_backups(name: &str) -> Vec<BackupInfo> {
+    (1..=SAVE_BACKUPS_MAX).filter(|&i| backup_path(name, i).is_file())
+        .map(|backup| BackupInfo {
+            backup,
+            summary: read_file(&backup_path(name, backup)).map(|state| summarize(&state)),
+        })
+        .collect()
+}
+
+/// # Restore Backup
+/// 
+/// Replaces an unreadable slot with one of its backups, and loads it. What was in
+/// the slot is kept alongside, as .broken, in case it's wanted.
+pub fn restore_backup(name: &str, backup: usize) -> Result<CommonState, SaveError> {
+    check_slot_name(name)?;
+    let path = backup_path(name, backup);
+    let file = fs::read_to_string(&path).map_err(|err| SaveError::Io(err.to_string()))?;
+    let mut state = CommonState::load(verify_save(&file)?)?;
+    let primary = slot_path(name);
+    let _lock = WRITE_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if primary.is_file() {
+        fs::rename(&primary, primary.with_extension(format!("{}.broken", SAVE_EXTENSION)))
+            .map_err(|err| SaveError::Io(err.to_string()))?;
+    }
+    write_atomic(&primary, &file).map_err(|err| SaveError::Io(err.to_string()))?;
+    state.save_slot = name.to_string();
+    Ok(state)
+}
+
+/// # List Slots
+/// 
+/// Every save slot, sorted by name, with a summary of what's in each.
+pub fn list_slots() -> Vec<SlotInfo> {
+    let Ok(entries) = fs::read_dir(SAVE_DIR) else {
+        return vec![];
+    };
+    let mut names: Vec<String> = entries.filter_map(|entry| {
+        let path = entry.ok()?.path();
+        if path.extension()? != SAVE_EXTENSION {
+            return None;
+        }
+        let name = path.file_stem()?.to_str()?.to_string();
+        check_slot_name(&name).ok().map(|_| name)
+    }).collect();
+    names.sort();
+    names.into_iter().map(|name| {
+        let summary = read_slot(&name).map(|state| summarize(&state));
+        SlotInfo { name, summary }
+    }).collect()
+}
+
+/// The slot list's summary of a saved game.
+fn summarize(state: &CommonState) -> SlotSummary {
+    SlotSummary {
+        player_name: state.player_name.clone(),
+        money: state.money,
+        entropy: state.entropy,
+        game_length: state.game_length,
+        tainted: state.tainted,
+    }
+}
+
+/// Reads the game saved in a file, so long as it's signed.
+fn read_file(path: &Path) -> Result<CommonState, SaveError> {
+    let file = fs::read_to_string(path).map_err(|err| SaveError::Io(err.to_string()))?;
+    CommonState::load(verify_save(&file)?)
+}
+
+/// # Read Slot
+/// 
+/// Loads the game saved in a slot.
+pub fn read_slot(name: &str) -> Result<CommonState, SaveError> {
+    check_slot_name(name)?;
+    if !slot_exists(name) {
+        return Err(SaveError::NoSuchSlot(name.to_string()));
+    }
+    let mut state = read_file(&slot_path(name))?;
+    state.save_slot = name.to_string();
+    Ok(state)
+}
+
+/// # Read Slot Tainted
+/// 
+/// Loads a slot whose save has been tampered with, ignoring its signature. The run
+/// is marked as tainted, for good, so it's kept off leaderboards and achievements.
+pub fn read_slot_tainted(name: &str) -> Result<CommonState, SaveError> {
+    check_slot_name(name)?;
+    if !slot_exists(name) {
+        return Err(SaveError::NoSuchSlot(name.to_string()));
+    }
+    let file = fs::read_to_string(slot_path(name)).map_err(|err| SaveError::Io(err.to_string()))?;
+    let mut state = CommonState::load(split_signature(&file).0)?;
+    state.tainted = true;
+    state.save_slot = name.to_string();
+    Ok(state)
+}
+
+/// # Write Slot
+/// 
+/// Saves the game to a slot. If the slot's already there, it's only replaced when
+/// overwrite is set, so the player can be asked first.
+pub fn write_slot(name: &str, common_state: &mut CommonState, overwrite: bool)
+-> Result<(), SaveError> {
+    check_slot_name(name)?;
+    if !overwrite && slot_exists(name) {
+        return Err(SaveError::SlotExists(name.to_string()));
+    }
+    let save = common_state.save_str();
+    write_save(name, &save, common_state.save_backups)?;
+    common_state.save_slot = name.to_string();
+    common_state.autosave.saved(Instant::now());
+    Ok(())
+}
+
+/// # Write Save
+/// 
+/// Writes an already made save over a slot. What was there is rotated into the
+/// slot's backups first, keeping as many as asked for, and the new save is signed
+/// and written atomically.
+pub fn write_save(name: &str, save: &str, backups: usize) -> Result<(), SaveError> {
+    check_slot_name(name)?;
+    let save = sign_save(save)?;
+    let _lock = WRITE_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    fs::create_dir_all(SAVE_DIR)
+        .and_then(|_| rotate_backups(name, backups))
+        .and_then(|_| write_atomic(&slot_path(name), &save))
+        .map_err(|err| SaveError::Io(err.to_string()))
+}
+
+/// # Write Atomic
+/// 
+/// Writes the file so that it's either all there or not changed at all. It's
+/// written to a temp file, synced to disk, then renamed over the old one.
+fn write_atomic(path: &Path, contents: &str) -> io::Result<()> {
+    let temp = path.with_extension(format!("{}.tmp", SAVE_EXTENSION));
+    let mut file = File::create(&temp)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()?;
+    drop(file);
+    fs::rename(&temp, path)?;
+    // Syncing the folder makes the rename stick, where the platform allows it.
+    if let Some(dir) = path.parent()
+        && let Ok(dir) = File::open(dir) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+/// # Rotate Backups
+/// 
+/// Shifts the slot's backups along one, dropping the oldest, and copies the slot in
+/// as the newest. Backups past the number to keep are removed.
+fn rotate_backups(name: &str, keep: usize) -> io::Result<()> {
+    let keep = keep.min(SAVE_BACKUPS_MAX);
+    for i in (keep.max(1)..=SAVE_BACKUPS_MAX).rev() {
+        if backup_path(name, i).is_file() {
+            fs::remove_file(backup_path(name, i))?;
+        }
+    }
+    if keep == 0 || !slot_exists(name) {
+        return Ok(());
+    }
+    for i in (1..keep).rev() {
+        if backup_path(name, i).is_file() {
+            fs::rename(backup_path(name, i), backup_path(name, i + 1))?;
+        }
+    }
+    fs::copy(slot_path(name), backup_path(name, 1))?;
+    Ok(())
+}
+
+/// Removes all of a slot's backups.
+fn remove_backups(name: &str) -> io::Result<()> {
+    for i in 1..=SAVE_BACKUPS_MAX {
+        if backup_path(name, i).is_file() {
+            fs::remove_file(backup_path(name, i))?;
+        }
+    }
+    Ok(())
+}
+
+/// # Delete Slot
+pub fn delete_slot(name: &str) -> Result<(), SaveError> {
+    check_slot_name(name)?;
+    if !slot_exists(name) {
+        return Err(SaveError::NoSuchSlot(name.to_string()));
+    }
+    fs::remove_file(slot_path(name))
+        .and_then(|_| remove_backups(name))
+        .map_err(|err| SaveError::Io(err.to_string()))
+}
+
+/// # Copy Slot
+/// 
+/// Copies a slot to a new name, replacing what's there only when overwrite is set.
+/// The backups of what was replaced are removed, as they'd no longer match the slot.
+pub fn copy_slot(from: &str, to: &str, overwrite: bool) -> Result<(), SaveError> {
+    check_move(from, to, overwrite)?;
+    remove_backups(to)
+        .and_then(|_| fs::copy(slot_path(from), slot_path(to))).map(|_| ())
+        .map_err(|err| SaveError::Io(err.to_string()))
+}
+
+/// # Rename Slot
+/// 
+/// Renames a slot, replacing what's there only when overwrite is set. The slot's
+/// backups go with it.
+pub fn rename_slot(from: &str, to: &str, overwrite: bool) -> Result<(), SaveError> {
+    check_move(from, to, overwrite)?;
+    remove_backups(to)
+        .and_then(|_| fs::rename(slot_path(from), slot_path(to)))
+        .and_then(|_| {
+            for i in 1..=SAVE_BACKUPS_MAX {
+                if backup_path(from, i).is_file() {
+                    fs::rename(backup_path(from, i), backup_path(to, i))?;
+                }
+            }
+            Ok(())
+        })
+        .map_err(|err| SaveError::Io(err.to_string()))
+}
+
+/// Checks a slot can be copied or renamed to another.
+fn check_move(from: &str, to: &str, overwrite: bool) -> Result<(), SaveError> {
+    check_slot_name(from)?;
+    check_slot_name(to)?;
+    if !slot_exists(from) {
+        return Err(SaveError::NoSuchSlot(from.to_string()));
+    }
+    if from == to {
+        return Err(SaveError::SameSlot);
+    }
+    if !overwrite && slot_exists(to) {
+        return Err(SaveError::SlotExists(to.to_string()));
+    }
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use std::{fs::{self, OpenOptions}, io::{ErrorKind, Write}, path::PathBuf, sync::OnceLock};
+
+use hmac_sha256::HMAC;
+use rand::RngCore;
+
+use crate::{save::{SaveError, SaveFile, SIGNED_SAVE_VERSION}, save_slots::SAVE_DIR};
+
+/// The file in the save folder holding this install's signing key.
+pub const KEY_FILE: &str = "install.key";
+/// What the signature is put under, at the end of a save.
+const SIGNATURE_HEADER: &str = "\n[signature]\nhmac = ";
+
+/// The key, once it's been read or made.
+static INSTALL_KEY: OnceLock<[u8; 32]> = OnceLock::new();
+
+/// # Install Key
+/// 
+/// The key saves are signed with. Each install makes its own the first time it's
+/// needed, so a save can't be re-signed after editing it without digging the key
+/// out of the save folder.
+pub fn install_key() -> Result<[u8; 32], SaveError> {
+    if let Some(key) = INSTALL_KEY.get() {
+        return Ok(*key);
+    }
+    let path = PathBuf::from(SAVE_DIR).join(KEY_FILE);
+    fs::create_dir_all(SAVE_DIR).map_err(|err| SaveError::Io(err.to_string()))?;
+    let mut key = [0u8; 32];
+    rand::rng().fill_bytes(&mut key);
+    match OpenOptions::new().write(true).create_new(true).open(&path) {
+        Ok(mut file) => {
+            file.write_all(to_hex(&key).as_bytes())
+                .and_then(|_| file.sync_all())
+                .map_err(|err| SaveError::Io(err.to_string()))?;
+        },
+        // Someone else made it first, so theirs is the key.
+        Err(err) if err.kind() == ErrorKind::AlreadyExists => {
+            let hex = fs::read_to_string(&path).map_err(|err| SaveError::Io(err.to_string()))?;
+            key = from_hex(hex.trim()).ok_or(SaveError::BadInstallKey)?;
+        },
+        Err(err) => return Err(SaveError::Io(err.to_string())),
+    }
+    Ok(*INSTALL_KEY.get_or_init(|| key))
+}
+
+/// # Sign Save
+/// 
+/// Adds a [signature] section to the end of the save, an HMAC-SHA256 of everything
+/// before it under the install key.
+pub fn sign_save(save: &str) -> Result<String, SaveError> {
+    let mac = HMAC::mac(save.as_bytes(), install_key()?);
+    Ok(format!("{}{}{}\n", save, SIGNATURE_HEADER, to_hex(&mac)))
+}
+
+/// # Verify Save
+/// 
+/// Checks a signed save wasn't changed outside the game, returning the save
+/// without its signature. A save from before signing is only let through on an
+/// install that's never signed anything, the first run after updating, and it's
+/// signed the next time it's written. Any other save with no signature is refused,
+/// as there's no telling what's been done to it.
+pub fn verify_save(text: &str) -> Result<&str, SaveError> {
+    let (save, signature) = split_signature(text);
+    let Some(signature) = signature else {
+        return if predates_signing(save) && !has_install_key() {
+            Ok(save)
+        } else {
+            Err(SaveError::Unsigned)
+        };
+    };
+    let expected = from_hex(signature).ok_or(SaveError::Tampered)?;
+    if HMAC::verify(save.as_bytes(), install_key()?, &expected) {
+        Ok(save)
+    } else {
+        Err(SaveError::Tampered)
+    }
+}
+
+/// # Has Install Key
+/// 
+/// Whether this install has its signing key yet, which it makes the first time it
+/// signs a save.
+pub fn has_install_key() -> bool {
+    INSTALL_KEY.get().is_some() || PathBuf::from(SAVE_DIR).join(KEY_FILE).is_file()
+}
+
+/// # Predates Signing
+/// 
+/// Whether the save is from before saves were signed, an old CSV save or a version
+/// older than signing. A save that can't be read is let through too, for loading it
+/// to say what's wrong with it.
+pub fn predates_signing(save: &str) -> bool {
+    !SaveFile::is_save_file(save)
+        || SaveFile::parse(save).map_or(true, |file| file.version < SIGNED_SAVE_VERSION)
+}
+
+/// # Split Signature
+/// 
+/// The save and its signature, if it has one.
+pub fn split_signature(text: &str) -> (&str, Option<&str>) {
+    match text.rfind(SIGNATURE_HEADER) {
+        Some(split) => (&text[..split], Some(text[split + SIGNATURE_HEADER.len()..].trim())),
+        None => (text, None),
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<[u8; 32]> {
+    if hex.len() != 64 {
+        return None;
+    }
+    let mut bytes = [0u8; 32];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(hex.get(i * 2..i * 2 + 2)?, 16).ok()?;
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A signed save, under a test key so nothing's read from or made in the save folder.
+    fn signed(save: &str) -> String {
+        let _ = INSTALL_KEY.set([7; 32]);
+        sign_save(save).unwrap()
+    }
+
+    const SAVE: &str = "# Improbability Machine save\nversion = 3\n\n[common]\nmoney = 960f\n";
+
+    #[test]
+    fn signed_saves_verify() {
+        let text = signed(SAVE);
+        assert_eq!(verify_save(&text), Ok(SAVE));
+        assert_eq!(split_signature(&text).0, SAVE);
+    }
+
+    #[test]
+    fn edited_saves_are_tampered() {
+        let text = signed(SAVE).replace("960f", "96000f");
+        assert_eq!(verify_save(&text), Err(SaveError::Tampered));
+        let (save, _) = split_signature(&text);
+        let text = format!("{}\n[signature]\nhmac = {}\n", save, "0".repeat(64));
+        assert_eq!(verify_save(&text), Err(SaveError::Tampered));
+        let text = format!("{}\n[signature]\nhmac = not hex\n", save);
+        assert_eq!(verify_save(&text), Err(SaveError::Tampered));
+    }
+
+    #[test]
+    fn edited_saves_passed_off_as_older_are_unsigned() {
+        let text = signed(SAVE);
+        // Edit the money, strip the signature, and claim it's from before signing.
+        let (save, _) = split_signature(&text);
+        let doctored = save.replace("960f", "96000f").replace("version = 3", "version = 2");
+        assert!(predates_signing(&doctored));
+        let err = verify_save(&doctored).unwrap_err();
+        assert_eq!(err, SaveError::Unsigned);
+        assert!(err.is_tampered());
+        // Old CSV saves too, once this install has signed something.
+        assert_eq!(verify_save("Ada,96000,100,0,60"), Err(SaveError::Unsigned));
+    }
+}
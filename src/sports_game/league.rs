@@ -0,0 +1,779 @@
+use std::{cmp::{Ordering, Reverse}, time::{Duration, Instant, SystemTime}};
+
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{game::{binomial, entropy, GameCommonData}, money::Money, save::{SaveError, Section}};
+
+/// The number of teams in the league.
+pub const LEAGUE_TEAMS: usize = 8;
+/// How much game time passes between each round of matches.
+pub const ROUND_LENGTH: Duration = Duration::from_secs(120);
+/// How many chances to score each team gets in a match. Each one is worth a point.
+pub const POSSESSIONS: u32 = 12;
+/// The rating bonus the home team gets.
+pub const HOME_EDGE: f64 = 50.0;
+/// The share of each bet the book returns on average.
+pub const SPORTS_RETURN: f64 = 0.92;
+/// How many seasons are simulated to price a futures bet.
+pub const FUTURES_SIMS: usize = 2000;
+/// Points in the standings for a win and for a draw.
+const WIN_POINTS: u32 = 3;
+const DRAW_POINTS: u32 = 1;
+
+/// The names teams are drawn from when a league is generated.
+const TEAM_NAMES: [&str; 12] = ["Ashford Anchors", "Bramble Badgers", "Coldwater Comets",
+    "Dunmore Drakes", "Eastleigh Eagles", "Fenwick Foxes", "Greyhaven Giants",
+    "Holloway Hornets", "Ironbridge Imps", "Jarrow Jackals", "Kingsmere Knights",
+    "Lowfield Larks"];
+
+/// # Team
+/// 
+/// A team in the league, with its rating and record for the season.
+#[derive(Debug, Clone)]
+pub struct Team {
+    pub name: String,
+    /// How good the team is. Around 1500 for an average team.
+    pub rating: f64,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    pub points_for: u32,
+    pub points_against: u32,
+}
+
+impl Team {
+    /// # Table Points
+    /// 
+    /// The team's points in the standings.
+    pub fn table_points(&self) -> u32 {
+        self.wins * WIN_POINTS + self.draws * DRAW_POINTS
+    }
+}
+
+/// # Match Result
+/// 
+/// Who won a match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchResult {
+    Home,
+    Draw,
+    Away,
+}
+
+impl MatchResult {
+    /// # From Margin
+    /// 
+    /// The result for a given home margin (home score - away score).
+    pub fn from_margin(margin: i32) -> Self {
+        if margin > 0 {
+            MatchResult::Home
+        } else if margin < 0 {
+            MatchResult::Away
+        } else {
+            MatchResult::Draw
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "h" => Some(MatchResult::Home),
+            "d" => Some(MatchResult::Draw),
+            "a" => Some(MatchResult::Away),
+            _ => None,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            MatchResult::Home => "h",
+            MatchResult::Draw => "d",
+            MatchResult::Away => "a",
+        }
+    }
+}
+
+/// # Match
+/// 
+/// A single scheduled match of the season.
+#[derive(Debug, Clone)]
+pub struct Match {
+    /// The round the match is played in. Round 0 is played one round length into
+    /// the season.
+    pub round: u32,
+    /// The index of the home team.
+    pub home: usize,
+    /// The index of the away team.
+    pub away: usize,
+    /// The final (home, away) score, once played.
+    pub score: Option<(u32, u32)>,
+    /// The result entropy has been spent to force, if any.
+    pub tilt: Option<MatchResult>,
+}
+
+/// # Bet Kind
+/// 
+/// The kinds of bets the book takes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BetKind {
+    /// The result of a single match.
+    Moneyline { game: usize, pick: MatchResult },
+    /// The home team's margin against a half point line. If home is true, the bet is
+    /// on the home margin being over the line, otherwise under.
+    Spread { game: usize, line: f64, home: bool },
+    /// A team finishing the season top of the table.
+    Future { team: usize },
+}
+
+/// # Sports Bet
+/// 
+/// A bet placed with the book. The stake is taken when placed, and the payout rate
+/// is locked in at the odds when it was placed.
+#[derive(Debug, Clone)]
+pub struct SportsBet {
+    pub kind: BetKind,
+    pub stake: Money,
+    pub payout: f64,
+    /// The chance of the bet winning, at the odds when it was placed.
+    pub chance: f64,
+}
+
+/// # League Update
+/// 
+/// What happened when the league was brought up to the current game time.
+#[derive(Debug, Default)]
+pub struct LeagueUpdate {
+    /// The money paid out by settled bets.
+    pub payout: Money,
+    /// The entropy harvested from the untilted results the player bet on, once each,
+    /// and from futures won on teams that played no tilted matches.
+    pub entropy: f64,
+    /// What happened, for the player.
+    pub messages: Vec<String>,
+}
+
+/// # League
+/// 
+/// A generated league of teams playing a double round robin season. Rounds are played
+/// as game time passes, and the book takes moneyline, spread, and season futures bets.
+#[derive(Debug)]
+pub struct League {
+    /// Which season this is, starting at 1.
+    pub season: u32,
+    /// The game time the current season started at.
+    pub season_start: Duration,
+    pub teams: Vec<Team>,
+    /// The season schedule, in round order.
+    pub matches: Vec<Match>,
+    /// Bets still waiting to be settled.
+    pub bets: Vec<SportsBet>,
+    /// Shared common Data
+    pub base: GameCommonData,
+}
+
+impl League {
+    /// # Generate
+    /// 
+    /// Generates a new league, with its first season starting at the given game time.
+    pub fn generate<R: Rng>(rng: &mut R, now: Duration) -> Self {
+        let mut names = TEAM_NAMES.to_vec();
+        names.shuffle(rng);
+        let teams = names.into_iter().take(LEAGUE_TEAMS).map(|name| Team {
+            name: name.to_string(),
+            rating: 1500.0 + rng.random_range(-150.0..150.0),
+            wins: 0,
+            draws: 0,
+            losses: 0,
+            points_for: 0,
+            points_against: 0,
+        }).collect();
+        let mut league = Self {
+            season: 0,
+            season_start: now,
+            teams,
+            matches: vec![],
+            bets: vec![],
+            base: GameCommonData::new("Sportsbook".to_string(), Money::from_pence(1), Money::from_pence(200),
+                0.0, Duration::from_secs(300)),
+        };
+        league.new_season(rng, now);
+        league
+    }
+
+    /// # New Season
+    /// 
+    /// Resets the standings, drifts the ratings a bit, and builds a new schedule.
+    fn new_season<R: Rng>(&mut self, rng: &mut R, now: Duration) {
+        self.season += 1;
+        self.season_start = now;
+        for team in self.teams.iter_mut() {
+            if self.season > 1 {
+                team.rating += rng.random_range(-50.0..50.0);
+            }
+            team.wins = 0;
+            team.draws = 0;
+            team.losses = 0;
+            team.points_for = 0;
+            team.points_against = 0;
+        }
+        self.matches = League::schedule(self.teams.len());
+    }
+
+    /// # Schedule
+    /// 
+    /// A double round robin, built with the circle method. Every team plays every
+    /// other team once at home and once away.
+    fn schedule(teams: usize) -> Vec<Match> {
+        let mut order: Vec<usize> = (0..teams).collect();
+        let rounds = teams as u32 - 1;
+        let mut matches = vec![];
+        for round in 0..rounds {
+            for i in 0..teams / 2 {
+                let (a, b) = (order[i], order[teams - 1 - i]);
+                let (home, away) = if round % 2 == 0 { (a, b) } else { (b, a) };
+                matches.push(Match { round, home, away, score: None, tilt: None });
+            }
+            order[1..].rotate_right(1);
+        }
+        let second_half: Vec<Match> = matches.iter().map(|m| Match {
+            round: m.round + rounds,
+            home: m.away,
+            away: m.home,
+            score: None,
+            tilt: None,
+        }).collect();
+        matches.extend(second_half);
+        matches
+    }
+
+    /// # Rounds
+    /// 
+    /// How many rounds are in a season.
+    pub fn rounds(&self) -> u32 {
+        (self.teams.len() as u32 - 1) * 2
+    }
+
+    /// # Round Time
+    /// 
+    /// The game time the given round is played at.
+    pub fn round_time(&self, round: u32) -> Duration {
+        self.season_start + ROUND_LENGTH * (round + 1)
+    }
+
+    /// # Next Round
+    /// 
+    /// The next round to be played. If the season is done, returns None.
+    pub fn next_round(&self) -> Option<u32> {
+        self.matches.iter().find(|m| m.score.is_none()).map(|m| m.round)
+    }
+
+    /// # Score Chance
+    /// 
+    /// The chance each team scores on a possession. The rating difference (with the
+    /// home edge) shifts the home team's chance up from 1/2, and the away team's down.
+    pub fn score_chance(&self, game: usize) -> (f64, f64) {
+        let m = &self.matches[game];
+        let diff = self.teams[m.home].rating + HOME_EDGE - self.teams[m.away].rating;
+        let home = (0.5 + diff / 1000.0).clamp(0.2, 0.8);
+        (home, 1.0 - home)
+    }
+
+    /// # Margin Chances
+    /// 
+    /// The exact chance of every home margin, from -POSSESSIONS to +POSSESSIONS,
+    /// indexed by margin + POSSESSIONS. Each score is a binomial over the possessions.
+    pub fn margin_chances(&self, game: usize) -> Vec<f64> {
+        let (home, away) = self.score_chance(game);
+        let home_scores = binomial(POSSESSIONS, home);
+        let away_scores = binomial(POSSESSIONS, away);
+        let mut margins = vec![0.0; POSSESSIONS as usize * 2 + 1];
+        for (h, ph) in home_scores.iter().enumerate() {
+            for (a, pa) in away_scores.iter().enumerate() {
+                margins[h + POSSESSIONS as usize - a] += ph * pa;
+            }
+        }
+        margins
+    }
+
+    /// # Result Chance
+    /// 
+    /// The exact chance of a match ending in the given result.
+    pub fn result_chance(&self, game: usize, result: MatchResult) -> f64 {
+        self.margin_chances(game).iter().enumerate()
+            .filter(|(i, _)| MatchResult::from_margin(*i as i32 - POSSESSIONS as i32) == result)
+            .map(|(_, p)| p)
+            .sum()
+    }
+
+    /// # Spread Line
+    /// 
+    /// The half point line for the home margin closest to an even bet.
+    pub fn spread_line(&self, game: usize) -> f64 {
+        let margins = self.margin_chances(game);
+        let mut over = 1.0;
+        let mut best = (f64::MAX, 0.0);
+        for (i, p) in margins.iter().enumerate() {
+            over -= p;
+            let line = i as f64 - POSSESSIONS as f64 + 0.5;
+            if (over - 0.5).abs() < best.0 {
+                best = ((over - 0.5).abs(), line);
+            }
+        }
+        best.1
+    }
+
+    /// # Spread Chance
+    /// 
+    /// The exact chance of the home margin going over (or under) the line.
+    pub fn spread_chance(&self, game: usize, line: f64, home: bool) -> f64 {
+        let over: f64 = self.margin_chances(game).iter().enumerate()
+            .filter(|(i, _)| *i as f64 - POSSESSIONS as f64 > line)
+            .map(|(_, p)| p)
+            .sum();
+        if home { over } else { 1.0 - over }
+    }
+
+    /// # Future Chances
+    /// 
+    /// The chance of each team finishing top of the table, estimated by simulating
+    /// the rest of the season FUTURES_SIMS times.
+    /// 
+    /// Every team gets half a title added, so long shots that never won a simulated
+    /// season are still priced instead of being impossible.
+    pub fn future_chances<R: Rng>(&self, rng: &mut R) -> Vec<f64> {
+        let mut titles = vec![0; self.teams.len()];
+        for _ in 0..FUTURES_SIMS {
+            let mut points: Vec<(u32, i64)> = self.teams.iter()
+                .map(|t| (t.table_points(), t.points_for as i64 - t.points_against as i64))
+                .collect();
+            for (game, m) in self.matches.iter().enumerate() {
+                if m.score.is_some() {
+                    continue;
+                }
+                let (h, a) = self.sample_score(rng, game);
+                League::tally(&mut points, m.home, m.away, h, a);
+            }
+            titles[League::table_leader(&points)] += 1;
+        }
+        let total = FUTURES_SIMS as f64 + 0.5 * self.teams.len() as f64;
+        titles.iter().map(|t| (*t as f64 + 0.5) / total).collect()
+    }
+
+    /// Adds a result to a running (points, difference) table.
+    fn tally(points: &mut [(u32, i64)], home: usize, away: usize, h: u32, a: u32) {
+        points[home].1 += h as i64 - a as i64;
+        points[away].1 += a as i64 - h as i64;
+        match MatchResult::from_margin(h as i32 - a as i32) {
+            MatchResult::Home => points[home].0 += WIN_POINTS,
+            MatchResult::Away => points[away].0 += WIN_POINTS,
+            MatchResult::Draw => {
+                points[home].0 += DRAW_POINTS;
+                points[away].0 += DRAW_POINTS;
+            },
+        }
+    }
+
+    /// The team top of a (points, difference) table. Ties go to the earliest team.
+    fn table_leader(points: &[(u32, i64)]) -> usize {
+        let mut leader = 0;
+        for (i, p) in points.iter().enumerate() {
+            if *p > points[leader] {
+                leader = i;
+            }
+        }
+        leader
+    }
+
+    /// # Leader
+    /// 
+    /// The team currently top of the table.
+    pub fn leader(&self) -> usize {
+        let points: Vec<(u32, i64)> = self.teams.iter()
+            .map(|t| (t.table_points(), t.points_for as i64 - t.points_against as i64))
+            .collect();
+        League::table_leader(&points)
+    }
+
+    /// # Standings
+    /// 
+    /// The team indices in table order.
+    pub fn standings(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.teams.len()).collect();
+        order.sort_by_key(|&i| {
+            let t = &self.teams[i];
+            Reverse((t.table_points(), t.points_for as i64 - t.points_against as i64))
+        });
+        order
+    }
+
+    /// Samples a score for a match from its possession chances.
+    fn sample_score<R: Rng>(&self, rng: &mut R, game: usize) -> (u32, u32) {
+        let (home, away) = self.score_chance(game);
+        let h = (0..POSSESSIONS).filter(|_| rng.random_bool(home)).count() as u32;
+        let a = (0..POSSESSIONS).filter(|_| rng.random_bool(away)).count() as u32;
+        (h, a)
+    }
+
+    /// # Tilt Cost
+    /// 
+    /// The entropy cost of forcing a match to the given result, the surprisal of
+    /// that result.
+    pub fn tilt_cost(&self, game: usize, result: MatchResult) -> Result<f64, String> {
+        let m = self.matches.get(game).ok_or(String::from("No such match."))?;
+        if m.score.is_some() {
+            return Err(String::from("That match has already been played."));
+        }
+        if m.tilt.is_some() {
+            return Err(String::from("That match is already tilted."));
+        }
+        Ok(entropy(self.result_chance(game, result)))
+    }
+
+    /// # Tilt
+    /// 
+    /// Forces a match to end with the given result. Returns the cost.
+    /// 
+    /// Does not spend the entropy, only reports the cost.
+    pub fn tilt(&mut self, game: usize, result: MatchResult) -> Result<f64, String> {
+        let cost = self.tilt_cost(game, result)?;
+        self.matches[game].tilt = Some(result);
+        Ok(cost)
+    }
+
+    /// # Odds
+    /// 
+    /// The chance and locked payout rate for a bet, at the current odds.
+    pub fn odds<R: Rng>(&self, rng: &mut R, kind: &BetKind) -> Result<(f64, f64), String> {
+        let chance = match kind {
+            BetKind::Moneyline { game, pick } => {
+                self.open_match(*game)?;
+                self.result_chance(*game, *pick)
+            },
+            BetKind::Spread { game, line, home } => {
+                self.open_match(*game)?;
+                self.spread_chance(*game, *line, *home)
+            },
+            BetKind::Future { team } => {
+                if *team >= self.teams.len() {
+                    return Err(String::from("No such team."));
+                }
+                self.future_chances(rng)[*team]
+            },
+        };
+        if chance <= 0.0 {
+            return Err(String::from("The book won't take a bet that can't win."));
+        }
+        Ok((chance, SPORTS_RETURN / chance))
+    }
+
+    /// Checks the match exists and is still open for bets.
+    fn open_match(&self, game: usize) -> Result<(), String> {
+        let m = self.matches.get(game).ok_or(String::from("No such match."))?;
+        if m.score.is_some() {
+            Err(String::from("That match has already been played."))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// # Place Bet
+    /// 
+    /// Places a bet at the current odds. Does not take the stake, only records it.
+    pub fn place_bet<R: Rng>(&mut self, rng: &mut R, kind: BetKind, stake: Money)
+    -> Result<SportsBet, String> {
+        let (chance, payout) = self.odds(rng, &kind)?;
+        let bet = SportsBet { kind, stake, payout, chance };
+        self.bets.push(bet.clone());
+        Ok(bet)
+    }
+
+    /// # Advance
+    /// 
+    /// Plays every round scheduled at or before the given game time, settling the bets
+    /// on them. When the season finishes the futures are settled and a new season
+    /// starts.
+    pub fn advance<R: Rng>(&mut self, rng: &mut R, now: Duration) -> LeagueUpdate {
+        let mut update = LeagueUpdate::default();
+        while let Some(round) = self.next_round() {
+            if self.round_time(round) > now {
+                break;
+            }
+            for game in 0..self.matches.len() {
+                if self.matches[game].round == round {
+                    self.play_match(rng, game, &mut update);
+                }
+            }
+            if self.next_round().is_none() {
+                self.finish_season(rng, &mut update);
+            }
+        }
+        update
+    }
+
+    /// Plays a match, honoring any tilt, and settles the bets on it.
+    fn play_match<R: Rng>(&mut self, rng: &mut R, game: usize, update: &mut LeagueUpdate) {
+        let tilt = self.matches[game].tilt;
+        let (mut h, mut a) = self.sample_score(rng, game);
+        if let Some(result) = tilt {
+            while MatchResult::from_margin(h as i32 - a as i32) != result {
+                (h, a) = self.sample_score(rng, game);
+            }
+        }
+        let result = MatchResult::from_margin(h as i32 - a as i32);
+        let surprisal = entropy(self.result_chance(game, result));
+        let (home, away) = (self.matches[game].home, self.matches[game].away);
+        for (team, scored, conceded) in [(home, h, a), (away, a, h)] {
+            let t = &mut self.teams[team];
+            t.points_for += scored;
+            t.points_against += conceded;
+            match scored.cmp(&conceded) {
+                Ordering::Greater => t.wins += 1,
+                Ordering::Less => t.losses += 1,
+                Ordering::Equal => t.draws += 1,
+            }
+        }
+        self.matches[game].score = Some((h, a));
+        update.messages.push(format!("{} {} - {} {}", self.teams[home].name, h, a,
+            self.teams[away].name));
+
+        let margin = h as f64 - a as f64;
+        let mut open = vec![];
+        let mut backed = false;
+        for bet in self.bets.drain(..) {
+            let won = match bet.kind {
+                BetKind::Moneyline { game: g, pick } if g == game => Some(pick == result),
+                BetKind::Spread { game: g, line, home } if g == game =>
+                    Some((margin > line) == home),
+                _ => None,
+            };
+            match won {
+                Some(won) => {
+                    let winnings = if won {
+                        bet.stake.mul_ratio(self.base.suspicion_payout(bet.payout))
+                    } else { Money::ZERO };
+                    self.base.record_outcome(bet.chance, bet.stake.mul_ratio(SPORTS_RETURN), won,
+                        winnings);
+                    update.payout += winnings;
+                    backed = true;
+                },
+                None => open.push(bet),
+            }
+        }
+        self.bets = open;
+        // The result is harvested once, however many bets were on it, unless it was
+        // forced.
+        if backed && tilt.is_none() {
+            update.entropy += surprisal;
+        }
+    }
+
+    /// Settles the futures and starts the next season.
+    fn finish_season<R: Rng>(&mut self, rng: &mut R, update: &mut LeagueUpdate) {
+        let champion = self.leader();
+        update.messages.push(format!("{} win season {}!", self.teams[champion].name,
+            self.season));
+        let mut open = vec![];
+        // The least surprising chance the champion was backed at, if it was.
+        let mut backed: Option<f64> = None;
+        for bet in self.bets.drain(..) {
+            if let BetKind::Future { team } = bet.kind {
+                let won = team == champion;
+                let winnings = if won {
+                    bet.stake.mul_ratio(self.base.suspicion_payout(bet.payout))
+                } else { Money::ZERO };
+                self.base.record_outcome(bet.chance, bet.stake.mul_ratio(SPORTS_RETURN), won,
+                    winnings);
+                update.payout += winnings;
+                if won {
+                    backed = Some(backed.map_or(bet.chance, |chance| chance.max(bet.chance)));
+                }
+            } else {
+                open.push(bet);
+            }
+        }
+        self.bets = open;
+        // A title the player tilted any of the champion's matches toward wasn't won 
+        // naturally, so it's not harvested.
+        let tilted = self.matches.iter()
+            .any(|m| m.tilt.is_some() && (m.home == champion || m.away == champion));
+        if let Some(chance) = backed.filter(|_| !tilted) {
+            update.entropy += entropy(chance);
+        }
+        let next_start = self.round_time(self.rounds() - 1);
+        self.new_season(rng, next_start);
+    }
+
+    /// # Save Section
+    /// 
+    /// Writes the league to a [league] save section, the book's game data followed by
+    /// a comma separated record per season, team, match and bet.
+    pub fn save_section(&self, now: Instant, wall_now: SystemTime) -> Section {
+        let mut section = Section::new("league");
+        self.base.save_into(&mut section, now, wall_now);
+        section.set("season", format!("{},{},{}", self.season, self.season_start.as_secs_f64(),
+            self.base.suspicion));
+        for t in self.teams.iter() {
+            section.set("team", format!("{},{},{},{},{},{},{}", t.name, t.rating, t.wins, t.draws,
+                t.losses, t.points_for, t.points_against));
+        }
+        for m in self.matches.iter() {
+            let score = m.score.map_or(String::from("-,-"), |(h, a)| format!("{},{}", h, a));
+            let tilt = m.tilt.map_or("-", |t| t.code());
+            section.set("match", format!("{},{},{},{},{}", m.round, m.home, m.away, score, tilt));
+        }
+        for b in self.bets.iter() {
+            let kind = match &b.kind {
+                BetKind::Moneyline { game, pick } => format!("moneyline,{},{}", game, pick.code()),
+                BetKind::Spread { game, line, home } => format!("spread,{},{},{}", game, line, home),
+                BetKind::Future { team } => format!("future,{}", team),
+            };
+            section.set("bet", format!("{},{},{},{}", b.stake.save_str(), b.payout, kind, b.chance));
+        }
+        section
+    }
+
+    /// # Load Section
+    /// 
+    /// Reads the league from a section written by save_section.
+    pub fn load_section(section: &Section, now: Instant, wall_now: SystemTime)
+    -> Result<Self, SaveError> {
+        let lines: Vec<String> = section.entries.iter()
+            .filter(|(key, _)| ["season", "team", "match", "bet"].contains(&key.as_str()))
+            .map(|(key, value)| format!("{},{}", key, value))
+            .collect();
+        let lines: Vec<&str> = lines.iter().map(|l| l.as_str()).collect();
+        let mut league = Self::load_str(&lines).ok_or(SaveError::BadLeague)?;
+        league.base.restore_from(&GameCommonData::load_section(section, now, wall_now)?);
+        Ok(league)
+    }
+
+    /// # Load Str(ing)
+    /// 
+    /// Loads a league from lines of comma separated records, as the old CSV saves 
+    /// wrote them. Returns None if they're malformed, or point at teams or matches
+    /// that aren't there.
+    pub fn load_str(lines: &[&str]) -> Option<Self> {
+        let mut league = Self {
+            season: 0,
+            season_start: Duration::ZERO,
+            teams: vec![],
+            matches: vec![],
+            bets: vec![],
+            base: GameCommonData::new("Sportsbook".to_string(), Money::from_pence(1), Money::from_pence(200),
+                0.0, Duration::from_secs(300)),
+        };
+        for line in lines {
+            let fields: Vec<&str> = line.split(',').collect();
+            match fields[0] {
+                "season" => {
+                    league.season = fields.get(1)?.parse().ok()?;
+                    league.season_start = Duration::try_from_secs_f64(fields.get(2)?.parse().ok()?)
+                        .ok()?;
+                    league.base.suspicion = fields.get(3)?.parse().ok()?;
+                },
+                "team" => league.teams.push(Team {
+                    name: fields.get(1)?.to_string(),
+                    rating: fields.get(2)?.parse().ok()?,
+                    wins: fields.get(3)?.parse().ok()?,
+                    draws: fields.get(4)?.parse().ok()?,
+                    losses: fields.get(5)?.parse().ok()?,
+                    points_for: fields.get(6)?.parse().ok()?,
+                    points_against: fields.get(7)?.parse().ok()?,
+                }),
+                "match" => league.matches.push(Match {
+                    round: fields.get(1)?.parse().ok()?,
+                    home: fields.get(2)?.parse().ok()?,
+                    away: fields.get(3)?.parse().ok()?,
+                    score: match (fields.get(4)?.parse().ok(), fields.get(5)?.parse().ok()) {
+                        (Some(h), Some(a)) => Some((h, a)),
+                        _ => None,
+                    },
+                    tilt: MatchResult::parse(fields.get(6)?),
+                }),
+                "bet" => {
+                    // Where the chance goes after the kind's fields.
+                    let chance_field = match *fields.get(3)? {
+                        "moneyline" => 6,
+                        "spread" => 7,
+                        _ => 5,
+                    };
+                    let kind = match *fields.get(3)? {
+                        "moneyline" => BetKind::Moneyline {
+                            game: fields.get(4)?.parse().ok()?,
+                            pick: MatchResult::parse(fields.get(5)?)?,
+                        },
+                        "spread" => BetKind::Spread {
+                            game: fields.get(4)?.parse().ok()?,
+                            line: fields.get(5)?.parse().ok()?,
+                            home: fields.get(6)?.parse().ok()?,
+                        },
+                        "future" => BetKind::Future { team: fields.get(4)?.parse().ok()? },
+                        _ => return None,
+                    };
+                    league.bets.push(SportsBet {
+                        kind,
+                        stake: Money::load_str(fields.get(1)?)?,
+                        payout: fields.get(2)?.parse().ok()?,
+                        chance: fields.get(chance_field)?.parse().ok()?,
+                    });
+                },
+                _ => return None,
+            }
+        }
+        if league.teams.len() < 2 || league.matches.is_empty() {
+            return None;
+        }
+        // Every team and match a record points at has to be there, and every number
+        // has to be one the odds can be worked out from.
+        let (teams, games) = (league.teams.len(), league.matches.len());
+        let sound = league.base.suspicion.is_finite()
+            && league.teams.iter().all(|team| team.rating.is_finite())
+            && league.matches.iter().all(|m| m.home < teams && m.away < teams)
+            && league.bets.iter().all(|bet| bet.payout.is_finite() && bet.payout > 0.0
+                && bet.chance > 0.0 && bet.chance <= 1.0
+                && !bet.stake.is_negative()
+                && match bet.kind {
+                    BetKind::Moneyline { game, .. } => game < games,
+                    BetKind::Spread { game, line, .. } => game < games && line.is_finite(),
+                    BetKind::Future { team } => team < teams,
+                });
+        sound.then_some(league)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    /// The first match of the given round.
+    fn first_of(league: &League, round: u32) -> usize {
+        league.matches.iter().position(|m| m.round == round).unwrap()
+    }
+
+    #[test]
+    fn only_untilted_matches_bet_on_are_harvested() {
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        let mut league = League::generate(&mut rng, Duration::ZERO);
+        let update = league.advance(&mut rng, league.round_time(0));
+        assert_eq!(update.entropy, 0.0);
+
+        let game = first_of(&league, 1);
+        for pick in [MatchResult::Home, MatchResult::Away] {
+            league.place_bet(&mut rng, BetKind::Moneyline { game, pick }, Money::from_pence(1))
+                .unwrap();
+        }
+        let update = league.advance(&mut rng, league.round_time(1));
+        let (h, a) = league.matches[game].score.unwrap();
+        let result = MatchResult::from_margin(h as i32 - a as i32);
+        assert_eq!(update.entropy, entropy(league.result_chance(game, result)));
+
+        let game = first_of(&league, 2);
+        league.tilt(game, MatchResult::Home).unwrap();
+        league.place_bet(&mut rng, BetKind::Moneyline { game, pick: MatchResult::Home },
+            Money::from_pence(1)).unwrap();
+        let update = league.advance(&mut rng, league.round_time(2));
+        assert_eq!(update.entropy, 0.0);
+        assert!(update.payout > Money::ZERO);
+    }
+}
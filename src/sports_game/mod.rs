@@ -0,0 +1,2 @@
+pub mod league;
+pub mod sports_cmd;
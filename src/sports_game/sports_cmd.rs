@@ -0,0 +1,159 @@
+use std::{io::{stdin, stdout}, time::Instant};
+
+use crossterm::{style::Print, terminal, ExecutableCommand};
+use rand::Rng;
+
+use crate::{autosave::AutosaveEvent, common_state::CommonState, machine::machine::Manipulation, money::{looks_like_money, Money}, sports_game::league::{BetKind, League, MatchResult}};
+
+/// # Print League
+/// 
+/// Prints the standings, the next round's matches with their odds, and the open bets.
+fn print_league(league: &League, now_secs: f64) {
+    stdout().execute(Print(format!("Season {}\n", league.season))).unwrap();
+    stdout().execute(Print("  #  Team                   W  D  L   PF  PA  Pts\n")).unwrap();
+    for (i, team) in league.standings().into_iter().map(|i| (i, &league.teams[i])) {
+        stdout().execute(Print(format!("{:>3}  {:<20} {:>2} {:>2} {:>2}  {:>3} {:>3}  {:>3}\n", i,
+            team.name, team.wins, team.draws, team.losses, team.points_for,
+            team.points_against, team.table_points()))).unwrap();
+    }
+    if let Some(round) = league.next_round() {
+        let wait = (league.round_time(round).as_secs_f64() - now_secs).max(0.0);
+        stdout().execute(Print(format!("\nRound {} of {} in {:.0} s\n", round + 1,
+            league.rounds(), wait))).unwrap();
+        for (game, m) in league.matches.iter().enumerate().filter(|(_, m)| m.round == round) {
+            let line = league.spread_line(game);
+            stdout().execute(Print(format!(
+                "{:>3}: {} v {}\n     H {:.2}% | D {:.2}% | A {:.2}% | Home {:+.1}: Over {:.2}% Under {:.2}%{}\n",
+                game, league.teams[m.home].name, league.teams[m.away].name,
+                league.result_chance(game, MatchResult::Home) * 100.0,
+                league.result_chance(game, MatchResult::Draw) * 100.0,
+                league.result_chance(game, MatchResult::Away) * 100.0,
+                line, league.spread_chance(game, line, true) * 100.0,
+                league.spread_chance(game, line, false) * 100.0,
+                if m.tilt.is_some() { " (Tilted)" } else { "" }))).unwrap();
+        }
+    }
+    if !league.bets.is_empty() {
+        stdout().execute(Print("\nOpen Bets:\n")).unwrap();
+        for bet in league.bets.iter() {
+            let desc = match &bet.kind {
+                BetKind::Moneyline { game, pick } => format!("Match {} result {:?}", game, pick),
+                BetKind::Spread { game, line, home } => format!("Match {} home margin {} {}",
+                    game, if *home { "over" } else { "under" }, line),
+                BetKind::Future { team } => format!("{} to win season {}",
+                    league.teams[*team].name, league.season),
+            };
+            stdout().execute(Print(format!("  {} at x{:.2}: {}\n", bet.stake, bet.payout, desc)))
+                .unwrap();
+        }
+    }
+}
+
+/// # Sportsbook Screen
+/// 
+/// The book for the league. Rounds are played as game time passes, and bets are
+/// settled whenever the screen refreshes.
+pub fn sportsbook_screen<R: Rng>(common_state: &mut CommonState, rng: &mut R) {
+    stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
+    // The league is held here while the book is open, and handed back on exit.
+    let mut league = common_state.league.take()
+        .unwrap_or_else(|| League::generate(rng, common_state.game_time()));
+    let mut msg = String::new();
+    loop {
+        let now = common_state.game_time();
+        // Catch the league up to now and pay out.
+        let update = league.advance(rng, now);
+        common_state.credit_payout(update.payout, "Sportsbook payout");
+        common_state.harvest(update.entropy);
+        if !update.messages.is_empty() {
+            msg = format!("{}\n{}", update.messages.join("\n"), msg);
+        }
+        let kicked_out = league.base.kickout_update(Instant::now());
+
+        stdout().execute(Print(format!("\t\t!!!Sportsbook!!!\nCommands: M # H/D/A -> Moneyline | S # O/U -> Spread | F # -> Future on Team # | T # H/D/A -> Tilt Match | R -> Refresh | Q -> Exit | Enter £sd to change Bet\nBet Min: {} | Bet Max: {}\n",
+            league.base.bet_min, league.base.bet_max))).unwrap();
+        stdout().execute(Print(format!("Money: {}\tEntropy: {} b\tSuspicion: {}\n", common_state.money, common_state.entropy, league.base.suspicion))).unwrap();
+        stdout().execute(Print(format!("Current Bet: {}\n", league.base.current_bet))).unwrap();
+        if kicked_out {
+            stdout().execute(Print(format!("!!! Kicked Out! Come back in {:.0} s !!!\n",
+                league.base.kickout_time_remaining()))).unwrap();
+        }
+        print_league(&league, now.as_secs_f64());
+        stdout().execute(Print(format!("\n{}\n", msg))).unwrap();
+        msg = String::new();
+
+        let mut buff = String::new();
+        stdin().read_line(&mut buff).unwrap();
+        buff = buff.trim().to_lowercase();
+        let args: Vec<&str> = buff.split_whitespace().collect();
+        let command = args.first().copied().unwrap_or("");
+        let number = args.get(1).and_then(|a| a.parse::<usize>().ok());
+        let option = args.get(2).copied().unwrap_or("");
+        let amount = buff.parse::<Money>();
+        if let Ok(bet) = amount {
+            if let Err(err) = league.base.set_bet(bet, common_state.money) {
+                msg = err.to_string();
+            }
+        } else if command == "m" || command == "s" || command == "f" {
+            let kind = match (command, number) {
+                (_, None) => Err(String::from("Which match or team?")),
+                ("m", Some(game)) => MatchResult::parse(option)
+                    .map(|pick| BetKind::Moneyline { game, pick })
+                    .ok_or(String::from("Pick H, D, or A.")),
+                ("s", Some(game)) if game < league.matches.len() => match option {
+                    "o" | "u" => Ok(BetKind::Spread { game, line: league.spread_line(game),
+                        home: option == "o" }),
+                    _ => Err(String::from("Pick O or U.")),
+                },
+                ("s", Some(_)) => Err(String::from("No such match.")),
+                (_, Some(team)) => Ok(BetKind::Future { team }),
+            };
+            let stake = league.base.current_bet;
+            msg = match common_state.check_bet(&league.base, stake, Instant::now()) {
+                Err(err) => err.to_string(),
+                Ok(()) => match kind.and_then(|kind| league.place_bet(rng, kind, stake)) {
+                    Ok(bet) => {
+                        common_state.spend_money(stake, "Sportsbook bet")
+                            .expect("The stake was checked before the bet was placed.");
+                        format!("Bet {} at x{:.2}.", stake, bet.payout)
+                    },
+                    Err(err) => err,
+                },
+            };
+        } else if command == "t" {
+            let result = MatchResult::parse(option);
+            msg = match (number, result) {
+                (Some(game), Some(result)) => match league.tilt_cost(game, result) {
+                    Ok(cost) => match common_state.spend_manipulation(Manipulation::Elimination, cost,
+                        "Sportsbook tilt", rng) {
+                        Ok(made) => {
+                            if made.took_effect() {
+                                league.tilt(game, result).unwrap();
+                                league.base.add_suspicion(cost);
+                            }
+                            league.base.add_suspicion(made.suspicion_spike());
+                            if league.base.kickout_roll(rng, Instant::now()) {
+                                common_state.autosave.request(AutosaveEvent::Kickout);
+                            }
+                            made.report(&format!("Match {} tilted", game))
+                        },
+                        Err(err) => err.to_string(),
+                    },
+                    Err(err) => err,
+                },
+                _ => String::from("Tilt needs a match and H, D, or A."),
+            };
+        } else if command == "r" {
+            // Refreshing happens at the top of every loop.
+        } else if command == "q" {
+            common_state.league = Some(league);
+            return;
+        } else {
+            msg = match amount {
+                Err(err) if looks_like_money(&buff) => err,
+                _ => String::from("Invalid Command."),
+            };
+        }
+        stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
+    }
+}
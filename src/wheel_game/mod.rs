@@ -0,0 +1,2 @@
+pub mod wheel;
+pub mod wheel_cmd;
\ No newline at end of file
@@ -0,0 +1,215 @@
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+
+use crate::{game::{entropy, EarlyExit, GameCommonData}, money::Money};
+
+/// # Wheel Symbol
+/// 
+/// Something a bet can be placed on, and what it pays per unit bet, stake included.
+#[derive(Debug, Clone, Copy)]
+pub struct WheelSymbol {
+    pub name: &'static str,
+    pub payout: f64,
+}
+
+/// # Segment
+/// 
+/// One segment of the wheel. The weight is how wide it is, so the chance of the
+/// pointer landing on it is its weight over the wheel's total weight.
+#[derive(Debug, Clone, Copy)]
+pub struct Segment {
+    /// Index into WHEEL_SYMBOLS.
+    pub symbol: usize,
+    pub weight: u32,
+}
+
+/// The symbols on the wheel, and what they pay.
+pub const WHEEL_SYMBOLS: [WheelSymbol; 6] = [
+    WheelSymbol { name: "1", payout: 2.0 },
+    WheelSymbol { name: "2", payout: 3.0 },
+    WheelSymbol { name: "5", payout: 6.0 },
+    WheelSymbol { name: "10", payout: 11.0 },
+    WheelSymbol { name: "20", payout: 21.0 },
+    WheelSymbol { name: "Star", payout: 41.0 },
+];
+
+/// The segments of the wheel, in order around it. The last wraps back to the first.
+pub const WHEEL_SEGMENTS: [Segment; 24] = [
+    Segment { symbol: 5, weight: 2 },
+    Segment { symbol: 0, weight: 4 },
+    Segment { symbol: 1, weight: 5 },
+    Segment { symbol: 0, weight: 5 },
+    Segment { symbol: 2, weight: 3 },
+    Segment { symbol: 0, weight: 4 },
+    Segment { symbol: 1, weight: 4 },
+    Segment { symbol: 0, weight: 5 },
+    Segment { symbol: 3, weight: 4 },
+    Segment { symbol: 0, weight: 4 },
+    Segment { symbol: 1, weight: 5 },
+    Segment { symbol: 2, weight: 4 },
+    Segment { symbol: 4, weight: 4 },
+    Segment { symbol: 0, weight: 5 },
+    Segment { symbol: 1, weight: 5 },
+    Segment { symbol: 0, weight: 4 },
+    Segment { symbol: 2, weight: 4 },
+    Segment { symbol: 0, weight: 4 },
+    Segment { symbol: 3, weight: 4 },
+    Segment { symbol: 1, weight: 4 },
+    Segment { symbol: 0, weight: 5 },
+    Segment { symbol: 2, weight: 3 },
+    Segment { symbol: 1, weight: 5 },
+    Segment { symbol: 0, weight: 4 },
+];
+
+/// # Money Wheel
+/// 
+/// A big six wheel. Bet on a symbol, spin, and if the pointer stops on that symbol
+/// it pays out. The segments are different widths, so some symbols are far more
+/// likely than others.
+/// 
+/// Before it stops, the pointer can be nudged along the wheel one segment at a time,
+/// with each nudge costing the surprisal of the segment it's pushed onto.
+#[derive(Debug)]
+pub struct MoneyWheel {
+    /// The symbol the player is betting on.
+    pub wager: usize,
+    /// The segment the pointer is on, if the wheel has been spun.
+    pub landed: Option<usize>,
+    /// How much entropy was spent nudging the current spin.
+    pub nudged: f64,
+    /// The current state of the game.
+    pub state: WheelState,
+    /// Shared common Data
+    pub base: GameCommonData,
+}
+
+#[derive(Debug)]
+pub enum WheelState {
+    /// No active bet, the last result is shown.
+    Hold,
+    /// The wheel is slowing, and can be nudged before it stops.
+    InBet,
+}
+
+impl Default for MoneyWheel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MoneyWheel {
+    /// Create a new Money Wheel, betting on the most common symbol.
+    pub fn new() -> Self {
+        Self {
+            wager: 0,
+            landed: None,
+            nudged: 0.0,
+            state: WheelState::Hold,
+            // Walking away from a spinning wheel leaves the stake on it.
+            base: GameCommonData { early_exit: EarlyExit::Forfeit,
+                ..GameCommonData::new("Money Wheel".to_string(), Money::from_pence(1),
+                    Money::from_pence(100), 0.0, Duration::from_secs(90)) },
+        }
+    }
+
+    /// # Total Weight
+    /// 
+    /// The combined weight of every segment on the wheel.
+    pub fn total_weight() -> u32 {
+        WHEEL_SEGMENTS.iter().map(|s| s.weight).sum()
+    }
+
+    /// # Segment Chance
+    /// 
+    /// The chance of the pointer landing on the given segment.
+    pub fn segment_chance(segment: usize) -> f64 {
+        WHEEL_SEGMENTS[segment].weight as f64 / MoneyWheel::total_weight() as f64
+    }
+
+    /// # Symbol Chance
+    /// 
+    /// The chance of the pointer landing on any segment of the given symbol.
+    pub fn symbol_chance(symbol: usize) -> f64 {
+        let weight: u32 = WHEEL_SEGMENTS.iter()
+            .filter(|s| s.symbol == symbol)
+            .map(|s| s.weight)
+            .sum();
+        weight as f64 / MoneyWheel::total_weight() as f64
+    }
+
+    /// # Expected Return
+    /// 
+    /// The long run return per unit bet on the symbol.
+    pub fn expected_return(symbol: usize) -> f64 {
+        MoneyWheel::symbol_chance(symbol) * WHEEL_SYMBOLS[symbol].payout
+    }
+
+    /// # Parse Symbol
+    /// 
+    /// Finds the symbol with the given name, ignoring case.
+    pub fn parse_symbol(s: &str) -> Option<usize> {
+        WHEEL_SYMBOLS.iter().position(|sym| sym.name.eq_ignore_ascii_case(s))
+    }
+
+    /// # Neighbour
+    /// 
+    /// The segment the given number of steps around the wheel, wrapping around.
+    pub fn neighbour(segment: usize, steps: i32) -> usize {
+        (segment as i32 + steps).rem_euclid(WHEEL_SEGMENTS.len() as i32) as usize
+    }
+
+    /// # Spin
+    /// 
+    /// Spins the wheel, picking where the pointer lands weighted by segment width.
+    pub fn spin<R: Rng>(&mut self, rng: &mut R) {
+        let mut roll = rng.random_range(0..MoneyWheel::total_weight());
+        let mut landed = 0;
+        for (i, segment) in WHEEL_SEGMENTS.iter().enumerate() {
+            landed = i;
+            if roll < segment.weight {
+                break;
+            }
+            roll -= segment.weight;
+        }
+        self.landed = Some(landed);
+        self.nudged = 0.0;
+        self.base.bet_start = Some(Instant::now());
+    }
+
+    /// # Nudge Cost
+    /// 
+    /// The entropy cost to nudge the pointer one segment in the given direction, the
+    /// surprisal of landing on the segment it's pushed onto.
+    pub fn nudge_cost(&self, forward: bool) -> Option<f64> {
+        self.landed.map(|landed| {
+            let target = MoneyWheel::neighbour(landed, if forward { 1 } else { -1 });
+            entropy(MoneyWheel::segment_chance(target))
+        })
+    }
+
+    /// # Nudge
+    /// 
+    /// Nudges the pointer one segment, returning the entropy it cost.
+    pub fn nudge(&mut self, forward: bool) -> Option<f64> {
+        let cost = self.nudge_cost(forward)?;
+        let landed = self.landed?;
+        self.landed = Some(MoneyWheel::neighbour(landed, if forward { 1 } else { -1 }));
+        self.nudged += cost;
+        Some(cost)
+    }
+
+    /// # Result
+    /// 
+    /// The symbol the pointer is on, if the wheel has been spun.
+    pub fn result(&self) -> Option<usize> {
+        self.landed.map(|landed| WHEEL_SEGMENTS[landed].symbol)
+    }
+
+    /// # Result Bits
+    /// 
+    /// The surprisal of the symbol the pointer is on.
+    pub fn result_bits(&self) -> f64 {
+        self.result().map_or(0.0, |symbol| entropy(MoneyWheel::symbol_chance(symbol)))
+    }
+}
@@ -0,0 +1,189 @@
+use std::{io::{stdin, stdout}, time::Instant};
+
+use crossterm::{style::Print, terminal, ExecutableCommand};
+use rand::Rng;
+
+use crate::{autosave::AutosaveEvent, common_state::CommonState, game::BetResult, machine::machine::Manipulation, money::{looks_like_money, Money}, wheel_game::wheel::{MoneyWheel, WheelState, WHEEL_SEGMENTS, WHEEL_SYMBOLS}};
+
+/// How many segments either side of the pointer are shown while it's slowing.
+const WHEEL_VIEW: i32 = 3;
+
+pub fn select_screen<R: Rng>(common_state: &mut CommonState, wheel: &mut MoneyWheel,
+rng: &mut R) -> Option<WheelState> {
+    match wheel.state {
+        WheelState::Hold => {
+            holding_screen(common_state, wheel)
+        },
+        WheelState::InBet => {
+            in_bet(common_state, wheel, rng)
+        },
+    }
+}
+
+/// # Print Symbols
+/// 
+/// Prints each symbol with its chance and payout.
+fn print_symbols() {
+    let mut table = String::from("Symbols: ");
+    for (i, symbol) in WHEEL_SYMBOLS.iter().enumerate() {
+        table += format!("{} {:.0}% -> x{} | ", symbol.name,
+            MoneyWheel::symbol_chance(i) * 100.0, symbol.payout).as_str();
+    }
+    stdout().execute(Print(format!("{}\n", table))).unwrap();
+}
+
+/// # Print Pointer
+/// 
+/// Prints the segments around the pointer, with their widths, marking the one
+/// it's on.
+fn print_pointer(wheel: &MoneyWheel) {
+    let Some(landed) = wheel.landed else {
+        return;
+    };
+    let mut view = String::new();
+    for step in -WHEEL_VIEW..=WHEEL_VIEW {
+        let segment = WHEEL_SEGMENTS[MoneyWheel::neighbour(landed, step)];
+        let name = WHEEL_SYMBOLS[segment.symbol].name;
+        if step == 0 {
+            view += format!(">[{}:{}]< ", name, segment.weight).as_str();
+        } else {
+            view += format!(" {}:{}  ", name, segment.weight).as_str();
+        }
+    }
+    stdout().execute(Print(format!("\t{}\n", view))).unwrap();
+}
+
+/// # Holding Screen
+/// 
+/// Shows the last spin, and lets the player pick their symbol and bet.
+pub fn holding_screen(common_state: &mut CommonState, wheel: &mut MoneyWheel) -> Option<WheelState> {
+    stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
+    let mut msg = String::new();
+    loop {
+        let kicked_out = wheel.base.kickout_update(Instant::now());
+        stdout().execute(Print(format!("\t\t!!!Money Wheel!!!\nCommands: B <Symbol> -> Bet on Symbol | S -> Spin | Q -> Exit | Enter £sd to change Bet\nBet Min: {} | Bet Max: {}\n",
+            wheel.base.bet_min, wheel.base.bet_max))).unwrap();
+        stdout().execute(Print(format!("Money: {}\tEntropy: {} b\tSuspicion: {}\n", common_state.money, common_state.entropy, wheel.base.suspicion))).unwrap();
+        stdout().execute(Print(format!("Current Bet: {} on {}\n", wheel.base.current_bet,
+            WHEEL_SYMBOLS[wheel.wager].name))).unwrap();
+        print_symbols();
+        if kicked_out {
+            stdout().execute(Print(format!("!!! Kicked Out! Come back in {:.0} s !!!\n",
+                wheel.base.kickout_time_remaining()))).unwrap();
+        }
+        print_pointer(wheel);
+        stdout().execute(Print(format!("{}\n", msg))).unwrap();
+        let mut buff = String::new();
+        stdin().read_line(&mut buff).unwrap();
+        buff = buff.trim().to_lowercase();
+        let amount = buff.parse::<Money>();
+        if let Ok(bet) = amount {
+            if let Err(err) = wheel.base.set_bet(bet, common_state.money) {
+                msg = err.to_string();
+            }
+        } else if let Some(name) = buff.strip_prefix("b ") {
+            match MoneyWheel::parse_symbol(name.trim()) {
+                Some(symbol) => {
+                    wheel.wager = symbol;
+                    msg = String::new();
+                },
+                None => msg = String::from("No such symbol."),
+            }
+        } else if buff == "s" {
+            let stake = wheel.base.current_bet;
+            match common_state.try_bet(&mut wheel.base, stake, Instant::now()) {
+                Ok(()) => {
+                    wheel.state = WheelState::InBet;
+                    return Some(WheelState::InBet);
+                },
+                Err(err) => msg = err.to_string(),
+            }
+        } else if buff == "q" {
+            return None;
+        } else {
+            msg = match amount {
+                Err(err) if looks_like_money(&buff) => err,
+                _ => String::from("Invalid Command."),
+            };
+        }
+        stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
+    }
+}
+
+/// # In Bet
+/// 
+/// Spins the wheel, and lets the player nudge the pointer with entropy before it
+/// stops.
+pub fn in_bet<R: Rng>(common_state: &mut CommonState, wheel: &mut MoneyWheel,
+rng: &mut R) -> Option<WheelState> {
+    stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
+    // The stake was taken when the bet was placed.
+    let stake = wheel.base.current_bet;
+    wheel.spin(rng);
+    let mut msg = String::new();
+    loop {
+        stdout().execute(Print("\t\t!!!Money Wheel!!!\nCommands: < / > -> Nudge Back/Forward | L -> Let it Stop | Q -> Walk Away (stake is lost)\n")).unwrap();
+        stdout().execute(Print(format!("Money: {}\tEntropy: {} b\tSuspicion: {}\n", common_state.money, common_state.entropy, wheel.base.suspicion))).unwrap();
+        stdout().execute(Print(format!("Bet: {} on {}\tNudged: {:.2} b\n", stake,
+            WHEEL_SYMBOLS[wheel.wager].name, wheel.nudged))).unwrap();
+        print_pointer(wheel);
+        stdout().execute(Print(format!("Nudge Back: {:.2} b\tNudge Forward: {:.2} b\n",
+            nudge_price(common_state, wheel, false), nudge_price(common_state, wheel, true)))).unwrap();
+        stdout().execute(Print(format!("{}\n", msg))).unwrap();
+        let mut buff = String::new();
+        stdin().read_line(&mut buff).unwrap();
+        buff = buff.trim().to_lowercase();
+        if buff == "l" {
+            break;
+        } else if buff == "q" {
+            // Walking away from a spinning wheel.
+            if common_state.leave_bet(&mut wheel.base).is_some() {
+                wheel.state = WheelState::Hold;
+                return Some(WheelState::Hold);
+            }
+            break;
+        } else if buff == "<" || buff == ">" {
+            let forward = buff == ">";
+            let cost = wheel.nudge_cost(forward).unwrap_or(0.0);
+            msg = match common_state.spend_manipulation(Manipulation::Selection, cost,
+                "Money Wheel nudge", rng) {
+                Ok(made) => {
+                    if made.took_effect() {
+                        wheel.nudge(forward);
+                    }
+                    wheel.base.add_suspicion(made.suspicion_spike());
+                    made.malfunction.map_or(String::new(), |m| m.to_string())
+                },
+                Err(err) => err.to_string(),
+            };
+        } else {
+            msg = String::from("Invalid Command.");
+        }
+        stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
+    }
+    let forced = wheel.nudged > 0.0;
+    if forced {
+        // Everyone watched the wheel creep along.
+        wheel.base.add_suspicion(wheel.nudged);
+    } else {
+        common_state.harvest(wheel.result_bits());
+    }
+    // finalize financial gains.
+    let won = wheel.result() == Some(wheel.wager);
+    let result = BetResult { won,
+        payout: if won { WHEEL_SYMBOLS[wheel.wager].payout } else { 0.0 },
+        win_chance: MoneyWheel::symbol_chance(wheel.wager),
+        expected_return: MoneyWheel::expected_return(wheel.wager) };
+    common_state.settle_bet(&mut wheel.base, result);
+    if forced && wheel.base.kickout_roll(rng, Instant::now()) {
+        common_state.autosave.request(AutosaveEvent::Kickout);
+    }
+    wheel.state = WheelState::Hold;
+    Some(WheelState::Hold)
+}
+
+/// What a nudge costs the player, after the machine's discount.
+fn nudge_price(common_state: &CommonState, wheel: &MoneyWheel, forward: bool) -> f64 {
+    common_state.machine.entropy_cost(Manipulation::Selection,
+        wheel.nudge_cost(forward).unwrap_or(0.0))
+}